@@ -0,0 +1,29 @@
+#![no_main]
+
+use std::collections::HashMap;
+
+use libfuzzer_sys::fuzz_target;
+use sling::model::{FailureReb, SuccessReb};
+use sling::stats::{failure_stats, success_stats};
+
+// Mirrors the split used by `success_stats`/`failure_stats` callers in `rpc_sling.rs`: a
+// caller-supplied time window plus the two lookup maps those functions use only to decorate
+// the top-5 channel-partner entries with an alias, which a fuzz run never needs populated.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 8 {
+        return;
+    }
+    let (window_bytes, rest) = data.split_at(8);
+    let time_window = u64::from_le_bytes(window_bytes.try_into().unwrap());
+    let alias_map = HashMap::new();
+    let peer_channels = HashMap::new();
+
+    if let Ok(successes) = serde_json::from_slice::<Vec<SuccessReb>>(rest) {
+        // `success_stats` must never panic on attacker- or corruption-controlled input; a `None`
+        // return (e.g. an empty or all-filtered-out input) is a valid outcome, not a bug.
+        let _ = success_stats(successes, time_window, &alias_map, &peer_channels);
+    }
+    if let Ok(failures) = serde_json::from_slice::<Vec<FailureReb>>(rest) {
+        let _ = failure_stats(failures, time_window, &alias_map, &peer_channels);
+    }
+});
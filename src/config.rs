@@ -10,22 +10,54 @@ use serde_json::json;
 
 use crate::{
     at_or_above_version,
-    model::PluginState,
+    model::{OptionSource, PluginState},
+    util::now_secs,
     Config,
+    OPT_ASKRENE_PUBLISH_ENABLED,
+    OPT_ASKRENE_PUBLISH_LAYER,
+    OPT_BACKOFF_BASE_SECS,
+    OPT_BACKOFF_MAX_SECS,
     OPT_CANDIDATES_MIN_AGE,
+    OPT_CANDIDATE_FEE_WEIGHT,
+    OPT_COORDINATE_REBALANCES,
+    OPT_COORD_NEGOTIATION_TIMEOUT_SECS,
     OPT_DEPLETEUPTOAMOUNT,
     OPT_DEPLETEUPTOPERCENT,
+    OPT_DIJKSTRA_BIDIRECTIONAL,
+    OPT_FUNDING_VERIFICATION_BATCH_SIZE,
+    OPT_FUNDING_VERIFICATION_INTERVAL_SECS,
+    OPT_GRAPH_SNAPSHOT_INTERVAL,
+    OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS,
     OPT_INFORM_LAYERS,
+    OPT_JOB_RETRY_BASE_SECS,
+    OPT_JOB_RETRY_MAX_ATTEMPTS,
+    OPT_JOB_RETRY_MAX_SECS,
+    OPT_LIQUIDITY_COMPACT_INTERVAL,
+    OPT_LIQUIDITY_HALFLIFE,
+    OPT_LIQUIDITY_MAX_AGE,
+    OPT_LIQUIDITY_PENALTY_MULTIPLIER,
+    OPT_LIQUIDITY_PROBABILISTIC_SCORING,
     OPT_MAXHOPS,
     OPT_MAX_HTLC_COUNT,
+    OPT_METRICS_BIND,
+    OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY,
     OPT_PARALLELJOBS,
+    OPT_PROBE_ENABLED,
+    OPT_PROBE_INTERVAL_SECS,
     OPT_REFRESH_ALIASMAP_INTERVAL,
+    OPT_REQUIRED_NODE_FEATURE_BIT,
     OPT_RESET_LIQUIDITY_INTERVAL,
+    OPT_RGS_INTERVAL_SECS,
+    OPT_RGS_URL,
+    OPT_STALE_CHANNEL_HORIZON_SECS,
     OPT_STATS_DELETE_FAILURES_AGE,
     OPT_STATS_DELETE_FAILURES_SIZE,
     OPT_STATS_DELETE_SUCCESSES_AGE,
     OPT_STATS_DELETE_SUCCESSES_SIZE,
     OPT_TIMEOUTPAY,
+    OPT_TIMEOUT_ROUTE_SEARCH,
+    OPT_TRANQUILITY,
+    OPT_VERIFY_CHANNEL_FUNDING,
 };
 
 pub async fn setconfig_callback(
@@ -58,6 +90,7 @@ pub async fn setconfig_callback(
             data: None
         }))
     })?;
+    drop(config);
 
     plugin.set_option_str(name, opt_value).map_err(|e| {
         anyhow!(json!(RpcError {
@@ -67,33 +100,92 @@ pub async fn setconfig_callback(
         }))
     })?;
 
+    if let Some(spec) = OPTION_SPECS.iter().find(|spec| spec.name == name) {
+        plugin
+            .state()
+            .option_set_at
+            .lock()
+            .insert(spec.name, (OptionSource::Runtime, now_secs()));
+    }
+
     Ok(json!({}))
 }
 
+/// Options whose values are `options::Value::StringArray` rather than a scalar. `parse_option`
+/// consults this to decide how to interpret the raw value; `check_option` matches
+/// `OPT_INFORM_LAYERS` by name.
+const STRING_ARRAY_OPTIONS: &[&str] = &[OPT_INFORM_LAYERS];
+
 fn parse_option(name: &str, value: &serde_json::Value) -> Result<options::Value, Error> {
     match name {
-        n if n.eq(OPT_DEPLETEUPTOPERCENT) => {
+        n if n.eq(OPT_DEPLETEUPTOPERCENT)
+            || n.eq(OPT_TRANQUILITY)
+            || n.eq(OPT_CANDIDATE_FEE_WEIGHT) =>
+        {
             if value.is_string() {
                 Ok(options::Value::String(value.as_str().unwrap().to_owned()))
             } else {
                 Err(anyhow!("{} is not a valid string!", name))
             }
         }
-        // n if n.eq(OPT_INFORM_LAYERS) => {
-        //     if let Some(layers) = value.as_array() {
-        //         let mut string_array = Vec::new();
-        //         for layer in layers.iter() {
-        //             if layer.is_string() {
-        //                 string_array.push(layer.as_str().unwrap().to_owned());
-        //             } else {
-        //                 return Err(anyhow!("{} is not a valid string!", layer));
-        //             }
-        //         }
-        //         Ok(options::Value::StringArray(string_array))
-        //     } else {
-        //         Err(anyhow!("{} is not a valid string array!", name))
-        //     }
-        // }
+        // These accept either a bare integer (in the option's historical base unit) or a
+        // duration string like `"30m"`/`"12h"`/`"7d"`/`"2w"`, so we keep them as a raw string
+        // here and let `check_option`'s `parse_duration_secs` decide which one it got.
+        n if n.eq(OPT_REFRESH_ALIASMAP_INTERVAL)
+            || n.eq(OPT_RESET_LIQUIDITY_INTERVAL)
+            || n.eq(OPT_STATS_DELETE_FAILURES_AGE)
+            || n.eq(OPT_STATS_DELETE_SUCCESSES_AGE) =>
+        {
+            if let Some(n_i64) = value.as_i64() {
+                Ok(options::Value::String(n_i64.to_string()))
+            } else if let Some(n_str) = value.as_str() {
+                Ok(options::Value::String(n_str.to_owned()))
+            } else {
+                Err(anyhow!("{} is not a valid duration!", name))
+            }
+        }
+        // A JSON array is an explicit full list (as CLN hands us repeated `--opt=a --opt=b`
+        // startup args): always replaces `config.inform_layers`. A plain/comma-separated string
+        // is also a full replacement, unless prefixed with `+`, in which case `check_option`
+        // adds the listed layers to the existing ones instead of overwriting them; we carry that
+        // intent through by leaving the `+` attached to the first parsed element.
+        n if STRING_ARRAY_OPTIONS.contains(&n) => {
+            let parts = if let Some(layers) = value.as_array() {
+                let mut parts = Vec::with_capacity(layers.len());
+                for layer in layers.iter() {
+                    let layer = layer
+                        .as_str()
+                        .ok_or_else(|| anyhow!("{} array elements must be strings!", name))?
+                        .trim();
+                    if layer.is_empty() {
+                        return Err(anyhow!("{} does not accept empty strings!", name));
+                    }
+                    parts.push(layer.to_owned());
+                }
+                parts
+            } else if let Some(s) = value.as_str() {
+                let trimmed = s.trim();
+                let append = trimmed.starts_with('+');
+                let body = trimmed.strip_prefix('+').unwrap_or(trimmed);
+                let mut parts: Vec<String> = Vec::new();
+                for part in body.split(',') {
+                    let part = part.trim();
+                    if part.is_empty() {
+                        return Err(anyhow!("{} does not accept empty strings!", name));
+                    }
+                    parts.push(part.to_owned());
+                }
+                if append {
+                    if let Some(first) = parts.first_mut() {
+                        *first = format!("+{first}");
+                    }
+                }
+                parts
+            } else {
+                return Err(anyhow!("{} is not a valid string array!", name));
+            };
+            Ok(options::Value::StringArray(parts))
+        }
         _ => {
             if let Some(n_i64) = value.as_i64() {
                 return Ok(options::Value::Integer(n_i64));
@@ -107,6 +199,35 @@ fn parse_option(name: &str, value: &serde_json::Value) -> Result<options::Value,
     }
 }
 
+/// Parses a human-readable duration like `"30m"`, `"12h"`, `"7d"`, `"2w"` into seconds, or a
+/// bare number (interpreted as `default_unit_secs`, the option's own historical base unit, for
+/// backwards compatibility) into seconds. Recognized suffixes: `s`=1, `m`=60, `h`=3600,
+/// `d`=86400, `w`=604800. Uses `checked_mul` so an absurdly large input errors instead of
+/// silently wrapping.
+pub(crate) fn parse_duration_secs(s: &str, default_unit_secs: u64) -> Result<u64, Error> {
+    let trimmed = s.trim();
+    let (digits, factor) = [
+        ("w", 604_800),
+        ("d", 86_400),
+        ("h", 3_600),
+        ("m", 60),
+        ("s", 1),
+    ]
+    .iter()
+    .find_map(|(suffix, factor)| trimmed.strip_suffix(suffix).map(|d| (d, *factor)))
+    .unwrap_or((trimmed, default_unit_secs));
+
+    let amount: u64 = digits.parse().map_err(|_| {
+        anyhow!(
+            "`{}` is not a valid duration (expected a number optionally suffixed with s/m/h/d/w)",
+            s
+        )
+    })?;
+    amount
+        .checked_mul(factor)
+        .ok_or_else(|| anyhow!("`{}` overflows when converted to seconds", s))
+}
+
 fn validate_u64_input(
     value: u64,
     var_name: &str,
@@ -140,7 +261,7 @@ fn is_valid_hour_timestamp(val: u64) -> bool {
     Utc::now().timestamp() as u64 > val
 }
 
-fn options_value_to_u64(
+pub(crate) fn options_value_to_u64(
     name: &str,
     value: i64,
     gteq: u64,
@@ -157,6 +278,217 @@ fn options_value_to_u64(
     }
 }
 
+/// Inclusive lower and upper bound for a numeric option, looked up by name in [`OPTION_SPECS`].
+///
+/// Replaces the old pattern of every `check_option` arm re-implementing its own
+/// `options_value_to_u64(...)` + `try_from(...)` dance with only a lower bound: this gives every
+/// numeric option an upper bound too (`u64::MAX` where none applies), enforced with the same
+/// error text everywhere.
+pub(crate) struct OptionSpec {
+    pub(crate) name: &'static str,
+    pub(crate) min: u64,
+    pub(crate) max: u64,
+    /// The option's built-in default, i.e. the literal it's assigned in `Config::new`. Lets
+    /// `sling-listconfigs` report a default without needing a second table to go stale against.
+    pub(crate) default: u64,
+}
+
+/// Bounds for every numeric option that doesn't need its own bespoke validation (floats, durations,
+/// booleans, strings, etc. are still special-cased in `check_option`). `max` is `u64::MAX` for
+/// options where we don't yet have a meaningful ceiling to enforce. Also doubles as the backing
+/// table for `sling-listconfigs`, see `crate::rpc_sling::slinglistconfigs`.
+pub(crate) const OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec {
+        name: OPT_LIQUIDITY_COMPACT_INTERVAL,
+        min: 10,
+        max: u64::MAX,
+        default: 3_600,
+    },
+    OptionSpec {
+        name: OPT_GRAPH_SNAPSHOT_INTERVAL,
+        min: 10,
+        max: u64::MAX,
+        default: 900,
+    },
+    OptionSpec {
+        name: OPT_DEPLETEUPTOAMOUNT,
+        min: 0,
+        max: u64::MAX,
+        default: 2_000_000_000,
+    },
+    // A legacy fixed-size BOLT #4 onion holds at most 20 hops, so maxhops is capped there.
+    OptionSpec {
+        name: OPT_MAXHOPS,
+        min: 2,
+        max: 20,
+        default: 8,
+    },
+    OptionSpec {
+        name: OPT_CANDIDATES_MIN_AGE,
+        min: 0,
+        max: u64::MAX,
+        default: 0,
+    },
+    OptionSpec {
+        name: OPT_PARALLELJOBS,
+        min: 1,
+        max: 100,
+        default: 1,
+    },
+    OptionSpec {
+        name: OPT_TIMEOUT_ROUTE_SEARCH,
+        min: 1,
+        max: u64::MAX,
+        default: 30,
+    },
+    OptionSpec {
+        name: OPT_TIMEOUTPAY,
+        min: 1,
+        max: u64::MAX,
+        default: 120,
+    },
+    // BOLT #2's `max_accepted_htlcs` hard-caps a channel at 483 pending HTLCs.
+    OptionSpec {
+        name: OPT_MAX_HTLC_COUNT,
+        min: 1,
+        max: 483,
+        default: 5,
+    },
+    OptionSpec {
+        name: OPT_STATS_DELETE_FAILURES_SIZE,
+        min: 0,
+        max: u64::MAX,
+        default: 10_000,
+    },
+    OptionSpec {
+        name: OPT_STATS_DELETE_SUCCESSES_SIZE,
+        min: 0,
+        max: u64::MAX,
+        default: 10_000,
+    },
+    OptionSpec {
+        name: OPT_LIQUIDITY_HALFLIFE,
+        min: 1,
+        max: u64::MAX,
+        default: 43_200,
+    },
+    OptionSpec {
+        name: OPT_LIQUIDITY_MAX_AGE,
+        min: 0,
+        max: u64::MAX,
+        default: 0,
+    },
+    OptionSpec {
+        name: OPT_LIQUIDITY_PENALTY_MULTIPLIER,
+        min: 0,
+        max: u64::MAX,
+        default: 200,
+    },
+    OptionSpec {
+        name: OPT_COORD_NEGOTIATION_TIMEOUT_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 5,
+    },
+    OptionSpec {
+        name: OPT_BACKOFF_BASE_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 30,
+    },
+    OptionSpec {
+        name: OPT_BACKOFF_MAX_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 3_600,
+    },
+    OptionSpec {
+        name: OPT_JOB_RETRY_BASE_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 60,
+    },
+    OptionSpec {
+        name: OPT_JOB_RETRY_MAX_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 3_600,
+    },
+    OptionSpec {
+        name: OPT_JOB_RETRY_MAX_ATTEMPTS,
+        min: 1,
+        max: u64::MAX,
+        default: 10,
+    },
+    OptionSpec {
+        name: OPT_PROBE_INTERVAL_SECS,
+        min: 60,
+        max: u64::MAX,
+        default: 1_800,
+    },
+    OptionSpec {
+        name: OPT_RGS_INTERVAL_SECS,
+        min: 60,
+        max: u64::MAX,
+        default: 3_600,
+    },
+    OptionSpec {
+        name: OPT_STALE_CHANNEL_HORIZON_SECS,
+        min: 3_600,
+        max: u64::MAX,
+        default: 60 * 60 * 24 * 14,
+    },
+    OptionSpec {
+        name: OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS,
+        min: 60,
+        max: u64::MAX,
+        default: 60 * 60 * 24,
+    },
+    OptionSpec {
+        name: OPT_FUNDING_VERIFICATION_BATCH_SIZE,
+        min: 1,
+        max: u64::MAX,
+        default: 25,
+    },
+    OptionSpec {
+        name: OPT_FUNDING_VERIFICATION_INTERVAL_SECS,
+        min: 1,
+        max: u64::MAX,
+        default: 60,
+    },
+];
+
+pub(crate) fn spec_for(name: &str) -> &'static OptionSpec {
+    OPTION_SPECS
+        .iter()
+        .find(|spec| spec.name == name)
+        .unwrap_or_else(|| panic!("no OptionSpec registered for option `{name}`"))
+}
+
+/// Looks up `name`'s bounds in [`OPTION_SPECS`] and rejects `value` if it falls outside them,
+/// naming both bounds in the error so `setconfig` and startup option parsing give identical
+/// feedback.
+pub(crate) fn validate_range(name: &str, value: i64, spec: &OptionSpec) -> Result<u64, Error> {
+    if value < 0 {
+        return Err(anyhow!(
+            "{} needs to be a positive number and not `{}`.",
+            name,
+            value
+        ));
+    }
+    let value = value as u64;
+    if value < spec.min || value > spec.max {
+        return Err(anyhow!(
+            "{} needs to be between {} and {}, not `{}`.",
+            name,
+            spec.min,
+            spec.max,
+            value
+        ));
+    }
+    Ok(value)
+}
+
 pub async fn get_startup_options(
     plugin: &ConfiguredPlugin<PluginState, tokio::io::Stdin, tokio::io::Stdout>,
     state: PluginState,
@@ -178,68 +510,256 @@ pub async fn get_startup_options(
     config.cltv_delta = cltv_delta;
     config.at_or_above_24_11 = at_or_above_version(&config.version, "24.11")?;
 
+    // Only `OPTION_SPECS` entries have a meaningful "set_at" to report via `sling-listconfigs`;
+    // everything else (floats, strings, durations, ...) stays untracked.
+    let record_startup = |name: &'static str| {
+        if OPTION_SPECS.iter().any(|spec| spec.name == name) {
+            state
+                .option_set_at
+                .lock()
+                .insert(name, (OptionSource::Startup, now_secs()));
+        }
+    };
+
     if let Some(rai) = plugin.option_str(OPT_REFRESH_ALIASMAP_INTERVAL)? {
         check_option(&mut config, OPT_REFRESH_ALIASMAP_INTERVAL, &rai)?;
     };
     if let Some(rli) = plugin.option_str(OPT_RESET_LIQUIDITY_INTERVAL)? {
         check_option(&mut config, OPT_RESET_LIQUIDITY_INTERVAL, &rli)?;
     };
+    if let Some(lci) = plugin.option_str(OPT_LIQUIDITY_COMPACT_INTERVAL)? {
+        check_option(&mut config, OPT_LIQUIDITY_COMPACT_INTERVAL, &lci)?;
+        record_startup(OPT_LIQUIDITY_COMPACT_INTERVAL);
+    };
+    if let Some(gsi) = plugin.option_str(OPT_GRAPH_SNAPSHOT_INTERVAL)? {
+        check_option(&mut config, OPT_GRAPH_SNAPSHOT_INTERVAL, &gsi)?;
+        record_startup(OPT_GRAPH_SNAPSHOT_INTERVAL);
+    };
     if let Some(dup) = plugin.option_str(OPT_DEPLETEUPTOPERCENT)? {
         check_option(&mut config, OPT_DEPLETEUPTOPERCENT, &dup)?;
     };
     if let Some(dua) = plugin.option_str(OPT_DEPLETEUPTOAMOUNT)? {
         check_option(&mut config, OPT_DEPLETEUPTOAMOUNT, &dua)?;
+        record_startup(OPT_DEPLETEUPTOAMOUNT);
     };
     if let Some(mhops) = plugin.option_str(OPT_MAXHOPS)? {
         check_option(&mut config, OPT_MAXHOPS, &mhops)?;
+        record_startup(OPT_MAXHOPS);
     };
     if let Some(cma) = plugin.option_str(OPT_CANDIDATES_MIN_AGE)? {
         check_option(&mut config, OPT_CANDIDATES_MIN_AGE, &cma)?;
+        record_startup(OPT_CANDIDATES_MIN_AGE);
     };
     if let Some(pj) = plugin.option_str(OPT_PARALLELJOBS)? {
         check_option(&mut config, OPT_PARALLELJOBS, &pj)?;
+        record_startup(OPT_PARALLELJOBS);
     };
     if let Some(tp) = plugin.option_str(OPT_TIMEOUTPAY)? {
         check_option(&mut config, OPT_TIMEOUTPAY, &tp)?;
+        record_startup(OPT_TIMEOUTPAY);
+    };
+    if let Some(trs) = plugin.option_str(OPT_TIMEOUT_ROUTE_SEARCH)? {
+        check_option(&mut config, OPT_TIMEOUT_ROUTE_SEARCH, &trs)?;
+        record_startup(OPT_TIMEOUT_ROUTE_SEARCH);
     };
     if let Some(mhc) = plugin.option_str(OPT_MAX_HTLC_COUNT)? {
         check_option(&mut config, OPT_MAX_HTLC_COUNT, &mhc)?;
+        record_startup(OPT_MAX_HTLC_COUNT);
     };
     if let Some(sdfa) = plugin.option_str(OPT_STATS_DELETE_FAILURES_AGE)? {
         check_option(&mut config, OPT_STATS_DELETE_FAILURES_AGE, &sdfa)?;
     };
     if let Some(sdfs) = plugin.option_str(OPT_STATS_DELETE_FAILURES_SIZE)? {
         check_option(&mut config, OPT_STATS_DELETE_FAILURES_SIZE, &sdfs)?;
+        record_startup(OPT_STATS_DELETE_FAILURES_SIZE);
     };
     if let Some(sdsa) = plugin.option_str(OPT_STATS_DELETE_SUCCESSES_AGE)? {
         check_option(&mut config, OPT_STATS_DELETE_SUCCESSES_AGE, &sdsa)?;
     };
     if let Some(sdss) = plugin.option_str(OPT_STATS_DELETE_SUCCESSES_SIZE)? {
         check_option(&mut config, OPT_STATS_DELETE_SUCCESSES_SIZE, &sdss)?;
+        record_startup(OPT_STATS_DELETE_SUCCESSES_SIZE);
     };
     if let Some(layers) = plugin.option_str(OPT_INFORM_LAYERS)? {
         check_option(&mut config, OPT_INFORM_LAYERS, &layers)?;
     }
+    if let Some(lhl) = plugin.option_str(OPT_LIQUIDITY_HALFLIFE)? {
+        check_option(&mut config, OPT_LIQUIDITY_HALFLIFE, &lhl)?;
+        record_startup(OPT_LIQUIDITY_HALFLIFE);
+    };
+    if let Some(lma) = plugin.option_str(OPT_LIQUIDITY_MAX_AGE)? {
+        check_option(&mut config, OPT_LIQUIDITY_MAX_AGE, &lma)?;
+        record_startup(OPT_LIQUIDITY_MAX_AGE);
+    };
+    if let Some(lpm) = plugin.option_str(OPT_LIQUIDITY_PENALTY_MULTIPLIER)? {
+        check_option(&mut config, OPT_LIQUIDITY_PENALTY_MULTIPLIER, &lpm)?;
+        record_startup(OPT_LIQUIDITY_PENALTY_MULTIPLIER);
+    };
+    if let Some(lps) = plugin.option_str(OPT_LIQUIDITY_PROBABILISTIC_SCORING)? {
+        check_option(&mut config, OPT_LIQUIDITY_PROBABILISTIC_SCORING, &lps)?;
+    };
+    if let Some(cr) = plugin.option_str(OPT_COORDINATE_REBALANCES)? {
+        check_option(&mut config, OPT_COORDINATE_REBALANCES, &cr)?;
+    };
+    if let Some(db) = plugin.option_str(OPT_DIJKSTRA_BIDIRECTIONAL)? {
+        check_option(&mut config, OPT_DIJKSTRA_BIDIRECTIONAL, &db)?;
+    };
+    if let Some(cnt) = plugin.option_str(OPT_COORD_NEGOTIATION_TIMEOUT_SECS)? {
+        check_option(&mut config, OPT_COORD_NEGOTIATION_TIMEOUT_SECS, &cnt)?;
+        record_startup(OPT_COORD_NEGOTIATION_TIMEOUT_SECS);
+    };
+    if let Some(mcsp) = plugin.option_str(OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY)? {
+        check_option(&mut config, OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY, &mcsp)?;
+    };
+    if let Some(cfw) = plugin.option_str(OPT_CANDIDATE_FEE_WEIGHT)? {
+        check_option(&mut config, OPT_CANDIDATE_FEE_WEIGHT, &cfw)?;
+    };
+    if let Some(bbs) = plugin.option_str(OPT_BACKOFF_BASE_SECS)? {
+        check_option(&mut config, OPT_BACKOFF_BASE_SECS, &bbs)?;
+        record_startup(OPT_BACKOFF_BASE_SECS);
+    };
+    if let Some(bms) = plugin.option_str(OPT_BACKOFF_MAX_SECS)? {
+        check_option(&mut config, OPT_BACKOFF_MAX_SECS, &bms)?;
+        record_startup(OPT_BACKOFF_MAX_SECS);
+    };
+    if let Some(jrbs) = plugin.option_str(OPT_JOB_RETRY_BASE_SECS)? {
+        check_option(&mut config, OPT_JOB_RETRY_BASE_SECS, &jrbs)?;
+        record_startup(OPT_JOB_RETRY_BASE_SECS);
+    };
+    if let Some(jrms) = plugin.option_str(OPT_JOB_RETRY_MAX_SECS)? {
+        check_option(&mut config, OPT_JOB_RETRY_MAX_SECS, &jrms)?;
+        record_startup(OPT_JOB_RETRY_MAX_SECS);
+    };
+    if let Some(jrma) = plugin.option_str(OPT_JOB_RETRY_MAX_ATTEMPTS)? {
+        check_option(&mut config, OPT_JOB_RETRY_MAX_ATTEMPTS, &jrma)?;
+        record_startup(OPT_JOB_RETRY_MAX_ATTEMPTS);
+    };
+    if let Some(tr) = plugin.option_str(OPT_TRANQUILITY)? {
+        check_option(&mut config, OPT_TRANQUILITY, &tr)?;
+    };
+    if let Some(mb) = plugin.option_str(OPT_METRICS_BIND)? {
+        check_option(&mut config, OPT_METRICS_BIND, &mb)?;
+    };
+    if let Some(pe) = plugin.option_str(OPT_PROBE_ENABLED)? {
+        check_option(&mut config, OPT_PROBE_ENABLED, &pe)?;
+    };
+    if let Some(pis) = plugin.option_str(OPT_PROBE_INTERVAL_SECS)? {
+        check_option(&mut config, OPT_PROBE_INTERVAL_SECS, &pis)?;
+        record_startup(OPT_PROBE_INTERVAL_SECS);
+    };
+    if let Some(ape) = plugin.option_str(OPT_ASKRENE_PUBLISH_ENABLED)? {
+        check_option(&mut config, OPT_ASKRENE_PUBLISH_ENABLED, &ape)?;
+    };
+    if let Some(apl) = plugin.option_str(OPT_ASKRENE_PUBLISH_LAYER)? {
+        check_option(&mut config, OPT_ASKRENE_PUBLISH_LAYER, &apl)?;
+    };
+    if let Some(ru) = plugin.option_str(OPT_RGS_URL)? {
+        check_option(&mut config, OPT_RGS_URL, &ru)?;
+    };
+    if let Some(ris) = plugin.option_str(OPT_RGS_INTERVAL_SECS)? {
+        check_option(&mut config, OPT_RGS_INTERVAL_SECS, &ris)?;
+        record_startup(OPT_RGS_INTERVAL_SECS);
+    };
+    if let Some(sch) = plugin.option_str(OPT_STALE_CHANNEL_HORIZON_SECS)? {
+        check_option(&mut config, OPT_STALE_CHANNEL_HORIZON_SECS, &sch)?;
+        record_startup(OPT_STALE_CHANNEL_HORIZON_SECS);
+    };
+    if let Some(icts) = plugin.option_str(OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS)? {
+        check_option(&mut config, OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS, &icts)?;
+        record_startup(OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS);
+    };
+    if let Some(vcf) = plugin.option_str(OPT_VERIFY_CHANNEL_FUNDING)? {
+        check_option(&mut config, OPT_VERIFY_CHANNEL_FUNDING, &vcf)?;
+    };
+    if let Some(fvbs) = plugin.option_str(OPT_FUNDING_VERIFICATION_BATCH_SIZE)? {
+        check_option(&mut config, OPT_FUNDING_VERIFICATION_BATCH_SIZE, &fvbs)?;
+        record_startup(OPT_FUNDING_VERIFICATION_BATCH_SIZE);
+    };
+    if let Some(fvis) = plugin.option_str(OPT_FUNDING_VERIFICATION_INTERVAL_SECS)? {
+        check_option(&mut config, OPT_FUNDING_VERIFICATION_INTERVAL_SECS, &fvis)?;
+        record_startup(OPT_FUNDING_VERIFICATION_INTERVAL_SECS);
+    };
+    if let Some(rnfb) = plugin.option_str(OPT_REQUIRED_NODE_FEATURE_BIT)? {
+        check_option(&mut config, OPT_REQUIRED_NODE_FEATURE_BIT, &rnfb)?;
+    };
 
     Ok(())
 }
 
+/// Either replaces `current` wholesale with `layers`, or (when `append` is set) grows it with
+/// any `layers` entries it doesn't already contain, preserving the existing order.
+pub(crate) fn apply_inform_layers(current: &mut Vec<String>, layers: Vec<String>, append: bool) {
+    if append {
+        for layer in layers {
+            if !current.contains(&layer) {
+                current.push(layer);
+            }
+        }
+    } else {
+        *current = layers;
+    }
+}
+
+/// The live value behind one of `OPTION_SPECS`'s option names, for `sling-listconfigs`. Returns
+/// `None` for any name not covered by that table.
+pub(crate) fn current_numeric_value(config: &Config, name: &str) -> Option<u64> {
+    Some(match name {
+        n if n.eq(OPT_LIQUIDITY_COMPACT_INTERVAL) => config.liquidity_compact_interval,
+        n if n.eq(OPT_GRAPH_SNAPSHOT_INTERVAL) => config.graph_snapshot_interval,
+        n if n.eq(OPT_DEPLETEUPTOAMOUNT) => config.depleteuptoamount,
+        n if n.eq(OPT_MAXHOPS) => u64::from(config.maxhops),
+        n if n.eq(OPT_CANDIDATES_MIN_AGE) => u64::from(config.candidates_min_age),
+        n if n.eq(OPT_PARALLELJOBS) => u64::from(config.paralleljobs),
+        n if n.eq(OPT_TIMEOUT_ROUTE_SEARCH) => config.timeout_route_search,
+        n if n.eq(OPT_TIMEOUTPAY) => u64::from(config.timeoutpay),
+        n if n.eq(OPT_MAX_HTLC_COUNT) => config.max_htlc_count,
+        n if n.eq(OPT_STATS_DELETE_FAILURES_SIZE) => config.stats_delete_failures_size,
+        n if n.eq(OPT_STATS_DELETE_SUCCESSES_SIZE) => config.stats_delete_successes_size,
+        n if n.eq(OPT_LIQUIDITY_HALFLIFE) => config.liquidity_halflife,
+        n if n.eq(OPT_LIQUIDITY_MAX_AGE) => config.liquidity_max_age,
+        n if n.eq(OPT_LIQUIDITY_PENALTY_MULTIPLIER) => config.liquidity_penalty_multiplier,
+        n if n.eq(OPT_COORD_NEGOTIATION_TIMEOUT_SECS) => config.coord_negotiation_timeout_secs,
+        n if n.eq(OPT_BACKOFF_BASE_SECS) => config.backoff_base_secs,
+        n if n.eq(OPT_BACKOFF_MAX_SECS) => config.backoff_max_secs,
+        n if n.eq(OPT_JOB_RETRY_BASE_SECS) => config.job_retry_base_secs,
+        n if n.eq(OPT_JOB_RETRY_MAX_SECS) => config.job_retry_max_secs,
+        n if n.eq(OPT_JOB_RETRY_MAX_ATTEMPTS) => config.job_retry_max_attempts,
+        n if n.eq(OPT_PROBE_INTERVAL_SECS) => config.probe_interval_secs,
+        n if n.eq(OPT_RGS_INTERVAL_SECS) => config.rgs_interval_secs,
+        n if n.eq(OPT_STALE_CHANNEL_HORIZON_SECS) => config.stale_channel_horizon_secs,
+        n if n.eq(OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS) => config.incomplete_channel_timeout_secs,
+        n if n.eq(OPT_FUNDING_VERIFICATION_BATCH_SIZE) => config.funding_verification_batch_size,
+        n if n.eq(OPT_FUNDING_VERIFICATION_INTERVAL_SECS) => {
+            config.funding_verification_interval_secs
+        }
+        _ => return None,
+    })
+}
+
 fn check_option(config: &mut Config, name: &str, value: &options::Value) -> Result<(), Error> {
     match name {
         n if n.eq(OPT_REFRESH_ALIASMAP_INTERVAL) => {
-            config.refresh_aliasmap_interval = options_value_to_u64(
-                OPT_REFRESH_ALIASMAP_INTERVAL,
+            let secs = parse_duration_secs(value.as_str().unwrap(), 1)?;
+            config.refresh_aliasmap_interval =
+                validate_u64_input(secs, OPT_REFRESH_ALIASMAP_INTERVAL, 1, None)?
+        }
+        n if n.eq(OPT_RESET_LIQUIDITY_INTERVAL) => {
+            let secs = parse_duration_secs(value.as_str().unwrap(), 60)?;
+            config.reset_liquidity_interval =
+                validate_u64_input(secs, OPT_RESET_LIQUIDITY_INTERVAL, 10 * 60, None)?
+        }
+        n if n.eq(OPT_LIQUIDITY_COMPACT_INTERVAL) => {
+            config.liquidity_compact_interval = validate_range(
+                OPT_LIQUIDITY_COMPACT_INTERVAL,
                 value.as_i64().unwrap(),
-                1,
-                None,
+                spec_for(OPT_LIQUIDITY_COMPACT_INTERVAL),
             )?
         }
-        n if n.eq(OPT_RESET_LIQUIDITY_INTERVAL) => {
-            config.reset_liquidity_interval = options_value_to_u64(
-                OPT_RESET_LIQUIDITY_INTERVAL,
+        n if n.eq(OPT_GRAPH_SNAPSHOT_INTERVAL) => {
+            config.graph_snapshot_interval = validate_range(
+                OPT_GRAPH_SNAPSHOT_INTERVAL,
                 value.as_i64().unwrap(),
-                10,
-                None,
+                spec_for(OPT_GRAPH_SNAPSHOT_INTERVAL),
             )?
         }
         n if n.eq(OPT_DEPLETEUPTOPERCENT) => {
@@ -265,80 +785,302 @@ fn check_option(config: &mut Config, name: &str, value: &options::Value) -> Resu
             }
         }
         n if n.eq(OPT_DEPLETEUPTOAMOUNT) => {
-            config.depleteuptoamount =
-                options_value_to_u64(OPT_DEPLETEUPTOAMOUNT, value.as_i64().unwrap(), 0, None)?
-                    * 1000
+            config.depleteuptoamount = validate_range(
+                OPT_DEPLETEUPTOAMOUNT,
+                value.as_i64().unwrap(),
+                spec_for(OPT_DEPLETEUPTOAMOUNT),
+            )? * 1000
         }
         n if n.eq(OPT_MAXHOPS) => {
-            config.maxhops = u8::try_from(options_value_to_u64(
+            config.maxhops = u8::try_from(validate_range(
                 OPT_MAXHOPS,
                 value.as_i64().unwrap(),
-                2,
-                None,
+                spec_for(OPT_MAXHOPS),
             )?)?
         }
         n if n.eq(OPT_CANDIDATES_MIN_AGE) => {
-            config.candidates_min_age = u32::try_from(options_value_to_u64(
+            config.candidates_min_age = u32::try_from(validate_range(
                 OPT_CANDIDATES_MIN_AGE,
                 value.as_i64().unwrap(),
-                0,
-                None,
+                spec_for(OPT_CANDIDATES_MIN_AGE),
             )?)?
         }
         n if n.eq(OPT_PARALLELJOBS) => {
-            config.paralleljobs = u16::try_from(options_value_to_u64(
+            config.paralleljobs = u16::try_from(validate_range(
                 OPT_PARALLELJOBS,
                 value.as_i64().unwrap(),
-                1,
-                None,
+                spec_for(OPT_PARALLELJOBS),
             )?)?
         }
+        n if n.eq(OPT_TIMEOUT_ROUTE_SEARCH) => {
+            config.timeout_route_search = validate_range(
+                OPT_TIMEOUT_ROUTE_SEARCH,
+                value.as_i64().unwrap(),
+                spec_for(OPT_TIMEOUT_ROUTE_SEARCH),
+            )?
+        }
         n if n.eq(OPT_TIMEOUTPAY) => {
-            config.timeoutpay = u16::try_from(options_value_to_u64(
+            config.timeoutpay = u16::try_from(validate_range(
                 OPT_TIMEOUTPAY,
                 value.as_i64().unwrap(),
-                1,
-                None,
+                spec_for(OPT_TIMEOUTPAY),
             )?)?
         }
         n if n.eq(OPT_MAX_HTLC_COUNT) => {
-            config.max_htlc_count =
-                options_value_to_u64(OPT_MAX_HTLC_COUNT, value.as_i64().unwrap(), 1, None)?
-        }
-        n if n.eq(OPT_STATS_DELETE_FAILURES_AGE) => {
-            config.stats_delete_failures_age = options_value_to_u64(
-                OPT_STATS_DELETE_FAILURES_AGE,
+            config.max_htlc_count = validate_range(
+                OPT_MAX_HTLC_COUNT,
                 value.as_i64().unwrap(),
-                0,
-                Some(24 * 60 * 60),
+                spec_for(OPT_MAX_HTLC_COUNT),
             )?
         }
+        n if n.eq(OPT_STATS_DELETE_FAILURES_AGE) => {
+            let secs = parse_duration_secs(value.as_str().unwrap(), 24 * 60 * 60)?;
+            config.stats_delete_failures_age =
+                validate_u64_input(secs, OPT_STATS_DELETE_FAILURES_AGE, 0, Some(1))?
+        }
         n if n.eq(OPT_STATS_DELETE_FAILURES_SIZE) => {
-            config.stats_delete_failures_size = options_value_to_u64(
+            config.stats_delete_failures_size = validate_range(
                 OPT_STATS_DELETE_FAILURES_SIZE,
                 value.as_i64().unwrap(),
-                0,
-                None,
+                spec_for(OPT_STATS_DELETE_FAILURES_SIZE),
             )?
         }
         n if n.eq(OPT_STATS_DELETE_SUCCESSES_AGE) => {
-            config.stats_delete_successes_age = options_value_to_u64(
-                OPT_STATS_DELETE_SUCCESSES_AGE,
-                value.as_i64().unwrap(),
-                0,
-                Some(24 * 60 * 60),
-            )?
+            let secs = parse_duration_secs(value.as_str().unwrap(), 24 * 60 * 60)?;
+            config.stats_delete_successes_age =
+                validate_u64_input(secs, OPT_STATS_DELETE_SUCCESSES_AGE, 0, Some(1))?
         }
         n if n.eq(OPT_STATS_DELETE_SUCCESSES_SIZE) => {
-            config.stats_delete_successes_size = options_value_to_u64(
+            config.stats_delete_successes_size = validate_range(
                 OPT_STATS_DELETE_SUCCESSES_SIZE,
                 value.as_i64().unwrap(),
-                0,
-                None,
+                spec_for(OPT_STATS_DELETE_SUCCESSES_SIZE),
             )?
         }
         n if n.eq(OPT_INFORM_LAYERS) => {
-            config.inform_layers = value.as_str_arr().unwrap().clone();
+            let mut layers = value.as_str_arr().unwrap().clone();
+            let append = layers.first().is_some_and(|f| f.starts_with('+'));
+            if append {
+                if let Some(first) = layers.first_mut() {
+                    *first = first.trim_start_matches('+').to_owned();
+                }
+            }
+            apply_inform_layers(&mut config.inform_layers, layers, append);
+        }
+        n if n.eq(OPT_LIQUIDITY_HALFLIFE) => {
+            config.liquidity_halflife = validate_range(
+                OPT_LIQUIDITY_HALFLIFE,
+                value.as_i64().unwrap(),
+                spec_for(OPT_LIQUIDITY_HALFLIFE),
+            )?
+        }
+        n if n.eq(OPT_LIQUIDITY_MAX_AGE) => {
+            config.liquidity_max_age = validate_range(
+                OPT_LIQUIDITY_MAX_AGE,
+                value.as_i64().unwrap(),
+                spec_for(OPT_LIQUIDITY_MAX_AGE),
+            )?
+        }
+        n if n.eq(OPT_LIQUIDITY_PENALTY_MULTIPLIER) => {
+            config.liquidity_penalty_multiplier = validate_range(
+                OPT_LIQUIDITY_PENALTY_MULTIPLIER,
+                value.as_i64().unwrap(),
+                spec_for(OPT_LIQUIDITY_PENALTY_MULTIPLIER),
+            )?
+        }
+        n if n.eq(OPT_LIQUIDITY_PROBABILISTIC_SCORING) => {
+            config.liquidity_probabilistic_scoring = value.as_bool().unwrap()
+        }
+        n if n.eq(OPT_COORDINATE_REBALANCES) => {
+            config.coordinate_rebalances = value.as_bool().unwrap()
+        }
+        n if n.eq(OPT_DIJKSTRA_BIDIRECTIONAL) => {
+            config.dijkstra_bidirectional = value.as_bool().unwrap()
+        }
+        n if n.eq(OPT_COORD_NEGOTIATION_TIMEOUT_SECS) => {
+            config.coord_negotiation_timeout_secs = validate_range(
+                OPT_COORD_NEGOTIATION_TIMEOUT_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_COORD_NEGOTIATION_TIMEOUT_SECS),
+            )?
+        }
+        n if n.eq(OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY) => {
+            config.min_candidate_success_probability = match value.as_str().unwrap().parse::<f64>()
+            {
+                Ok(f) => {
+                    if (0.0..=1.0).contains(&f) {
+                        f
+                    } else {
+                        return Err(anyhow!(
+                            "Error: {} needs to be between 0 and 1, not `{}`.",
+                            OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY,
+                            f
+                        ));
+                    }
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "Error: {} could not parse a floating point for `{}`.",
+                        e,
+                        OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY,
+                    ))
+                }
+            }
+        }
+        n if n.eq(OPT_CANDIDATE_FEE_WEIGHT) => {
+            config.candidate_fee_weight = match value.as_str().unwrap().parse::<f64>() {
+                Ok(f) => {
+                    if f >= 0.0 {
+                        f
+                    } else {
+                        return Err(anyhow!(
+                            "Error: {} needs to be greater than or equal to 0, not `{}`.",
+                            OPT_CANDIDATE_FEE_WEIGHT,
+                            f
+                        ));
+                    }
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "Error: {} could not parse a floating point for `{}`.",
+                        e,
+                        OPT_CANDIDATE_FEE_WEIGHT,
+                    ))
+                }
+            }
+        }
+        n if n.eq(OPT_BACKOFF_BASE_SECS) => {
+            config.backoff_base_secs = validate_range(
+                OPT_BACKOFF_BASE_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_BACKOFF_BASE_SECS),
+            )?
+        }
+        n if n.eq(OPT_BACKOFF_MAX_SECS) => {
+            config.backoff_max_secs = validate_range(
+                OPT_BACKOFF_MAX_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_BACKOFF_MAX_SECS),
+            )?
+        }
+        n if n.eq(OPT_JOB_RETRY_BASE_SECS) => {
+            config.job_retry_base_secs = validate_range(
+                OPT_JOB_RETRY_BASE_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_JOB_RETRY_BASE_SECS),
+            )?
+        }
+        n if n.eq(OPT_JOB_RETRY_MAX_SECS) => {
+            config.job_retry_max_secs = validate_range(
+                OPT_JOB_RETRY_MAX_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_JOB_RETRY_MAX_SECS),
+            )?
+        }
+        n if n.eq(OPT_JOB_RETRY_MAX_ATTEMPTS) => {
+            config.job_retry_max_attempts = validate_range(
+                OPT_JOB_RETRY_MAX_ATTEMPTS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_JOB_RETRY_MAX_ATTEMPTS),
+            )?
+        }
+        n if n.eq(OPT_TRANQUILITY) => {
+            config.tranquility = match value.as_str().unwrap().parse::<f64>() {
+                Ok(f) => {
+                    if f >= 0.0 {
+                        f
+                    } else {
+                        return Err(anyhow!(
+                            "Error: {} needs to be greater than or equal to 0, not `{}`.",
+                            OPT_TRANQUILITY,
+                            f
+                        ));
+                    }
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "Error: {} could not parse a floating point for `{}`.",
+                        e,
+                        OPT_TRANQUILITY,
+                    ))
+                }
+            }
+        }
+        n if n.eq(OPT_METRICS_BIND) => {
+            let addr = value.as_str().unwrap().trim();
+            config.metrics_bind_addr = if addr.is_empty() {
+                None
+            } else {
+                Some(addr.parse().map_err(|e| {
+                    anyhow!(
+                        "Error: {} could not parse a socket address for `{}`: {}.",
+                        OPT_METRICS_BIND,
+                        addr,
+                        e
+                    )
+                })?)
+            };
+        }
+        n if n.eq(OPT_PROBE_ENABLED) => config.probe_enabled = value.as_bool().unwrap(),
+        n if n.eq(OPT_PROBE_INTERVAL_SECS) => {
+            config.probe_interval_secs = validate_range(
+                OPT_PROBE_INTERVAL_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_PROBE_INTERVAL_SECS),
+            )?
+        }
+        n if n.eq(OPT_ASKRENE_PUBLISH_ENABLED) => {
+            config.askrene_publish_enabled = value.as_bool().unwrap()
+        }
+        n if n.eq(OPT_ASKRENE_PUBLISH_LAYER) => {
+            config.askrene_publish_layer = value.as_str().unwrap().trim().to_string()
+        }
+        n if n.eq(OPT_RGS_URL) => config.rgs_url = value.as_str().unwrap().trim().to_string(),
+        n if n.eq(OPT_RGS_INTERVAL_SECS) => {
+            config.rgs_interval_secs = validate_range(
+                OPT_RGS_INTERVAL_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_RGS_INTERVAL_SECS),
+            )?
+        }
+        n if n.eq(OPT_STALE_CHANNEL_HORIZON_SECS) => {
+            config.stale_channel_horizon_secs = validate_range(
+                OPT_STALE_CHANNEL_HORIZON_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_STALE_CHANNEL_HORIZON_SECS),
+            )?
+        }
+        n if n.eq(OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS) => {
+            config.incomplete_channel_timeout_secs = validate_range(
+                OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS),
+            )?
+        }
+        n if n.eq(OPT_VERIFY_CHANNEL_FUNDING) => {
+            config.verify_channel_funding = value.as_bool().unwrap()
+        }
+        n if n.eq(OPT_FUNDING_VERIFICATION_BATCH_SIZE) => {
+            config.funding_verification_batch_size = validate_range(
+                OPT_FUNDING_VERIFICATION_BATCH_SIZE,
+                value.as_i64().unwrap(),
+                spec_for(OPT_FUNDING_VERIFICATION_BATCH_SIZE),
+            )?
+        }
+        n if n.eq(OPT_FUNDING_VERIFICATION_INTERVAL_SECS) => {
+            config.funding_verification_interval_secs = validate_range(
+                OPT_FUNDING_VERIFICATION_INTERVAL_SECS,
+                value.as_i64().unwrap(),
+                spec_for(OPT_FUNDING_VERIFICATION_INTERVAL_SECS),
+            )?
+        }
+        n if n.eq(OPT_REQUIRED_NODE_FEATURE_BIT) => {
+            let bit = value.as_i64().unwrap();
+            config.required_node_feature_bit = if bit < 0 {
+                None
+            } else {
+                Some(bit as u32)
+            }
         }
         _ => return Err(anyhow!("Unknown option: {}", name)),
     }
@@ -0,0 +1,247 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Error};
+use cln_plugin::Plugin;
+use cln_rpc::{
+    model::{requests::SendcustommsgRequest, Request},
+    primitives::{Amount, PublicKey, ShortChannelId},
+    ClnRpc,
+};
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sling::{Job, SatDirection};
+use tokio::{sync::Notify, time};
+
+use crate::{
+    model::{Config, CoordNegotiation, PluginState, TaskIdentifier},
+    util::is_channel_normal,
+};
+
+/// Odd so a peer that doesn't understand it ignores the message instead of closing the
+/// channel, per BOLT #1's rule for custom message types.
+const COORD_REBALANCE_REQUEST_TYPE: u16 = 45931;
+const COORD_REBALANCE_RESPONSE_TYPE: u16 = 45933;
+
+/// Maps a wire type to what kind of coordination message it carries, so `custommsg_handler`
+/// can dispatch without a growing `match` every time a new message type is composed in.
+enum CoordMsgKind {
+    Request,
+    Response,
+}
+
+const COORD_MSG_REGISTRY: &[(u16, CoordMsgKind)] = &[
+    (COORD_REBALANCE_REQUEST_TYPE, CoordMsgKind::Request),
+    (COORD_REBALANCE_RESPONSE_TYPE, CoordMsgKind::Response),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CoordRequestPayload {
+    request_id: u64,
+    chan_id: ShortChannelId,
+    direction: SatDirection,
+    amount_msat: u64,
+    maxppm: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CoordResponsePayload {
+    request_id: u64,
+    accepted: bool,
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(&mut hex, "{byte:02x}").unwrap();
+    }
+    hex
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    if hex.len() % 2 != 0 {
+        return Err(anyhow!("odd-length hex payload"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| anyhow!("invalid hex byte: {e}"))
+        })
+        .collect()
+}
+
+async fn send_coord_msg(
+    rpc_path: &PathBuf,
+    peer: PublicKey,
+    msg_type: u16,
+    payload: &impl Serialize,
+) -> Result<(), Error> {
+    let mut bytes = msg_type.to_be_bytes().to_vec();
+    bytes.extend(serde_json::to_vec(payload)?);
+
+    let mut rpc = ClnRpc::new(&rpc_path).await?;
+    rpc.call(Request::SendCustomMsg(SendcustommsgRequest {
+        node_id: peer,
+        msg: encode_hex(&bytes),
+    }))
+    .await
+    .map_err(|e| anyhow!("Error calling sendcustommsg: {:?}", e))?;
+    Ok(())
+}
+
+/// Before launching a job attempt against a directly-connected peer, ask it over a custom
+/// message whether it has matching liquidity and agrees on fee for the rebalance `job`
+/// describes. Returns `Ok(true)` if the peer accepted, didn't answer in time (most likely
+/// because it isn't running sling), or couldn't be reached at all; only an explicit nack
+/// yields `Ok(false)`, which `health_check` treats like a `PeerNotReady` tempban.
+pub async fn negotiate_rebalance(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    task_ident: &TaskIdentifier,
+    job: &Job,
+    peer: PublicKey,
+) -> Result<bool, Error> {
+    let request_id = rng().random::<u64>();
+    let request = CoordRequestPayload {
+        request_id,
+        chan_id: task_ident.get_chan_id(),
+        direction: job.sat_direction,
+        amount_msat: job.amount_msat,
+        maxppm: job.maxppm,
+    };
+
+    let notify = Arc::new(Notify::new());
+    plugin.state().coord_negotiations.lock().insert(
+        request_id,
+        CoordNegotiation {
+            accepted: None,
+            notify: notify.clone(),
+        },
+    );
+
+    if let Err(e) = send_coord_msg(
+        &config.rpc_path,
+        peer,
+        COORD_REBALANCE_REQUEST_TYPE,
+        &request,
+    )
+    .await
+    {
+        plugin.state().coord_negotiations.lock().remove(&request_id);
+        log::warn!("{task_ident}: failed to send rebalance-coordination request to {peer}: {e}");
+        return Ok(true);
+    }
+
+    let timed_out = time::timeout(
+        Duration::from_secs(config.coord_negotiation_timeout_secs),
+        notify.notified(),
+    )
+    .await
+    .is_err();
+
+    let negotiation = plugin.state().coord_negotiations.lock().remove(&request_id);
+    if timed_out {
+        log::debug!(
+            "{task_ident}: {peer} didn't ack/nack rebalance coordination in time, assuming \
+             unsupported"
+        );
+        return Ok(true);
+    }
+
+    Ok(negotiation.and_then(|n| n.accepted).unwrap_or(true))
+}
+
+async fn handle_negotiation_request(
+    plugin: &Plugin<PluginState>,
+    peer: PublicKey,
+    body: &[u8],
+) -> Result<(), Error> {
+    let request: CoordRequestPayload = serde_json::from_slice(body)?;
+
+    let accepted = {
+        let peer_channels = plugin.state().peer_channels.lock();
+        match peer_channels.get(&request.chan_id) {
+            Some(channel) if channel.peer_id == peer && is_channel_normal(channel).is_ok() => {
+                match request.direction {
+                    // They're pushing sat to us over this channel: we need room to receive it.
+                    SatDirection::Push => {
+                        Amount::msat(&channel.receivable_msat.unwrap()) >= request.amount_msat
+                    }
+                    // They're pulling sat from us over this channel: we need it to spend.
+                    SatDirection::Pull => {
+                        Amount::msat(&channel.spendable_msat.unwrap()) >= request.amount_msat
+                    }
+                }
+            }
+            _ => false,
+        }
+    };
+
+    log::debug!(
+        "{}: {} rebalance-coordination request from {peer} for {}msat",
+        request.chan_id,
+        if accepted { "accepting" } else { "declining" },
+        request.amount_msat
+    );
+
+    let response = CoordResponsePayload {
+        request_id: request.request_id,
+        accepted,
+    };
+    let rpc_path = plugin.state().config.lock().rpc_path.clone();
+    send_coord_msg(&rpc_path, peer, COORD_REBALANCE_RESPONSE_TYPE, &response).await
+}
+
+fn handle_negotiation_response(plugin: &Plugin<PluginState>, body: &[u8]) {
+    let Ok(response) = serde_json::from_slice::<CoordResponsePayload>(body) else {
+        return;
+    };
+    let mut coord_negotiations = plugin.state().coord_negotiations.lock();
+    if let Some(negotiation) = coord_negotiations.get_mut(&response.request_id) {
+        negotiation.accepted = Some(response.accepted);
+        negotiation.notify.notify_waiters();
+    }
+}
+
+/// `custommsg` hook: dispatches incoming coordination messages by wire type via
+/// [`COORD_MSG_REGISTRY`] and otherwise continues the hook chain untouched, since other
+/// plugins may also be listening for their own custom message types.
+pub async fn custommsg_handler(
+    plugin: Plugin<PluginState>,
+    v: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let continue_response = json!({"result": "continue"});
+
+    let (Some(peer_id), Some(payload_hex)) = (
+        v.get("peer_id").and_then(|p| p.as_str()),
+        v.get("payload").and_then(|p| p.as_str()),
+    ) else {
+        return Ok(continue_response);
+    };
+
+    let Ok(bytes) = decode_hex(payload_hex) else {
+        return Ok(continue_response);
+    };
+    if bytes.len() < 2 {
+        return Ok(continue_response);
+    }
+    let msg_type = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let Some((_, kind)) = COORD_MSG_REGISTRY.iter().find(|(t, _)| *t == msg_type) else {
+        return Ok(continue_response);
+    };
+    let Ok(peer) = PublicKey::from_str(peer_id) else {
+        return Ok(continue_response);
+    };
+
+    match kind {
+        CoordMsgKind::Request => {
+            if let Err(e) = handle_negotiation_request(&plugin, peer, &bytes[2..]).await {
+                log::warn!("failed to handle rebalance-coordination request from {peer}: {e}");
+            }
+        }
+        CoordMsgKind::Response => handle_negotiation_response(&plugin, &bytes[2..]),
+    }
+
+    Ok(continue_response)
+}
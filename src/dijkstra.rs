@@ -1,16 +1,106 @@
-use crate::model::{Config, DijkstraNode, JobMessage, Liquidity, LnGraph, Task};
+use crate::model::{ChannelReservation, Config, DijkstraNode, JobMessage, Liquidity, LnGraph, Task};
 use crate::util::{edge_cost, fee_total_msat_precise};
 use anyhow::{anyhow, Error};
 use cln_rpc::model::requests::SendpayRoute;
 use cln_rpc::primitives::*;
 use sling::{Job, SatDirection};
+use parking_lot::Mutex;
+use rand::{rng, Rng};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::BinaryHeap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
 };
+
+const SHORTEST_PATH_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Floor below which a shard is no longer worth splitting further: routing fees and the
+/// dijkstra liquidity-uncertainty penalty dominate a shard this small, so halving it again
+/// just burns a part slot on dust instead of meaningfully unblocking a disjoint path.
+const MIN_SHARD_FLOOR_MSAT: u64 = 1_000_000;
+
+/// How many ranked alternates [`k_shortest_paths`] computes for `next_route`'s retry path.
+/// One extra on top of the route actually tried is usually enough to route around a single
+/// bad hop without paying for a deep search most retries will never consume.
+pub(crate) const NEXT_ROUTE_ALT_PATHS: usize = 2;
+
+fn shortest_path_cache() -> &'static Mutex<HashMap<String, (Instant, Vec<SendpayRoute>)>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, (Instant, Vec<SendpayRoute>)>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn shortest_path_cache_key(
+    config: &Config,
+    job: &Job,
+    task: &Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+) -> String {
+    let mut tempban_scids: Vec<String> = tempbans.keys().map(ToString::to_string).collect();
+    tempban_scids.sort();
+    let mut banned: Vec<String> = parallel_bans.iter().map(|b| format!("{b:?}")).collect();
+    banned.sort();
+    format!(
+        "{}|{}|{}|{}|{}|{}",
+        task.get_chan_id(),
+        job.sat_direction,
+        job.amount_msat,
+        job.get_maxhops(config.maxhops),
+        tempban_scids.join(","),
+        banned.join(","),
+    )
+}
+
+/// Repeated attempts for the same job (e.g. the retry loop in `sling()` right after a
+/// route failed for an unrelated reason, or several parallel tasks on the same channel)
+/// very often call `dijkstra` again with an identical start/goal/amount/ban set. Caching
+/// the resulting shortest-path route for a few seconds lets those repeats skip
+/// recomputing the whole tree, while the short TTL keeps it from serving a stale route
+/// once the gossip graph or liquidity beliefs have actually moved on.
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_cached(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &mut Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+) -> Result<Vec<SendpayRoute>, Error> {
+    let key = shortest_path_cache_key(config, job, task, tempbans, parallel_bans);
+
+    {
+        let cache = shortest_path_cache().lock();
+        if let Some((cached_at, route)) = cache.get(&key) {
+            if cached_at.elapsed() < SHORTEST_PATH_CACHE_TTL {
+                return Ok(route.clone());
+            }
+        }
+    }
+
+    let route = dijkstra_auto(
+        config,
+        lngraph,
+        job,
+        task,
+        tempbans,
+        parallel_bans,
+        liquidity,
+        reservations,
+    )?;
+
+    let mut cache = shortest_path_cache().lock();
+    cache.retain(|_, (cached_at, _)| cached_at.elapsed() < SHORTEST_PATH_CACHE_TTL);
+    cache.insert(key, (Instant::now(), route.clone()));
+
+    Ok(route)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn dijkstra(
     config: &Config,
     lngraph: &LnGraph,
@@ -19,6 +109,7 @@ pub fn dijkstra(
     tempbans: &HashMap<ShortChannelId, u64>,
     parallel_bans: &HashSet<ShortChannelIdDir>,
     liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
 ) -> Result<Vec<SendpayRoute>, Error> {
     let two_weeks_ago = (SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -31,6 +122,11 @@ pub fn dijkstra(
     let mut predecessor = HashMap::new();
     let mut visit_next = BinaryHeap::new();
     let zero_score = u64::default();
+    // Estimated cumulative routing fee (on `job.amount_msat`) of the cheapest known path to a
+    // node, tracked alongside `scores` so a path that's blown through `job.maxfee_msat` is
+    // pruned the same way one that's blown through `job.get_maxhops` already is, instead of only
+    // being caught after the fact in `next_route`'s too-expensive check.
+    let mut fees_msat: HashMap<PublicKey, u64> = HashMap::new();
 
     let mut excepts = Vec::new();
     excepts.extend(parallel_bans);
@@ -77,6 +173,7 @@ pub fn dijkstra(
     };
     let slingchan = construct_first_node(task, lngraph, job.sat_direction)?;
     scores.insert(start, slingchan);
+    fees_msat.insert(start, 0);
     visit_next.push(MinScored(zero_score, start));
     while let Some(MinScored(node_score, node)) = visit_next.pop() {
         if visited.contains(&node) {
@@ -100,9 +197,16 @@ pub fn dijkstra(
         if current_hops + 2 > job.get_maxhops(config.maxhops) {
             continue;
         }
-        for (scid, edge) in
-            lngraph.edges(&node, two_weeks_ago, task, config, job, &excepts, liquidity)
+        let current_fee_msat = *fees_msat.get(&node).unwrap();
+        if job
+            .get_maxfee_msat()
+            .is_some_and(|maxfee| current_fee_msat > maxfee)
         {
+            continue;
+        }
+        for (scid, edge) in lngraph.edges(
+            &node, two_weeks_ago, task, config, job, &excepts, liquidity, reservations,
+        ) {
             let next = edge.destination;
             if visited.contains(&next) {
                 // debug!(
@@ -115,8 +219,24 @@ pub fn dijkstra(
             let next_score = if edge.source == task.my_pubkey {
                 0
             } else {
-                node_score + edge_cost(edge, job.amount_msat)
+                node_score
+                    + edge_cost(
+                        edge,
+                        job.amount_msat,
+                        liquidity.get(scid),
+                        config.liquidity_halflife,
+                        config.liquidity_penalty_multiplier,
+                        config.liquidity_probabilistic_scoring,
+                        reservations.get(scid).map_or(0, |r| r.reserved_msat),
+                    )
             };
+            let next_fee_msat = current_fee_msat
+                + fee_total_msat_precise(
+                    edge.fee_per_millionth,
+                    edge.base_fee_millisatoshi,
+                    job.amount_msat,
+                )
+                .ceil() as u64;
             // debug!(
             //     "{}: next: {} node_score:{} next_score:{}",
             //     slingchan.channel.short_channel_id.to_string(),
@@ -141,6 +261,7 @@ pub fn dijkstra(
                             short_channel_id: scid.short_channel_id,
                         };
                         *ent.into_mut() = dijkstra_node;
+                        fees_msat.insert(next, next_fee_msat);
                         visit_next.push(MinScored(next_score, next));
                         predecessor.insert(next, node);
                     }
@@ -160,6 +281,7 @@ pub fn dijkstra(
                         short_channel_id: scid.short_channel_id,
                     };
                     ent.insert(dijkstra_node);
+                    fees_msat.insert(next, next_fee_msat);
                     visit_next.push(MinScored(next_score, next));
                     predecessor.insert(next, node);
                 }
@@ -179,6 +301,551 @@ pub fn dijkstra(
     )
 }
 
+/// Splits `job.amount_msat` into shards and routes each independently instead of looking for
+/// a single path that can carry the whole amount, banning the channels already claimed by
+/// earlier parts so no two parts share a hop. Callers send/waitsendpay each part
+/// independently, sharing one payment hash, same as a single-part route but with the
+/// preimage only released once enough parts land (see [`crate::htlc::htlc_handler`]).
+///
+/// `job.get_parts()` is an upper bound on the number of shards, not a fixed target: shards
+/// start at the even/randomized split [`split_part_amounts`] returns, but a shard that can't
+/// find a disjoint path is halved and retried rather than failing the whole job outright,
+/// down to [`MIN_SHARD_FLOOR_MSAT`] or until the part-count bound would be exceeded. A shard
+/// that still can't route at the floor is dropped, so the accumulated parts can fall short of
+/// `job.amount_msat` when the channel graph genuinely can't carry all of it disjointly — the
+/// caller (`next_route` in `slings.rs`) treats whatever sum comes back as this attempt's
+/// amount, same as it already tolerates `onceamount` partial fills.
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_mpp(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &mut Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+) -> Result<Vec<Vec<SendpayRoute>>, Error> {
+    let max_parts = job.get_parts();
+    if max_parts <= 1 {
+        return Ok(vec![dijkstra(
+            config,
+            lngraph,
+            job,
+            task,
+            tempbans,
+            parallel_bans,
+            liquidity,
+            reservations,
+        )?]);
+    }
+
+    let min_shard_msat = (job.amount_msat / u64::from(max_parts) / 4).max(MIN_SHARD_FLOOR_MSAT);
+
+    let mut pending: VecDeque<u64> = split_part_amounts(job.amount_msat, max_parts, job.get_randomsplit())
+        .into_iter()
+        .filter(|&a| a > 0)
+        .collect();
+
+    let mut claimed_channels = parallel_bans.clone();
+    let mut part_routes: Vec<Vec<SendpayRoute>> = Vec::new();
+    while let Some(amount_msat) = pending.pop_front() {
+        if part_routes.len() >= usize::from(max_parts) {
+            break;
+        }
+        let mut part_job = job.clone();
+        part_job.amount_msat = amount_msat;
+
+        let route = dijkstra(
+            config,
+            lngraph,
+            &part_job,
+            task,
+            tempbans,
+            &claimed_channels,
+            liquidity,
+            reservations,
+        )?;
+
+        if route.is_empty() {
+            let half_msat = amount_msat / 2;
+            let slots_left = usize::from(max_parts) - part_routes.len() - pending.len();
+            if half_msat >= min_shard_msat && slots_left >= 2 {
+                pending.push_back(half_msat);
+                pending.push_back(amount_msat - half_msat);
+            }
+            // Otherwise this shard can't be split any further without exceeding the
+            // part-count bound or dropping below the minimum shard size: drop it and let
+            // the job fall short rather than failing the whole attempt.
+            continue;
+        }
+
+        for hop in &route {
+            claimed_channels.insert(ShortChannelIdDir {
+                short_channel_id: hop.channel,
+                direction: 0,
+            });
+            claimed_channels.insert(ShortChannelIdDir {
+                short_channel_id: hop.channel,
+                direction: 1,
+            });
+        }
+        part_routes.push(route);
+    }
+
+    if part_routes.is_empty() {
+        return Err(anyhow!(
+            "could not find any disjoint paths to split amount across"
+        ));
+    }
+
+    Ok(part_routes)
+}
+
+/// Divides `total_msat` into `parts` amounts summing back to `total_msat`. With `randomize`
+/// false, every part gets an even share (the last absorbing the remainder), same as before
+/// randomized splitting existed. With `randomize` true, parts get independently jittered
+/// weights instead, so a peer watching HTLC sizes on its own channels can't tell the split
+/// came from one rebalance attempt by the amounts alone.
+fn split_part_amounts(total_msat: u64, parts: u8, randomize: bool) -> Vec<u64> {
+    let parts = u64::from(parts);
+    if !randomize || parts <= 1 {
+        let base_part_msat = total_msat / parts;
+        let remainder_msat = total_msat % parts;
+        return (0..parts)
+            .map(|i| {
+                base_part_msat
+                    + if i == parts - 1 { remainder_msat } else { 0 }
+            })
+            .collect();
+    }
+
+    let weights: Vec<f64> = (0..parts).map(|_| rng().random_range(0.5..1.5)).collect();
+    let weight_sum: f64 = weights.iter().sum();
+    let mut amounts: Vec<u64> = weights
+        .iter()
+        .map(|w| ((w / weight_sum) * total_msat as f64) as u64)
+        .collect();
+    let assigned_msat: u64 = amounts.iter().sum();
+    *amounts.last_mut().unwrap() += total_msat - assigned_msat;
+    amounts
+}
+
+/// Searches a route independently for each channel in `candidatelist`, restricting each
+/// search to that one candidate (via a throwaway [`Job`] clone with a single-element
+/// `add_candidates`) instead of letting `dijkstra` pick freely across the whole candidate
+/// set in one search. Runs up to `max_parallelism` searches at once across OS threads: every
+/// search only reads `config`/`lngraph`/`liquidity` and works off its own cloned `job`/`task`,
+/// so candidates don't need to coordinate with each other beyond the pool itself. Worth
+/// reaching for once the candidate list is large enough that running searches one at a time
+/// would otherwise serialize a slow search behind every other candidate.
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_per_candidate(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+    candidatelist: &[ShortChannelId],
+    max_parallelism: u16,
+) -> Vec<(ShortChannelId, Option<Vec<SendpayRoute>>)> {
+    if candidatelist.is_empty() {
+        return Vec::new();
+    }
+    let worker_count = usize::from(max_parallelism.max(1)).min(candidatelist.len());
+    let chunk_size = candidatelist.len().div_ceil(worker_count);
+
+    let results = Mutex::new(Vec::with_capacity(candidatelist.len()));
+    std::thread::scope(|scope| {
+        for chunk in candidatelist.chunks(chunk_size.max(1)) {
+            scope.spawn(|| {
+                let mut chunk_results = Vec::with_capacity(chunk.len());
+                for &candidate in chunk {
+                    let mut candidate_job = job.clone();
+                    candidate_job.add_candidates(vec![candidate]);
+                    let mut candidate_task = task.clone();
+                    let route = dijkstra(
+                        config,
+                        lngraph,
+                        &candidate_job,
+                        &mut candidate_task,
+                        tempbans,
+                        parallel_bans,
+                        liquidity,
+                        reservations,
+                    )
+                    .ok()
+                    .filter(|route| !route.is_empty());
+                    chunk_results.push((candidate, route));
+                }
+                results.lock().extend(chunk_results);
+            });
+        }
+    });
+    results.into_inner()
+}
+
+/// Returns up to `k` ranked alternative routes instead of just the single cheapest one,
+/// so callers (e.g. a failed-route retry) can fall back to the next-best path without
+/// re-running a full search from scratch.
+///
+/// This is a practical approximation of Yen's k-shortest-paths algorithm rather than a
+/// literal spur-node implementation: `dijkstra` only exposes a fixed start/goal pair
+/// derived from the job direction, so we can't re-root a search at an arbitrary spur
+/// node on the previous path. Instead, after each path is found we ban its single most
+/// expensive hop and search again, which forces the next result to diverge around that
+/// bottleneck while still reusing cheap hops shared with earlier paths.
+#[allow(clippy::too_many_arguments)]
+pub fn k_shortest_paths(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &mut Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+    k: usize,
+) -> Result<Vec<Vec<SendpayRoute>>, Error> {
+    let mut banned = parallel_bans.clone();
+    let mut seen_paths = HashSet::new();
+    let mut paths = Vec::new();
+
+    // a handful of extra attempts beyond k lets us skip over duplicate paths without
+    // looping forever on a graph that simply has fewer than k distinct routes
+    let max_attempts = k * 4 + 4;
+    for _attempt in 0..max_attempts {
+        if paths.len() >= k {
+            break;
+        }
+        let route = dijkstra_auto(
+            config, lngraph, job, task, tempbans, &banned, liquidity, reservations,
+        )?;
+        if route.is_empty() {
+            break;
+        }
+
+        let path_key: Vec<ShortChannelId> = route.iter().map(|hop| hop.channel).collect();
+        let costliest_hop = costliest_hop_channel(&route);
+        banned.insert(ShortChannelIdDir {
+            short_channel_id: costliest_hop,
+            direction: 0,
+        });
+        banned.insert(ShortChannelIdDir {
+            short_channel_id: costliest_hop,
+            direction: 1,
+        });
+
+        if seen_paths.insert(path_key) {
+            paths.push(route);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Picks between the plain single-source `dijkstra` and [`dijkstra_bidirectional`] based on
+/// `config.dijkstra_bidirectional` (see `sling-dijkstra-bidirectional`), so a single-path
+/// caller doesn't have to make that choice itself.
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_auto(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &mut Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+) -> Result<Vec<SendpayRoute>, Error> {
+    if config.dijkstra_bidirectional {
+        dijkstra_bidirectional(
+            config,
+            lngraph,
+            job,
+            task,
+            tempbans,
+            parallel_bans,
+            liquidity,
+            reservations,
+        )
+    } else {
+        dijkstra(
+            config,
+            lngraph,
+            job,
+            task,
+            tempbans,
+            parallel_bans,
+            liquidity,
+            reservations,
+        )
+    }
+}
+
+/// Alternates a forward search from `start` with a backward search from `goal` (walking
+/// `edges_incoming` instead of `edges`) and stops as soon as some node has been settled
+/// by both sides, stitching the two partial paths together at that meeting node. On
+/// large graphs this typically settles far fewer nodes than a single one-directional
+/// search that has to reach all the way to `goal`.
+///
+/// Falls back to the plain single-directional `dijkstra` if no meeting node is found
+/// (e.g. a highly asymmetric graph where one side never reaches the other), so this is
+/// always at least as correct as the existing search, only potentially faster.
+#[allow(clippy::too_many_arguments)]
+pub fn dijkstra_bidirectional(
+    config: &Config,
+    lngraph: &LnGraph,
+    job: &Job,
+    task: &mut Task,
+    tempbans: &HashMap<ShortChannelId, u64>,
+    parallel_bans: &HashSet<ShortChannelIdDir>,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+) -> Result<Vec<SendpayRoute>, Error> {
+    let two_weeks_ago = (SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - 60 * 60 * 24 * 14) as u32;
+
+    let mut excepts = Vec::new();
+    excepts.extend(parallel_bans);
+    for scid in tempbans.keys() {
+        excepts.push(ShortChannelIdDir {
+            short_channel_id: *scid,
+            direction: 0,
+        });
+        excepts.push(ShortChannelIdDir {
+            short_channel_id: *scid,
+            direction: 1,
+        });
+    }
+    match job.sat_direction {
+        SatDirection::Pull => {
+            for scid in config.exclude_chans_pull.iter() {
+                excepts.push(ShortChannelIdDir {
+                    short_channel_id: *scid,
+                    direction: 0,
+                });
+                excepts.push(ShortChannelIdDir {
+                    short_channel_id: *scid,
+                    direction: 1,
+                });
+            }
+        }
+        SatDirection::Push => {
+            for scid in config.exclude_chans_push.iter() {
+                excepts.push(ShortChannelIdDir {
+                    short_channel_id: *scid,
+                    direction: 0,
+                });
+                excepts.push(ShortChannelIdDir {
+                    short_channel_id: *scid,
+                    direction: 1,
+                });
+            }
+        }
+    }
+
+    let (start, goal) = match job.sat_direction {
+        SatDirection::Pull => (task.my_pubkey, task.other_pubkey),
+        SatDirection::Push => (task.other_pubkey, task.my_pubkey),
+    };
+    let slingchan = construct_first_node(task, lngraph, job.sat_direction)?;
+
+    let mut visited_fwd = HashSet::with_capacity(lngraph.node_count());
+    let mut scores_fwd = HashMap::new();
+    let mut predecessor_fwd = HashMap::new();
+    let mut visit_next_fwd = BinaryHeap::new();
+    scores_fwd.insert(start, slingchan);
+    visit_next_fwd.push(MinScored(0_u64, start));
+
+    let mut visited_bwd = HashSet::with_capacity(lngraph.node_count());
+    let mut scores_bwd: HashMap<PublicKey, DijkstraNode> = HashMap::new();
+    let mut predecessor_bwd = HashMap::new();
+    let mut visit_next_bwd = BinaryHeap::new();
+    visit_next_bwd.push(MinScored(0_u64, goal));
+
+    let mut meeting_node: Option<PublicKey> = None;
+    let mut best_total_score = u64::MAX;
+
+    while meeting_node.is_none() && (!visit_next_fwd.is_empty() || !visit_next_bwd.is_empty()) {
+        let expand_fwd = match (visit_next_fwd.peek(), visit_next_bwd.peek()) {
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some(MinScored(f, _)), Some(MinScored(b, _))) => f <= b,
+            (None, None) => break,
+        };
+
+        if expand_fwd {
+            if let Some(MinScored(node_score, node)) = visit_next_fwd.pop() {
+                if visited_fwd.contains(&node) {
+                    continue;
+                }
+                let current_hops = scores_fwd.get(&node).unwrap().hops;
+                if current_hops + 2 <= job.get_maxhops(config.maxhops) {
+                    for (scid, edge) in lngraph.edges(
+                        &node, two_weeks_ago, &[], config, job, &excepts, liquidity, reservations,
+                    ) {
+                        let next = edge.destination;
+                        if visited_fwd.contains(&next) {
+                            continue;
+                        }
+                        let next_score = if edge.source == task.my_pubkey {
+                            0
+                        } else {
+                            node_score
+                                + edge_cost(
+                                    edge,
+                                    job.amount_msat,
+                                    liquidity.get(scid),
+                                    config.liquidity_halflife,
+                                    config.liquidity_penalty_multiplier,
+                                    config.liquidity_probabilistic_scoring,
+                                    reservations.get(scid).map_or(0, |r| r.reserved_msat),
+                                )
+                        };
+                        let better = match scores_fwd.get(&next) {
+                            Some(existing) => next_score < existing.score,
+                            None => true,
+                        };
+                        if better {
+                            scores_fwd.insert(
+                                next,
+                                DijkstraNode {
+                                    score: next_score,
+                                    channel_state: *edge,
+                                    destination: next,
+                                    hops: current_hops + 1,
+                                    short_channel_id: scid.short_channel_id,
+                                },
+                            );
+                            predecessor_fwd.insert(next, node);
+                            visit_next_fwd.push(MinScored(next_score, next));
+                        }
+                    }
+                }
+                visited_fwd.insert(node);
+                if visited_bwd.contains(&node) {
+                    let total = node_score + scores_bwd.get(&node).map_or(0, |n| n.score);
+                    if total < best_total_score {
+                        best_total_score = total;
+                        meeting_node = Some(node);
+                    }
+                }
+            }
+        } else if let Some(MinScored(node_score, node)) = visit_next_bwd.pop() {
+            if visited_bwd.contains(&node) {
+                continue;
+            }
+            let current_hops = scores_bwd.get(&node).map_or(0, |n| n.hops);
+            if current_hops + 2 <= job.get_maxhops(config.maxhops) {
+                for (scid, edge) in lngraph.edges_incoming(
+                    &node, two_weeks_ago, &[], config, job, &excepts, liquidity, reservations,
+                ) {
+                    let next = edge.source;
+                    if visited_bwd.contains(&next) {
+                        continue;
+                    }
+                    let next_score = node_score
+                        + edge_cost(
+                            edge,
+                            job.amount_msat,
+                            liquidity.get(scid),
+                            config.liquidity_halflife,
+                            config.liquidity_penalty_multiplier,
+                            config.liquidity_probabilistic_scoring,
+                            reservations.get(scid).map_or(0, |r| r.reserved_msat),
+                        );
+                    let better = match scores_bwd.get(&next) {
+                        Some(existing) => next_score < existing.score,
+                        None => true,
+                    };
+                    if better {
+                        scores_bwd.insert(
+                            next,
+                            DijkstraNode {
+                                score: next_score,
+                                channel_state: *edge,
+                                destination: node,
+                                hops: current_hops + 1,
+                                short_channel_id: scid.short_channel_id,
+                            },
+                        );
+                        predecessor_bwd.insert(next, node);
+                        visit_next_bwd.push(MinScored(next_score, next));
+                    }
+                }
+            }
+            visited_bwd.insert(node);
+            if visited_fwd.contains(&node) {
+                let total = node_score + scores_fwd.get(&node).map_or(0, |n| n.score);
+                if total < best_total_score {
+                    best_total_score = total;
+                    meeting_node = Some(node);
+                }
+            }
+        }
+    }
+
+    if meeting_node.is_none() {
+        return dijkstra(
+            config,
+            lngraph,
+            job,
+            task,
+            tempbans,
+            parallel_bans,
+            liquidity,
+            reservations,
+        );
+    }
+
+    // Stitch: the forward predecessor chain covers start..meet, keyed by the node each
+    // edge arrives at. The backward maps key each edge by the node it leaves and point
+    // towards `goal` instead, so walk them from the meeting node and re-key every hop by
+    // the node it arrives at, giving `build_route` one chain from `goal` back to `start`.
+    let mut scores = scores_fwd;
+    let mut predecessor = predecessor_fwd;
+    let mut current = meeting_node.unwrap();
+    while current != goal {
+        let (Some(&next), Some(&hop)) = (predecessor_bwd.get(&current), scores_bwd.get(&current))
+        else {
+            break;
+        };
+        predecessor.insert(next, current);
+        scores.insert(next, hop);
+        current = next;
+    }
+
+    build_route(
+        &predecessor,
+        &goal,
+        &scores,
+        job,
+        &start,
+        &slingchan,
+        config,
+    )
+}
+
+/// Finds the hop that consumed the most fee along `route` (the amount forwarded drops
+/// between consecutive hops by exactly the fee charged for that hop; the final hop is
+/// the delivered amount and charges nothing further).
+fn costliest_hop_channel(route: &[SendpayRoute]) -> ShortChannelId {
+    route
+        .windows(2)
+        .max_by_key(|pair| {
+            Amount::msat(&pair[0].amount_msat).saturating_sub(Amount::msat(&pair[1].amount_msat))
+        })
+        .map(|pair| pair[0].channel)
+        .unwrap_or_else(|| route[0].channel)
+}
+
 fn build_route(
     predecessor: &HashMap<PublicKey, PublicKey>,
     goal: &PublicKey,
@@ -1,3 +1,6 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
 use cln_rpc::primitives::Amount;
 use cln_rpc::primitives::PublicKey;
 use cln_rpc::primitives::Secret;
@@ -71,3 +74,100 @@ pub struct WaitsendpayErrorData {
     #[serde(alias = "failcodename")]
     pub failcodename: String,
 }
+
+/// Whether a BOLT #4 `failcode` describes a hop that's dead for the rest of this run, or one
+/// that's just temporarily out of liquidity or mispriced and worth retrying later.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FailureClass {
+    Permanent,
+    Temporary,
+}
+
+const PERM: u32 = 0x4000;
+const NODE: u32 = 0x2000;
+const UPDATE: u32 = 0x1000;
+
+const WIRE_PERMANENT_NODE_FAILURE: u32 = PERM | NODE | 2;
+const WIRE_TEMPORARY_CHANNEL_FAILURE: u32 = UPDATE | 7;
+const WIRE_PERMANENT_CHANNEL_FAILURE: u32 = PERM | 8;
+const WIRE_REQUIRED_CHANNEL_FEATURE_MISSING: u32 = PERM | 9;
+const WIRE_UNKNOWN_NEXT_PEER: u32 = PERM | 10;
+const WIRE_FEE_INSUFFICIENT: u32 = UPDATE | 12;
+const WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS: u32 = PERM | 15;
+const WIRE_CHANNEL_DISABLED: u32 = UPDATE | 20;
+
+/// Classifies a BOLT #4 `failcode` as [`FailureClass::Permanent`] (the erring node/channel is
+/// broken and won't recover within this run) or [`FailureClass::Temporary`] (just out of
+/// liquidity or fee-misaligned right now). Unrecognized codes default to `Temporary` so we
+/// back off and retry rather than permanently excluding a hop we don't understand yet.
+pub fn classify_failcode(failcode: u32) -> FailureClass {
+    match failcode {
+        WIRE_PERMANENT_CHANNEL_FAILURE
+        | WIRE_REQUIRED_CHANNEL_FEATURE_MISSING
+        | WIRE_UNKNOWN_NEXT_PEER
+        | WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS
+        | WIRE_PERMANENT_NODE_FAILURE => FailureClass::Permanent,
+        WIRE_TEMPORARY_CHANNEL_FAILURE | WIRE_FEE_INSUFFICIENT | WIRE_CHANNEL_DISABLED => {
+            FailureClass::Temporary
+        }
+        _ => FailureClass::Temporary,
+    }
+}
+
+/// The onion failure code `htlc_handler` reports back to the sender for an HTLC that landed on
+/// the wrong incoming `short_channel_id` (the payer used an alias or scid we're no longer
+/// resolving it on), encoded the way CLN's `htlc_accepted` hook wants it: hex digits of
+/// [`WIRE_TEMPORARY_CHANNEL_FAILURE`], since BOLT #4 has no dedicated "wrong scid" code and a
+/// temporary failure is the closest honest signal (retry on another path, don't blacklist the
+/// node).
+pub const ONION_WRONG_SCID_FAILURE_HEX: &str = "1007";
+
+/// A classified rebalance-attempt failure, replacing the free-form `failure_reason` strings
+/// that used to get stuffed straight into stats so callers could aggregate on something more
+/// stable than whatever text a given failcode/error happened to carry. `Other` preserves any
+/// reason this table doesn't know about yet rather than discarding it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum FailureReason {
+    Timeout,
+    TemporaryChannelFailure,
+    IncorrectCltvExpiry,
+    FeeInsufficient,
+    Disconnected,
+    WrongScid,
+    Other(String),
+}
+
+impl Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureReason::Timeout => write!(f, "WAITSENDPAY_TIMEOUT"),
+            FailureReason::TemporaryChannelFailure => write!(f, "WIRE_TEMPORARY_CHANNEL_FAILURE"),
+            FailureReason::IncorrectCltvExpiry => write!(f, "WIRE_INCORRECT_CLTV_EXPIRY"),
+            FailureReason::FeeInsufficient => write!(f, "WIRE_FEE_INSUFFICIENT"),
+            FailureReason::Disconnected => write!(f, "FIRST_PEER_NOT_READY"),
+            FailureReason::WrongScid => write!(f, "WRONG_SCID"),
+            FailureReason::Other(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl FromStr for FailureReason {
+    type Err = std::convert::Infallible;
+
+    /// Maps the strings the plugin already sees onto a variant: CLN's `failcodename` for an
+    /// onion error (e.g. `"WIRE_TEMPORARY_CHANNEL_FAILURE"`), or one of our own synthetic
+    /// reasons (`"WAITSENDPAY_TIMEOUT"`, `"FIRST_PEER_NOT_READY"`). Anything unrecognized
+    /// becomes [`FailureReason::Other`] instead of being rejected, since new failcodes show up
+    /// faster than this table gets updated.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "WAITSENDPAY_TIMEOUT" => FailureReason::Timeout,
+            "WIRE_TEMPORARY_CHANNEL_FAILURE" => FailureReason::TemporaryChannelFailure,
+            "WIRE_INCORRECT_CLTV_EXPIRY" => FailureReason::IncorrectCltvExpiry,
+            "WIRE_FEE_INSUFFICIENT" => FailureReason::FeeInsufficient,
+            "FIRST_PEER_NOT_READY" => FailureReason::Disconnected,
+            "WRONG_SCID" => FailureReason::WrongScid,
+            other => FailureReason::Other(other.to_string()),
+        })
+    }
+}
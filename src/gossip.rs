@@ -1,7 +1,7 @@
 use std::{
     fs::File,
     io::{BufReader, Read},
-    time::Instant,
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error};
@@ -10,7 +10,7 @@ use cln_plugin::Plugin;
 use cln_rpc::primitives::{Amount, ShortChannelId, ShortChannelIdDir};
 
 use crate::{
-    model::{IncompleteChannels, LnGraph, ShortChannelIdDirStateBuilder},
+    model::{IncompleteChannels, LnGraph, NodeInfo, PubKeyBytes, ShortChannelIdDirStateBuilder},
     PluginState,
 };
 
@@ -42,18 +42,37 @@ pub async fn read_gossip_store(
     reader: &mut BufReader<File>,
     is_start_up: &mut bool,
 ) -> Result<(), Error> {
-    let mut graph = plugin.state().graph.lock();
-    let mut incomplete_channels = plugin.state().incomplete_channels.lock();
-
-    let mut offset = 0;
-
-    read_gossip_file(
-        is_start_up,
-        reader,
-        &mut graph,
-        &mut incomplete_channels,
-        &mut offset,
-    )?;
+    let (stale_horizon_secs, incomplete_channel_timeout_secs, verify_channel_funding) = {
+        let config = plugin.state().config.lock();
+        (
+            config.stale_channel_horizon_secs,
+            config.incomplete_channel_timeout_secs,
+            config.verify_channel_funding,
+        )
+    };
+
+    let built = {
+        let mut graph = plugin.state().graph.lock();
+        let mut incomplete_channels = plugin.state().incomplete_channels.lock();
+        let mut offset = 0;
+
+        read_gossip_file(
+            is_start_up,
+            reader,
+            &mut graph,
+            &mut incomplete_channels,
+            &mut offset,
+            stale_horizon_secs,
+            incomplete_channel_timeout_secs,
+        )?
+    };
+
+    if verify_channel_funding && !built.is_empty() {
+        let mut pending = plugin.state().pending_funding_checks.lock();
+        for dir_chan in built {
+            pending.push_back(dir_chan.short_channel_id);
+        }
+    }
 
     Ok(())
 }
@@ -64,7 +83,9 @@ pub fn read_gossip_file(
     graph: &mut LnGraph,
     incomplete_channels: &mut IncompleteChannels,
     offset: &mut usize,
-) -> Result<(), anyhow::Error> {
+    stale_horizon_secs: u64,
+    incomplete_channel_timeout_secs: u64,
+) -> Result<Vec<ShortChannelIdDir>, anyhow::Error> {
     let now = Instant::now();
 
     if *is_start_up {
@@ -72,13 +93,23 @@ pub fn read_gossip_file(
         let mut gossip_ver_buffer = vec![0u8; 1];
         reader.read_exact(&mut gossip_ver_buffer)?;
         log::debug!("read_gossip_file: checking gossip_store version...");
-        if (gossip_ver_buffer[0] & 0b1110_0000) != 0b0000_0000 {
+        let gossip_store_version = gossip_ver_buffer[0];
+        if (gossip_store_version & 0b1110_0000) != 0b0000_0000 {
             log::warn!("read_gossip_file: Unsupported gossip_store version!");
             return Err(anyhow!(
                 "read_gossip_file: Unsupported gossip_store version!"
             ));
         }
-        log::debug!("read_gossip_file: gossip_store version is good");
+        // The lower 5 bits are the minor version, bumped whenever lnd/cln change a wrapper
+        // record or append fields. We don't special-case any minor version here: all the
+        // fixed-offset reads below stop before where new fields get appended, and
+        // `parse_channel_update` walks any trailing bytes as a TLV stream instead of assuming
+        // a fixed message length, so newer minor versions degrade gracefully instead of
+        // silently misreading fields.
+        log::debug!(
+            "read_gossip_file: gossip_store version is good (minor version {})",
+            gossip_store_version & 0b0001_1111
+        );
     }
 
     let mut gossip_file = vec![0u8; CHUNK_SIZE];
@@ -126,7 +157,26 @@ pub fn read_gossip_file(
 
     let post_now = Instant::now();
 
-    incomplete_channels.update_graph(graph);
+    let built = incomplete_channels.update_graph(graph);
+
+    let pruned = prune_stale_channels(graph, stale_horizon_secs);
+    if pruned > 0 {
+        log::debug!(
+            "read_gossip_file: pruned {pruned} channel directions with no update in {stale_horizon_secs}s"
+        );
+    }
+
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32;
+    let reaped = incomplete_channels.reap_timed_out(now_secs, incomplete_channel_timeout_secs as u32);
+    if reaped > 0 {
+        log::debug!(
+            "read_gossip_file: reaped {reaped} incomplete channel(s) with no update in \
+             {incomplete_channel_timeout_secs}s"
+        );
+    }
 
     *is_start_up = false;
 
@@ -139,7 +189,21 @@ pub fn read_gossip_file(
         graph.public_channel_count(),
         incomplete_channels.len()
     );
-    Ok(())
+    Ok(built)
+}
+
+/// Removes any channel direction whose newest `channel_update` (`last_update`) is older than
+/// `horizon_secs`, per BOLT 7's rule that a channel with no update in 14 days is considered
+/// closed. Returns how many directions were dropped so the caller can log it.
+pub fn prune_stale_channels(graph: &mut LnGraph, horizon_secs: u64) -> usize {
+    let cutoff = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        .saturating_sub(horizon_secs) as u32;
+    let before = graph.public_channel_count() + graph.private_channel_count();
+    graph.retain(|_, state| state.last_update >= cutoff);
+    before - (graph.public_channel_count() + graph.private_channel_count())
 }
 
 fn read_gossip_file_chunk(
@@ -154,16 +218,41 @@ fn read_gossip_file_chunk(
     );
     while *offset + 14 < gossip_file.len() {
         // Read the record header + type
+        let record_start = *offset;
         let flags = u16::from_be_bytes(gossip_file[*offset..*offset + 2].try_into()?);
         *offset += 2;
         let len = u16::from_be_bytes(gossip_file[*offset..*offset + 2].try_into()?) as usize;
-        *offset += 10;
+        *offset += 2;
+        let crc = u32::from_be_bytes(gossip_file[*offset..*offset + 4].try_into()?);
+        *offset += 8;
         if *offset + len > gossip_file.len() {
-            *offset -= 12;
+            *offset = record_start;
             break;
         }
-        // let crc;
-        // let timestamp;
+        // `len` covers the record's `msg_type` field plus its payload, so anything below 2
+        // can't even hold a `msg_type` — every branch below either reads one directly or
+        // computes `len - 2` for the payload that follows it, which would panic (underflow)
+        // or read out of bounds on a record this short. Treat it the same as a CRC mismatch:
+        // a corrupt/truncated record we skip rather than a fatal error.
+        if len < 2 {
+            log::warn!(
+                "read_gossip_file_chunk: record at offset {record_start} has invalid length \
+                 {len}, skipping corrupt record"
+            );
+            *offset += len;
+            continue;
+        }
+        let computed_crc = crc32c::crc32c(&gossip_file[*offset..*offset + len]);
+        if computed_crc != crc {
+            log::warn!(
+                "read_gossip_file_chunk: CRC mismatch at offset {} (expected {:08x}, got {:08x}), skipping corrupt record",
+                record_start,
+                crc,
+                computed_crc
+            );
+            *offset += len;
+            continue;
+        }
         let msg_type = u16::from_be_bytes(gossip_file[*offset..*offset + 2].try_into()?);
         *offset += 2;
 
@@ -182,7 +271,16 @@ fn read_gossip_file_chunk(
             256 => {
                 // public channel_announcement
                 let (scid, chan_ann) =
-                    parse_channel_announcement(&gossip_file[*offset..*offset + len - 2])?;
+                    match parse_channel_announcement(&gossip_file[*offset..*offset + len - 2]) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            log::debug!(
+                                "read_gossip_file_chunk: failed to parse channel_announcement: {e}"
+                            );
+                            *offset += len - 2;
+                            continue;
+                        }
+                    };
                 *offset += len - 2;
 
                 let dir_chan_0 = ShortChannelIdDir {
@@ -224,8 +322,18 @@ fn read_gossip_file_chunk(
                 //   - `amount_sat`: u64
                 //   - `len`: u16
                 //   - `msg_type + announcement`: u16 + u8[len-2]
-                let (scid, chan_ann) =
-                    parse_channel_announcement(&gossip_file[*offset + 12..*offset + 10 + len])?;
+                let (scid, chan_ann) = match parse_channel_announcement(
+                    &gossip_file[*offset + 12..*offset + 10 + len],
+                ) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        log::debug!(
+                            "read_gossip_file_chunk: failed to parse private channel_announcement: {e}"
+                        );
+                        *offset += len + 10;
+                        continue;
+                    }
+                };
 
                 let dir_chan_0 = ShortChannelIdDir {
                     short_channel_id: scid,
@@ -265,7 +373,16 @@ fn read_gossip_file_chunk(
             258 => {
                 // channel_update
                 let (scid_dir, chan_up) =
-                    parse_channel_update(&gossip_file[*offset..*offset + len - 2])?;
+                    match parse_channel_update(&gossip_file[*offset..*offset + len - 2]) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            log::debug!(
+                                "read_gossip_file_chunk: failed to parse channel_update: {e}"
+                            );
+                            *offset += len - 2;
+                            continue;
+                        }
+                    };
                 *offset += len - 2;
                 let mut updated = false;
 
@@ -290,7 +407,16 @@ fn read_gossip_file_chunk(
                 //   - `len`: u16
                 //   - `msg_type + update`: u16 + u8[len-2]
                 let (scid_dir, chan_up) =
-                    parse_channel_update(&gossip_file[*offset + 4..*offset + 2 + len])?;
+                    match parse_channel_update(&gossip_file[*offset + 4..*offset + 2 + len]) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            log::debug!(
+                            "read_gossip_file_chunk: failed to parse private channel_update: {e}"
+                        );
+                            *offset += len + 2;
+                            continue;
+                        }
+                    };
                 *offset += len + 2;
                 let mut updated = false;
                 if let Some(chan_state) = graph.get_state_mut_direction(scid_dir) {
@@ -308,6 +434,16 @@ fn read_gossip_file_chunk(
                     }
                 }
             }
+            257 => {
+                // node_announcement
+                match parse_node_announcement(&gossip_file[*offset..*offset + len - 2]) {
+                    Ok((node_id, node_info)) => graph.set_node_info(node_id, node_info),
+                    Err(e) => log::debug!(
+                        "read_gossip_file_chunk: failed to parse node_announcement: {e}"
+                    ),
+                }
+                *offset += len - 2;
+            }
             4101 => {
                 // gossip_store_channel_amount
                 //  - `satoshis`: u64
@@ -368,22 +504,193 @@ fn extract_scid(gossip_file: &[u8]) -> Result<ShortChannelId, anyhow::Error> {
 }
 
 fn parse_channel_update(inpu: &[u8]) -> Result<(ShortChannelIdDir, ChannelUpdate), Error> {
-    let scid = extract_scid(&inpu[96..104])?;
-    Ok((
+    let scid = extract_scid(
+        inpu.get(96..104)
+            .ok_or_else(|| anyhow!("channel_update: truncated before scid"))?,
+    )?;
+    let channel_flags = *inpu
+        .get(109)
+        .ok_or_else(|| anyhow!("channel_update: truncated before channel_flags"))?;
+    let chan_up = (
         ShortChannelIdDir {
             short_channel_id: scid,
-            direction: (inpu[109] & 0b0000_0001) as u32,
+            direction: (channel_flags & 0b0000_0001) as u32,
         },
         ChannelUpdate {
-            // message_flags: inpu[108],
-            // channel_flags: inpu[109],
-            active: ((inpu[109] & 0b0000_0010) >> 1) != 1,
-            last_update: u32::from_be_bytes(inpu[104..108].try_into()?),
-            base_fee_millisatoshi: u32::from_be_bytes(inpu[120..124].try_into()?),
-            fee_per_millionth: u32::from_be_bytes(inpu[124..128].try_into()?),
-            delay: (u32::from(inpu[110]) << 8) | u32::from(inpu[111]),
-            htlc_minimum_msat: Amount::from_msat(u64::from_be_bytes(inpu[112..120].try_into()?)),
-            htlc_maximum_msat: Amount::from_msat(u64::from_be_bytes(inpu[128..136].try_into()?)),
+            active: ((channel_flags & 0b0000_0010) >> 1) != 1,
+            last_update: u32::from_be_bytes(
+                inpu.get(104..108)
+                    .ok_or_else(|| anyhow!("channel_update: truncated timestamp"))?
+                    .try_into()?,
+            ),
+            base_fee_millisatoshi: u32::from_be_bytes(
+                inpu.get(120..124)
+                    .ok_or_else(|| anyhow!("channel_update: truncated base_fee_millisatoshi"))?
+                    .try_into()?,
+            ),
+            fee_per_millionth: u32::from_be_bytes(
+                inpu.get(124..128)
+                    .ok_or_else(|| anyhow!("channel_update: truncated fee_per_millionth"))?
+                    .try_into()?,
+            ),
+            delay: (u32::from(
+                *inpu
+                    .get(110)
+                    .ok_or_else(|| anyhow!("channel_update: truncated cltv_expiry_delta"))?,
+            ) << 8)
+                | u32::from(
+                    *inpu
+                        .get(111)
+                        .ok_or_else(|| anyhow!("channel_update: truncated cltv_expiry_delta"))?,
+                ),
+            htlc_minimum_msat: Amount::from_msat(u64::from_be_bytes(
+                inpu.get(112..120)
+                    .ok_or_else(|| anyhow!("channel_update: truncated htlc_minimum_msat"))?
+                    .try_into()?,
+            )),
+            htlc_maximum_msat: Amount::from_msat(u64::from_be_bytes(
+                inpu.get(128..136)
+                    .ok_or_else(|| anyhow!("channel_update: truncated htlc_maximum_msat"))?
+                    .try_into()?,
+            )),
+        },
+    );
+
+    // Anything past `htlc_maximum_msat` is a TLV stream that newer gossip_store/BOLT 7 minor
+    // versions may append to. We don't derive any fields from it today (no such TLV type is
+    // defined for `channel_update` yet), but walking it by type/length rather than ignoring it
+    // outright means a future field lands as a recognizable, loggable record instead of silently
+    // shifting offsets for anyone who starts reading past it.
+    if inpu.len() > 136 {
+        match read_tlv_stream(&inpu[136..]) {
+            Ok(tlvs) => {
+                for (tlv_type, value) in tlvs {
+                    log::trace!(
+                        "parse_channel_update: ignoring unknown trailing TLV type {} ({} bytes)",
+                        tlv_type,
+                        value.len()
+                    );
+                }
+            }
+            Err(e) => log::debug!("parse_channel_update: malformed trailing TLV stream: {e}"),
+        }
+    }
+
+    Ok(chan_up)
+}
+
+/// Walks a BOLT 1 TLV stream (`bigsize type` + `bigsize length` + `length` bytes of value,
+/// repeated to the end of `data`) and returns each record as `(type, value)`. Used to read past
+/// the fixed fields of a message without having to know what, if anything, a newer protocol
+/// version appended there.
+fn read_tlv_stream(data: &[u8]) -> Result<Vec<(u64, &[u8])>, Error> {
+    let mut records = Vec::new();
+    let mut offset = 0usize;
+    while offset < data.len() {
+        let (tlv_type, n) = read_bigsize(&data[offset..])?;
+        offset += n;
+        let (len, n) = read_bigsize(&data[offset..])?;
+        offset += n;
+        let len = len as usize;
+        let value = data
+            .get(offset..offset + len)
+            .ok_or_else(|| anyhow!("read_tlv_stream: truncated value for type {tlv_type}"))?;
+        offset += len;
+        records.push((tlv_type, value));
+    }
+    Ok(records)
+}
+
+/// Decodes a single BOLT 1 `bigsize` value from the start of `data`, returning the value and how
+/// many bytes it occupied.
+fn read_bigsize(data: &[u8]) -> Result<(u64, usize), Error> {
+    let first = *data
+        .first()
+        .ok_or_else(|| anyhow!("read_bigsize: empty input"))?;
+    match first {
+        0xfd => {
+            let bytes = data
+                .get(1..3)
+                .ok_or_else(|| anyhow!("read_bigsize: truncated u16"))?;
+            Ok((u16::from_be_bytes(bytes.try_into()?) as u64, 3))
+        }
+        0xfe => {
+            let bytes = data
+                .get(1..5)
+                .ok_or_else(|| anyhow!("read_bigsize: truncated u32"))?;
+            Ok((u32::from_be_bytes(bytes.try_into()?) as u64, 5))
+        }
+        0xff => {
+            let bytes = data
+                .get(1..9)
+                .ok_or_else(|| anyhow!("read_bigsize: truncated u64"))?;
+            Ok((u64::from_be_bytes(bytes.try_into()?), 9))
+        }
+        n => Ok((n as u64, 1)),
+    }
+}
+
+/// Parses a `node_announcement` (type 257): `signature:[u8;64]`, `features_len:u16` +
+/// `features:u8[features_len]`, `timestamp:u32`, `node_id:[u8;33]`, `rgb_color:[u8;3]`,
+/// `alias:[u8;32]` (null-padded, not length-prefixed), `addrlen:u16` + `addresses:u8[addrlen]`.
+/// Reads the declared `features_len`/`addrlen` rather than assuming fixed offsets, and bails
+/// with an `Err` (instead of panicking) on a short/truncated buffer so the caller can just skip
+/// the record.
+fn parse_node_announcement(inpu: &[u8]) -> Result<(PubKeyBytes, NodeInfo), Error> {
+    let mut offset = 64usize; // signature
+    let features_len = u16::from_be_bytes(
+        inpu.get(offset..offset + 2)
+            .ok_or_else(|| anyhow!("node_announcement: truncated before features_len"))?
+            .try_into()?,
+    ) as usize;
+    offset += 2;
+    let features = inpu
+        .get(offset..offset + features_len)
+        .ok_or_else(|| anyhow!("node_announcement: truncated features"))?
+        .to_vec();
+    offset += features_len;
+    let last_update = u32::from_be_bytes(
+        inpu.get(offset..offset + 4)
+            .ok_or_else(|| anyhow!("node_announcement: truncated timestamp"))?
+            .try_into()?,
+    );
+    offset += 4;
+    let node_id = PublicKey::from_slice(
+        inpu.get(offset..offset + 33)
+            .ok_or_else(|| anyhow!("node_announcement: truncated node_id"))?,
+    )?;
+    offset += 33;
+    let rgb_color: [u8; 3] = inpu
+        .get(offset..offset + 3)
+        .ok_or_else(|| anyhow!("node_announcement: truncated rgb_color"))?
+        .try_into()?;
+    offset += 3;
+    let alias_bytes = inpu
+        .get(offset..offset + 32)
+        .ok_or_else(|| anyhow!("node_announcement: truncated alias"))?;
+    offset += 32;
+    let alias = String::from_utf8_lossy(alias_bytes)
+        .trim_end_matches('\0')
+        .to_string();
+    let addr_len = u16::from_be_bytes(
+        inpu.get(offset..offset + 2)
+            .ok_or_else(|| anyhow!("node_announcement: truncated addrlen"))?
+            .try_into()?,
+    ) as usize;
+    offset += 2;
+    let addresses = inpu
+        .get(offset..offset + addr_len)
+        .ok_or_else(|| anyhow!("node_announcement: truncated addresses"))?
+        .to_vec();
+
+    Ok((
+        PubKeyBytes::from_pubkey(&node_id),
+        NodeInfo {
+            features,
+            last_update,
+            rgb_color,
+            alias,
+            addresses,
         },
     ))
 }
@@ -393,12 +700,25 @@ fn parse_channel_announcement(inpu: &[u8]) -> Result<(ShortChannelId, ChannelAnn
     // 64..128 sig2
     // 128..192 bc_sig_1
     // 192..256 bc_sig_2
-    let len = u16::from_be_bytes(inpu[256..258].try_into()?) as usize;
+    let len = u16::from_be_bytes(
+        inpu.get(256..258)
+            .ok_or_else(|| anyhow!("channel_announcement: truncated before features_len"))?
+            .try_into()?,
+    ) as usize;
     // 258..258+len features
     // 258+len..290+len chain_hash
-    let scid = extract_scid(&inpu[(290 + len)..(298 + len)])?;
-    let source = PublicKey::from_slice(&inpu[(298 + len)..(331 + len)])?;
-    let destination = PublicKey::from_slice(&inpu[(331 + len)..(364 + len)])?;
+    let scid = extract_scid(
+        inpu.get((290 + len)..(298 + len))
+            .ok_or_else(|| anyhow!("channel_announcement: truncated scid"))?,
+    )?;
+    let source = PublicKey::from_slice(
+        inpu.get((298 + len)..(331 + len))
+            .ok_or_else(|| anyhow!("channel_announcement: truncated source node_id"))?,
+    )?;
+    let destination = PublicKey::from_slice(
+        inpu.get((331 + len)..(364 + len))
+            .ok_or_else(|| anyhow!("channel_announcement: truncated destination node_id"))?,
+    )?;
     Ok((
         scid,
         ChannelAnnouncement {
@@ -1,13 +1,15 @@
 use std::{
     str::FromStr,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error, Ok};
 use cln_plugin::Plugin;
-use cln_rpc::primitives::ShortChannelId;
+use cln_rpc::primitives::{Amount, ShortChannelId};
 use serde_json::json;
+use tokio::time;
 
+use crate::errors::ONION_WRONG_SCID_FAILURE_HEX;
 use crate::model::PluginState;
 
 pub async fn htlc_handler(
@@ -34,7 +36,7 @@ pub async fn htlc_handler(
                     || (pi.incoming_alias.is_some() && scid == pi.incoming_alias.unwrap())
                 {
                     log::debug!("resolving htlc. payment_hash: {payment_hash}");
-                    Ok(json!({"result":"resolve","payment_key":pi.preimage}))
+                    return Ok(json!({"result":"resolve","payment_key":pi.preimage}));
                 } else if let Some(peer) = plugin.state().peer_channels.lock().get(&scid) {
                     log::info!(
                         "NOT resolving HTLC from {}: WRONG SCID: {scid} EXPECTED: {}. \
@@ -53,18 +55,113 @@ pub async fn htlc_handler(
 
                     pays.insert(payment_hash.to_owned(), pi);
 
-                    Ok(json!({"result": "fail", "failure_message": "1007"}))
+                    return Ok(json!({
+                        "result": "fail",
+                        "failure_message": ONION_WRONG_SCID_FAILURE_HEX
+                    }));
                 } else {
-                    Ok(json!({"result": "fail", "failure_message": "1007"}))
+                    return Ok(json!({
+                        "result": "fail",
+                        "failure_message": ONION_WRONG_SCID_FAILURE_HEX
+                    }));
                 }
-            } else {
-                Ok(json!({"result": "continue"}))
             }
+            drop(pays);
+
+            handle_mpp_part(plugin, payment_hash, scid, htlc).await
         }
         None => Ok(json!({"result": "continue"})),
     }
 }
 
+/// Handles one part of a multi-part (MPP) rebalance, holding the HTLC open until either
+/// enough parts have landed to cover [`crate::model::MppPay::target_msat`] (in which case
+/// every part, including this one, gets resolved with the shared preimage) or
+/// `part_timeout_secs` passes without that happening (in which case this part fails, same as
+/// a single-part rebalance that never gets enough liquidity). Payment hashes we have no
+/// record of at all (not ours, or already resolved and forgotten) fall through to `continue`
+/// so CLN keeps forwarding them normally.
+async fn handle_mpp_part(
+    plugin: Plugin<PluginState>,
+    payment_hash: &str,
+    scid: ShortChannelId,
+    htlc: &serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let amount_msat = htlc
+        .get("amount_msat")
+        .and_then(|a| a.as_str())
+        .and_then(|s| Amount::from_str(s).ok())
+        .map(|a| Amount::msat(&a));
+
+    let (preimage, notify, part_timeout_secs) = {
+        let mut mpp_pays = plugin.state().mpp_pays.lock();
+        let Some(state) = mpp_pays.get_mut(payment_hash) else {
+            return Ok(json!({"result": "continue"}));
+        };
+
+        if !(scid == state.resolve.incoming_scid
+            || state.resolve.incoming_alias.is_some_and(|alias| alias == scid))
+        {
+            log::info!(
+                "NOT resolving MPP part from WRONG SCID: {scid} EXPECTED: {}. \
+                payment_hash: {payment_hash}",
+                state.resolve.incoming_scid
+            );
+            if let Some(peer) = plugin.state().peer_channels.lock().get(&scid) {
+                plugin.state().bad_fwd_nodes.lock().insert(
+                    peer.peer_id,
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                );
+            }
+            return Ok(json!({
+                "result": "fail",
+                "failure_message": ONION_WRONG_SCID_FAILURE_HEX
+            }));
+        }
+
+        state.received_msat += amount_msat.unwrap_or(0);
+        log::debug!(
+            "{payment_hash}: MPP part landed, {}/{}msat received so far",
+            state.received_msat,
+            state.target_msat
+        );
+        if state.received_msat >= state.target_msat {
+            state.resolved = true;
+            state.notify.notify_waiters();
+            return Ok(json!({"result":"resolve","payment_key":state.resolve.preimage}));
+        }
+
+        (
+            state.resolve.preimage.clone(),
+            state.notify.clone(),
+            state.part_timeout_secs,
+        )
+    };
+
+    if time::timeout(Duration::from_secs(part_timeout_secs), notify.notified())
+        .await
+        .is_err()
+    {
+        log::info!("{payment_hash}: MPP part timed out waiting for sibling parts");
+        return Ok(json!({"result": "fail", "failure_message": "2002"}));
+    }
+
+    let resolved = plugin
+        .state()
+        .mpp_pays
+        .lock()
+        .get(payment_hash)
+        .is_some_and(|state| state.resolved);
+    if resolved {
+        Ok(json!({"result":"resolve","payment_key":preimage}))
+    } else {
+        Ok(json!({"result": "fail", "failure_message": "2002"}))
+    }
+}
+
 pub async fn block_added(plugin: Plugin<PluginState>, v: serde_json::Value) -> Result<(), Error> {
     let block = if let Some(b) = v.get("block") {
         b
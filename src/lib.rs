@@ -38,6 +38,67 @@ impl fmt::Display for SatDirection {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq)]
+pub enum ExceptDirection {
+    #[serde(alias = "pull")]
+    Pull,
+    #[serde(alias = "push")]
+    Push,
+    #[serde(alias = "both")]
+    Both,
+}
+
+impl FromStr for ExceptDirection {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pull" => Ok(ExceptDirection::Pull),
+            "push" => Ok(ExceptDirection::Push),
+            "both" => Ok(ExceptDirection::Both),
+            _ => Err(anyhow!("could not parse except direction from `{}`", s)),
+        }
+    }
+}
+impl fmt::Display for ExceptDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExceptDirection::Pull => write!(f, "pull"),
+            ExceptDirection::Push => write!(f, "push"),
+            ExceptDirection::Both => write!(f, "both"),
+        }
+    }
+}
+
+/// A channel exception as persisted to disk: which direction(s) it blocks, and
+/// optionally a unix timestamp after which it should be treated as expired. `expires_at`
+/// of `None` means the exception is permanent, same as before TTLs existed.
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq)]
+pub struct ExceptChan {
+    pub direction: ExceptDirection,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires_at: Option<u64>,
+}
+
+impl ExceptChan {
+    pub fn permanent(direction: ExceptDirection) -> ExceptChan {
+        ExceptChan {
+            direction,
+            expires_at: None,
+        }
+    }
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|e| now >= e)
+    }
+}
+
+/// A peer exception as persisted to disk, with an optional TTL mirroring [`ExceptChan`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerExcept {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires_at: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Job {
     pub sat_direction: SatDirection,
@@ -46,6 +107,8 @@ pub struct Job {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub outppm: Option<u64>,
     pub maxppm: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maxfee_msat: Option<u64>,
     #[serde(alias = "candidatelist")]
     #[serde(skip_serializing_if = "Option::is_none")]
     candidates: Option<Vec<ShortChannelId>>,
@@ -62,6 +125,19 @@ pub struct Job {
     paralleljobs: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub onceamount_msat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maxparts: Option<u8>,
+    #[serde(alias = "amountpart")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amountpart_msat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    randomsplit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minprobability: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    splitonfail: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
 }
 
 impl Display for Job {
@@ -71,6 +147,9 @@ impl Display for Job {
         parts.push(format!("amount_msat:{}", self.amount_msat));
         parts.push(format!("maxppm:{}", self.maxppm));
 
+        if let Some(mf) = self.maxfee_msat {
+            parts.push(format!("maxfee_msat:{}", mf));
+        }
         if let Some(o) = self.outppm {
             parts.push(format!("outppm:{}", o));
         }
@@ -101,12 +180,34 @@ impl Display for Job {
         if let Some(t) = self.onceamount_msat {
             parts.push(format!("onceamount_msat:{}", t));
         }
+        if let Some(mp) = self.maxparts {
+            parts.push(format!("maxparts:{}", mp));
+        }
+        if let Some(ap) = self.amountpart_msat {
+            parts.push(format!("amountpart_msat:{}", ap));
+        }
+        if let Some(rs) = self.randomsplit {
+            parts.push(format!("randomsplit:{}", rs));
+        }
+        if let Some(mp) = self.minprobability {
+            parts.push(format!("minprobability:{}", mp));
+        }
+        if let Some(sof) = self.splitonfail {
+            parts.push(format!("splitonfail:{}", sof));
+        }
+        if let Some(s) = &self.schedule {
+            parts.push(format!("schedule:{}", s));
+        }
 
         write!(f, "{}", parts.join(" "))
     }
 }
 
 impl Job {
+    /// Ceiling on the number of parts `get_parts` will split a rebalance into when
+    /// `amountpart_msat` is set without an explicit `maxparts`.
+    const DEFAULT_MAX_PARTS: u8 = 16;
+
     pub fn new(
         sat_direction: SatDirection,
         amount_msat: u64,
@@ -118,6 +219,7 @@ impl Job {
             amount_msat,
             outppm,
             maxppm,
+            maxfee_msat: None,
             candidates: None,
             target: None,
             maxhops: None,
@@ -125,6 +227,12 @@ impl Job {
             depleteuptoamount_msat: None,
             paralleljobs: None,
             onceamount_msat: None,
+            maxparts: None,
+            amountpart_msat: None,
+            randomsplit: None,
+            minprobability: None,
+            splitonfail: None,
+            schedule: None,
         }
     }
     pub fn add_candidates(&mut self, candidates: Vec<ShortChannelId>) {
@@ -133,6 +241,9 @@ impl Job {
     pub fn add_target(&mut self, target: f64) {
         self.target = Some(target);
     }
+    pub fn add_maxfee_msat(&mut self, maxfee_msat: u64) {
+        self.maxfee_msat = Some(maxfee_msat);
+    }
     pub fn add_maxhops(&mut self, maxhops: u8) {
         self.maxhops = Some(maxhops);
     }
@@ -148,6 +259,27 @@ impl Job {
     pub fn add_onceamount_msat(&mut self, amount_msat: u64) {
         self.onceamount_msat = Some(amount_msat);
     }
+    pub fn add_maxparts(&mut self, maxparts: u8) {
+        self.maxparts = Some(maxparts);
+    }
+    pub fn add_amountpart_msat(&mut self, amountpart_msat: u64) {
+        self.amountpart_msat = Some(amountpart_msat);
+    }
+    pub fn add_randomsplit(&mut self, randomsplit: bool) {
+        self.randomsplit = Some(randomsplit);
+    }
+    pub fn add_minprobability(&mut self, minprobability: f64) {
+        self.minprobability = Some(minprobability);
+    }
+    pub fn add_splitonfail(&mut self, splitonfail: bool) {
+        self.splitonfail = Some(splitonfail);
+    }
+    pub fn add_schedule(&mut self, schedule: String) {
+        self.schedule = Some(schedule);
+    }
+    pub fn get_schedule(&self) -> Option<&str> {
+        self.schedule.as_deref()
+    }
     pub fn is_balanced(
         &self,
         channel: &ListpeerchannelsChannels,
@@ -191,6 +323,11 @@ impl Job {
         }
         target_cap
     }
+    /// Absolute fee ceiling for this rebalance, in addition to `maxppm`. `None` (the default)
+    /// means only `maxppm` bounds the cost, same as before this cap existed.
+    pub fn get_maxfee_msat(&self) -> Option<u64> {
+        self.maxfee_msat
+    }
     pub fn get_maxhops(&self, config_maxhops: u8) -> u8 {
         if let Some(mh) = self.maxhops {
             mh + 1
@@ -222,6 +359,41 @@ impl Job {
             config_paralleljobs
         }
     }
+    /// Number of disjoint paths to split `amount_msat` across for this job. `1` (the
+    /// default) keeps the existing single-route behavior.
+    pub fn get_maxparts(&self) -> u8 {
+        self.maxparts.unwrap_or(1).max(1)
+    }
+    /// Effective number of parts to split this rebalance attempt across, combining
+    /// `amountpart_msat` (a target size per part) with `maxparts` (a ceiling on the count).
+    /// If only `maxparts` is set, every part is that size's share of `amount_msat`, same as
+    /// [`Job::get_maxparts`] always did. If `amountpart_msat` is also set, it takes priority:
+    /// the number of parts becomes however many of that size it takes to cover `amount_msat`,
+    /// capped at `maxparts` (or [`Job::DEFAULT_MAX_PARTS`] if `maxparts` wasn't set either).
+    pub fn get_parts(&self) -> u8 {
+        let Some(amountpart_msat) = self.amountpart_msat.filter(|a| *a > 0) else {
+            return self.get_maxparts();
+        };
+        let cap = u64::from(self.maxparts.unwrap_or(Self::DEFAULT_MAX_PARTS).max(1));
+        self.amount_msat.div_ceil(amountpart_msat).clamp(1, cap) as u8
+    }
+    /// Whether part sizes should be randomized instead of split evenly, so an outside
+    /// observer watching HTLC amounts can't fingerprint the split as a rebalance probe.
+    pub fn get_randomsplit(&self) -> bool {
+        self.randomsplit.unwrap_or(false)
+    }
+    /// Minimum estimated end-to-end success probability a candidate route must clear to be
+    /// attempted. `0.0` (the default) disables the prune, matching pre-existing jobs that
+    /// never set it.
+    pub fn get_minprobability(&self) -> f64 {
+        self.minprobability.unwrap_or(0.0)
+    }
+    /// Whether a multi-part job (`maxparts`/`amountpart_msat` > 1) should only actually split
+    /// once a single full-amount route fails, rather than always splitting. `false` (the
+    /// default) keeps splitting unconditionally, same as before this option existed.
+    pub fn get_splitonfail(&self) -> bool {
+        self.splitonfail.unwrap_or(false)
+    }
     pub fn get_candidates(&self) -> Vec<ShortChannelId> {
         if let Some(c) = &self.candidates {
             c.clone()
@@ -327,6 +499,8 @@ pub struct SuccessesInTimeWindow {
     pub feeppm_max: u32,
     pub feeppm_median: u32,
     pub feeppm_90th_percentile: u32,
+    pub feeppm_95th: u32,
+    pub feeppm_99th: u32,
     #[tabled(display("Self::display_partners"))]
     pub top_5_channel_partners: Vec<ChannelPartnerStats>,
     #[tabled(display("tabled::derive::display::option", "N/A"))]
@@ -346,8 +520,31 @@ impl SuccessesInTimeWindow {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Tabled)]
+pub struct LifetimeStats {
+    pub total_rebalances: u64,
+    pub total_failed_attempts: u64,
+    pub total_rebalanced_sats: u64,
+    pub total_fees_paid_sats: u64,
+    pub avg_attempts_per_success: f64,
+}
+
+/// One row of `sling-stats`' `bucket=day`/`bucket=hour` time series: rebalances and fees
+/// aggregated into a single fixed-width window instead of `SuccessesInTimeWindow`'s single
+/// whole-window total, so the series can be charted instead of just read as a point-in-time
+/// summary.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Tabled)]
+pub struct BucketedRebalanceStats {
+    pub time_bucket: String,
+    pub total_amount_sats: u64,
+    pub feeppm_weighted_avg: u64,
+    pub total_rebalances: u64,
+    pub total_failed_attempts: u64,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct SlingStats {
     pub successes_in_time_window: Option<SuccessesInTimeWindow>,
     pub failures_in_time_window: Option<FailuresInTimeWindow>,
+    pub lifetime_stats: Option<LifetimeStats>,
 }
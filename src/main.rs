@@ -4,7 +4,7 @@ use mimalloc::MiMalloc;
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-use std::{path::Path, time::Duration};
+use std::{collections::HashSet, path::Path, time::Duration};
 
 use anyhow::anyhow;
 use cln_plugin::{
@@ -16,6 +16,7 @@ use cln_plugin::{
         DefaultStringConfigOption,
     },
     Builder,
+    CustomNotificationTopic,
     RpcMethodBuilder,
 };
 use config::*;
@@ -24,22 +25,30 @@ use model::*;
 use notifications::*;
 use rpc_sling::*;
 use serde_json::json;
+use sling::ExceptDirection;
 use stats::*;
 use tokio::{self, time};
 use util::*;
 
 mod config;
+mod coordination;
 mod dijkstra;
 mod errors;
+mod funding;
 mod gossip;
 mod htlc;
+mod metrics;
 mod model;
 mod notifications;
 mod parse;
+mod probe;
 mod response;
+mod rgs;
 mod rpc_sling;
+mod scheduler;
 mod slings;
 mod stats;
+mod store;
 mod tasks;
 mod util;
 
@@ -48,24 +57,70 @@ mod tests;
 
 const OPT_REFRESH_ALIASMAP_INTERVAL: &str = "sling-refresh-aliasmap-interval";
 const OPT_RESET_LIQUIDITY_INTERVAL: &str = "sling-reset-liquidity-interval";
+const OPT_LIQUIDITY_COMPACT_INTERVAL: &str = "sling-liquidity-compact-interval";
+const OPT_GRAPH_SNAPSHOT_INTERVAL: &str = "sling-graph-snapshot-interval";
 const OPT_DEPLETEUPTOPERCENT: &str = "sling-depleteuptopercent";
 const OPT_DEPLETEUPTOAMOUNT: &str = "sling-depleteuptoamount";
 const OPT_MAXHOPS: &str = "sling-maxhops";
 const OPT_CANDIDATES_MIN_AGE: &str = "sling-candidates-min-age";
 const OPT_PARALLELJOBS: &str = "sling-paralleljobs";
 const OPT_TIMEOUTPAY: &str = "sling-timeoutpay";
+const OPT_TIMEOUT_ROUTE_SEARCH: &str = "sling-timeout-route-search";
 const OPT_MAX_HTLC_COUNT: &str = "sling-max-htlc-count";
 const OPT_STATS_DELETE_FAILURES_AGE: &str = "sling-stats-delete-failures-age";
 const OPT_STATS_DELETE_FAILURES_SIZE: &str = "sling-stats-delete-failures-size";
 const OPT_STATS_DELETE_SUCCESSES_AGE: &str = "sling-stats-delete-successes-age";
 const OPT_STATS_DELETE_SUCCESSES_SIZE: &str = "sling-stats-delete-successes-size";
 const OPT_INFORM_LAYERS: &str = "sling-inform-layers";
+const OPT_LIQUIDITY_HALFLIFE: &str = "sling-liquidity-halflife";
+const OPT_LIQUIDITY_MAX_AGE: &str = "sling-liquidity-max-age";
+const OPT_LIQUIDITY_PENALTY_MULTIPLIER: &str = "sling-liquidity-penalty-multiplier";
+const OPT_LIQUIDITY_PROBABILISTIC_SCORING: &str = "sling-liquidity-probabilistic-scoring";
+const OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY: &str = "sling-min-candidate-success-probability";
+const OPT_CANDIDATE_FEE_WEIGHT: &str = "sling-candidate-fee-weight";
+const OPT_COORDINATE_REBALANCES: &str = "sling-coordinate-rebalances";
+const OPT_COORD_NEGOTIATION_TIMEOUT_SECS: &str = "sling-coord-negotiation-timeout-secs";
+const OPT_BACKOFF_BASE_SECS: &str = "sling-backoff-base-secs";
+const OPT_BACKOFF_MAX_SECS: &str = "sling-backoff-max-secs";
+const OPT_JOB_RETRY_BASE_SECS: &str = "sling-job-retry-base-secs";
+const OPT_JOB_RETRY_MAX_SECS: &str = "sling-job-retry-max-secs";
+const OPT_JOB_RETRY_MAX_ATTEMPTS: &str = "sling-job-retry-max-attempts";
+const OPT_TRANQUILITY: &str = "sling-tranquility";
+const OPT_METRICS_BIND: &str = "sling-metrics-bind";
+const OPT_ROUTE_WORKERS: &str = "sling-route-workers";
+const OPT_SEND_WORKERS: &str = "sling-send-workers";
+const OPT_CANDIDATE_WORKERS: &str = "sling-candidate-workers";
+const OPT_DIJKSTRA_BIDIRECTIONAL: &str = "sling-dijkstra-bidirectional";
+const OPT_PROBE_ENABLED: &str = "sling-probe-enabled";
+const OPT_PROBE_INTERVAL_SECS: &str = "sling-probe-interval-secs";
+const OPT_ASKRENE_PUBLISH_ENABLED: &str = "sling-askrene-publish-enabled";
+const OPT_ASKRENE_PUBLISH_LAYER: &str = "sling-askrene-publish-layer";
+const OPT_RGS_URL: &str = "sling-rgs-url";
+const OPT_RGS_INTERVAL_SECS: &str = "sling-rgs-interval-secs";
+const OPT_STALE_CHANNEL_HORIZON_SECS: &str = "sling-stale-channel-horizon-secs";
+const OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS: &str = "sling-incomplete-channel-timeout-secs";
+const OPT_VERIFY_CHANNEL_FUNDING: &str = "sling-verify-channel-funding";
+const OPT_FUNDING_VERIFICATION_BATCH_SIZE: &str = "sling-funding-verification-batch-size";
+const OPT_FUNDING_VERIFICATION_INTERVAL_SECS: &str = "sling-funding-verification-interval-secs";
+const OPT_REQUIRED_NODE_FEATURE_BIT: &str = "sling-required-node-feature-bit";
 const OPT_AUTOGO: DefaultBooleanConfigOption = ConfigOption::new_bool_with_default(
     "sling-autogo",
     false,
     "Automatically start all jobs on startup. Default is `false`",
 );
 
+fn read_worker_option(
+    plugin: &cln_plugin::ConfiguredPlugin<PluginState, tokio::io::Stdin, tokio::io::Stdout>,
+    opt_name: &str,
+) -> Result<u16, anyhow::Error> {
+    let value = plugin
+        .option_str(opt_name)?
+        .ok_or_else(|| anyhow!("{opt_name} has no value"))?
+        .as_i64()
+        .ok_or_else(|| anyhow!("{opt_name} must be an integer"))?;
+    u16::try_from(options_value_to_u64(opt_name, value, 1, None)?).map_err(anyhow::Error::from)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     std::env::set_var(
@@ -75,18 +130,35 @@ async fn main() -> Result<(), anyhow::Error> {
     log_panics::init();
     let state;
     let confplugin;
-    let opt_refresh_aliasmap_interval: DefaultIntegerConfigOption =
+    let opt_refresh_aliasmap_interval: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_REFRESH_ALIASMAP_INTERVAL,
+        "3600",
+        "Refresh interval for aliasmap task in seconds. Accepts a human-readable duration \
+         (e.g. `30m`, `12h`, `7d`, `2w`) or a bare number of seconds. Default is `3600`",
+    )
+    .dynamic();
+    let opt_reset_liquidity_interval: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_RESET_LIQUIDITY_INTERVAL,
+        "360",
+        "Refresh interval for liquidity reset task. Accepts a human-readable duration \
+         (e.g. `30m`, `12h`, `7d`, `2w`) or a bare number of minutes. Default is `360`",
+    )
+    .dynamic();
+    let opt_liquidity_compact_interval: DefaultIntegerConfigOption =
         ConfigOption::new_i64_with_default(
-            OPT_REFRESH_ALIASMAP_INTERVAL,
+            OPT_LIQUIDITY_COMPACT_INTERVAL,
             3600,
-            "Refresh interval for aliasmap task. Default is `3600`",
+            "Interval in seconds at which the liquidity journal is compacted into a fresh \
+             snapshot. Default is `3600`",
         )
         .dynamic();
-    let opt_reset_liquidity_interval: DefaultIntegerConfigOption =
+    let opt_graph_snapshot_interval: DefaultIntegerConfigOption =
         ConfigOption::new_i64_with_default(
-            OPT_RESET_LIQUIDITY_INTERVAL,
-            360,
-            "Refresh interval for liquidity reset task. Default is `360`",
+            OPT_GRAPH_SNAPSHOT_INTERVAL,
+            900,
+            "Interval in seconds at which a compact snapshot of the gossip graph is persisted, \
+             so a restart can resume parsing `gossip_store` from where it left off instead of \
+             reparsing the whole file. Default is `900`",
         )
         .dynamic();
     let opt_depleteuptopercent: DefaultStringConfigOption = ConfigOption::new_str_with_default(
@@ -104,7 +176,7 @@ async fn main() -> Result<(), anyhow::Error> {
     let opt_maxhops: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
         OPT_MAXHOPS,
         8,
-        "Maximum number of hops in a route. Default is `8`",
+        "Maximum number of hops in a route. Default is `8`, must be between `2` and `20`",
     )
     .dynamic();
     let opt_candidates_min_age: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
@@ -116,7 +188,7 @@ async fn main() -> Result<(), anyhow::Error> {
     let opt_paralleljobs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
         OPT_PARALLELJOBS,
         1,
-        "Number of parallel tasks for a job. Default is `1`",
+        "Number of parallel tasks for a job. Default is `1`, must be between `1` and `100`",
     )
     .dynamic();
     let opt_timeoutpay: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
@@ -125,19 +197,27 @@ async fn main() -> Result<(), anyhow::Error> {
         "Timeout for rebalances until we give up and continue. Default is `120`",
     )
     .dynamic();
+    let opt_timeout_route_search: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_TIMEOUT_ROUTE_SEARCH,
+        30,
+        "Timeout in seconds for a single dijkstra route search before we give up on that \
+         attempt and continue. Default is `30`",
+    )
+    .dynamic();
     let opt_max_htlc_count: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
         OPT_MAX_HTLC_COUNT,
         5,
-        "Max number of htlc allowed pending in job and candidate. Default is `5`",
+        "Max number of htlc allowed pending in job and candidate. Default is `5`, must be \
+         between `1` and `483`",
+    )
+    .dynamic();
+    let opt_stats_delete_failures_age: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_STATS_DELETE_FAILURES_AGE,
+        "30",
+        "Max age of failure stats. Accepts a human-readable duration (e.g. `30m`, `12h`, `7d`, \
+         `2w`) or a bare number of days. Default is `30`",
     )
     .dynamic();
-    let opt_stats_delete_failures_age: DefaultIntegerConfigOption =
-        ConfigOption::new_i64_with_default(
-            OPT_STATS_DELETE_FAILURES_AGE,
-            30,
-            "Max age of failure stats in days. Default is `30`",
-        )
-        .dynamic();
     let opt_stats_delete_failures_size: DefaultIntegerConfigOption =
         ConfigOption::new_i64_with_default(
             OPT_STATS_DELETE_FAILURES_SIZE,
@@ -145,13 +225,13 @@ async fn main() -> Result<(), anyhow::Error> {
             "Max number of failure stats per channel. Default is `10000`",
         )
         .dynamic();
-    let opt_stats_delete_successes_age: DefaultIntegerConfigOption =
-        ConfigOption::new_i64_with_default(
-            OPT_STATS_DELETE_SUCCESSES_AGE,
-            30,
-            "Max age of success stats in days. Default is `30`",
-        )
-        .dynamic();
+    let opt_stats_delete_successes_age: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_STATS_DELETE_SUCCESSES_AGE,
+        "30",
+        "Max age of success stats. Accepts a human-readable duration (e.g. `30m`, `12h`, `7d`, \
+         `2w`) or a bare number of days. Default is `30`",
+    )
+    .dynamic();
     let opt_stats_delete_successes_size: DefaultIntegerConfigOption =
         ConfigOption::new_i64_with_default(
             OPT_STATS_DELETE_SUCCESSES_SIZE,
@@ -163,33 +243,308 @@ async fn main() -> Result<(), anyhow::Error> {
         OPT_INFORM_LAYERS,
         "xpay",
         "Inform these layers about our information we gather from rebalances. \
-         Can be stated multiple times",
+         Can be stated multiple times. Via `setconfig` also accepts a comma-separated list; \
+         prefix it with `+` to add to the existing layers instead of replacing them",
+    )
+    .dynamic();
+    let opt_liquidity_halflife: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_LIQUIDITY_HALFLIFE,
+        43_200,
+        "Half-life in seconds for decaying learned liquidity bounds back towards \
+         `[0, capacity]`. Default is `43200` (12h)",
+    )
+    .dynamic();
+    let opt_liquidity_max_age: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_LIQUIDITY_MAX_AGE,
+        0,
+        "Discard a learned liquidity estimate entirely on startup if it's older than this many \
+         seconds, instead of decaying it (see `sling-liquidity-halflife`) and keeping it around. \
+         `0` disables this and keeps every estimate regardless of age. Default is `0`",
+    )
+    .dynamic();
+    let opt_liquidity_penalty_multiplier: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_LIQUIDITY_PENALTY_MULTIPLIER,
+            200,
+            "Scale in ppm-ish units for the routing cost penalty added for channels with an \
+             uncertain learned liquidity (`-ln(probability) * multiplier`). Higher values make \
+             the rebalancer avoid recently-failed/uncertain channels more aggressively. Default \
+             is `200`",
+        )
+        .dynamic();
+    let opt_liquidity_probabilistic_scoring: DefaultBooleanConfigOption =
+        ConfigOption::new_bool_with_default(
+            OPT_LIQUIDITY_PROBABILISTIC_SCORING,
+            true,
+            "Bias `dijkstra` route selection towards channels our learned liquidity bounds \
+             make more likely to succeed, by adding a routing cost penalty for uncertain \
+             channels (see `sling-liquidity-penalty-multiplier`). Disabling this falls back to \
+             pure fee-based scoring. Default is `true`",
+        )
+        .dynamic();
+    let opt_min_candidate_success_probability: DefaultStringConfigOption =
+        ConfigOption::new_str_with_default(
+            OPT_MIN_CANDIDATE_SUCCESS_PROBABILITY,
+            "0.05",
+            "Minimum learned success probability (see `sling-liquidity-halflife`) a channel \
+             must clear to be offered as a routing candidate at all. Default is `0.05`",
+        )
+        .dynamic();
+    let opt_candidate_fee_weight: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_CANDIDATE_FEE_WEIGHT,
+        "1.0",
+        "How heavily `build_candidatelist` weighs a candidate's effective fee ppm against its \
+         learned success probability when ranking which candidate to try first. `0.0` ranks \
+         purely by success probability; higher values let a cheaper but less certain candidate \
+         outrank a pricier sure thing. Default is `1.0`",
+    )
+    .dynamic();
+    let opt_coordinate_rebalances: DefaultBooleanConfigOption = ConfigOption::new_bool_with_default(
+        OPT_COORDINATE_REBALANCES,
+        false,
+        "Before starting a job against a directly-connected peer, ask it over a custom message \
+         (see `crate::coordination`) to confirm it has matching liquidity and agrees on fee, and \
+         back off like a `PeerNotReady` tempban if it explicitly declines. No-ops against peers \
+         that don't answer, e.g. ones not running sling. Default is `false`",
+    )
+    .dynamic();
+    let opt_coord_negotiation_timeout_secs: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_COORD_NEGOTIATION_TIMEOUT_SECS,
+            5,
+            "Seconds to wait for a peer's ack/nack to a `sling-coordinate-rebalances` request \
+             before giving up and treating it as unsupported. Default is `5`",
+        )
+        .dynamic();
+    let opt_backoff_base_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_BACKOFF_BASE_SECS,
+        30,
+        "Base delay in seconds before retrying a channel after a temporary BOLT04 failure, \
+         doubling with each consecutive failure. Default is `30`",
+    )
+    .dynamic();
+    let opt_backoff_max_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_BACKOFF_MAX_SECS,
+        3_600,
+        "Ceiling in seconds for the per-channel temporary failure backoff. Default is `3600` (1h)",
+    )
+    .dynamic();
+    let opt_job_retry_base_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_JOB_RETRY_BASE_SECS,
+        60,
+        "Base delay in seconds before the scheduler retries a job task that exited in `Error`, \
+         doubling with each consecutive failure. Default is `60`",
+    )
+    .dynamic();
+    let opt_job_retry_max_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_JOB_RETRY_MAX_SECS,
+        3_600,
+        "Ceiling in seconds for `sling-job-retry-base-secs`'s exponential backoff. Default is \
+         `3600` (1h)",
+    )
+    .dynamic();
+    let opt_job_retry_max_attempts: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_JOB_RETRY_MAX_ATTEMPTS,
+        10,
+        "Consecutive task-exit failures after which the task is left dead in `Error` instead of \
+         being retried again. Default is `10`",
+    )
+    .dynamic();
+    let opt_tranquility: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_TRANQUILITY,
+        "1.0",
+        "Idle multiplier applied to the last attempt's duration between rebalance attempts. \
+         E.g. `2.0` spends roughly two thirds of the time idle. Default is `1.0`",
+    )
+    .dynamic();
+    let opt_metrics_bind: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_METRICS_BIND,
+        "",
+        "Bind address (e.g. `127.0.0.1:9321`) for the Prometheus metrics exporter. \
+         Empty disables the exporter. Default is ``",
+    );
+    let opt_route_workers: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_ROUTE_WORKERS,
+        4,
+        "Max number of dijkstra route searches allowed to run concurrently across all jobs. \
+         Read once at startup. Default is `4`",
+    );
+    let opt_send_workers: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_SEND_WORKERS,
+        4,
+        "Max number of sendpay/waitsendpay calls allowed to be in flight concurrently across \
+         all jobs. Read once at startup. Default is `4`",
+    );
+    let opt_candidate_workers: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_CANDIDATE_WORKERS,
+        4,
+        "Max number of OS threads `dijkstra_per_candidate` may use to search routes for \
+         different candidates concurrently. Read once at startup. Default is `4`",
     );
+    let opt_dijkstra_bidirectional: DefaultBooleanConfigOption =
+        ConfigOption::new_bool_with_default(
+            OPT_DIJKSTRA_BIDIRECTIONAL,
+            false,
+            "Use a bidirectional meet-in-the-middle search instead of the plain single-source \
+             `dijkstra`, which typically settles far fewer nodes on a large graph. Falls back \
+             to the plain search if no meeting node is found, so this is always at least as \
+             correct. Default is `false`",
+        )
+        .dynamic();
+    let opt_probe_enabled: DefaultBooleanConfigOption = ConfigOption::new_bool_with_default(
+        OPT_PROBE_ENABLED,
+        false,
+        "Periodically probe channels with unreachable payment hashes to learn their liquidity \
+         without moving funds, instead of only learning from real rebalance attempts. Default \
+         is `false`",
+    )
+    .dynamic();
+    let opt_probe_interval_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_PROBE_INTERVAL_SECS,
+        1_800,
+        "Interval in seconds between liquidity probes when `sling-probe-enabled` is `true`. \
+         Default is `1800` (30m)",
+    )
+    .dynamic();
+    let opt_askrene_publish_enabled: DefaultBooleanConfigOption = ConfigOption::new_bool_with_default(
+        OPT_ASKRENE_PUBLISH_ENABLED,
+        false,
+        "Publish sling's own learned liquidity beliefs into an askrene layer, so other \
+         pathfinders (xpay, getroutes) can use them too. Default is `false`",
+    )
+    .dynamic();
+    let opt_askrene_publish_layer: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_ASKRENE_PUBLISH_LAYER,
+        "sling",
+        "Name of the askrene layer sling publishes its liquidity beliefs into when \
+         `sling-askrene-publish-enabled` is `true`. Default is `sling`",
+    )
+    .dynamic();
+    let opt_rgs_url: DefaultStringConfigOption = ConfigOption::new_str_with_default(
+        OPT_RGS_URL,
+        "",
+        "Base URL of a Rapid Gossip Sync server to bootstrap/refresh the public graph from \
+         (e.g. `https://rapidsync.lightningdevkit.org/snapshot`). Empty disables it. Default is ``",
+    )
+    .dynamic();
+    let opt_rgs_interval_secs: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_RGS_INTERVAL_SECS,
+        3_600,
+        "Interval in seconds between Rapid Gossip Sync refreshes when `sling-rgs-url` is set. \
+         Default is `3600` (1h)",
+    )
+    .dynamic();
+    let opt_stale_channel_horizon_secs: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_STALE_CHANNEL_HORIZON_SECS,
+            60 * 60 * 24 * 14,
+            "Channel directions whose newest gossip update is older than this many seconds are \
+             pruned from the routing graph, per BOLT 7's 14-day staleness rule. Default is \
+             `1209600` (14d)",
+        )
+        .dynamic();
+    let opt_incomplete_channel_timeout_secs: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_INCOMPLETE_CHANNEL_TIMEOUT_SECS,
+            60 * 60 * 24,
+            "An incomplete channel (has an announcement or an update but not both) is dropped \
+             if it sits this many seconds without completing. Default is `86400` (1d)",
+        )
+        .dynamic();
+    let opt_verify_channel_funding: DefaultBooleanConfigOption = ConfigOption::new_bool_with_default(
+        OPT_VERIFY_CHANNEL_FUNDING,
+        false,
+        "Resolve each newly gossip-learned channel's funding output on-chain before trusting it \
+         for routing, dropping it if the output doesn't exist or isn't a witness output. \
+         Needs a working chain backend (`getrawblockbyheight`). Default is `false`",
+    )
+    .dynamic();
+    let opt_funding_verification_batch_size: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_FUNDING_VERIFICATION_BATCH_SIZE,
+            25,
+            "Max number of queued channels `sling-verify-channel-funding` resolves per run. \
+             Default is `25`",
+        )
+        .dynamic();
+    let opt_funding_verification_interval_secs: DefaultIntegerConfigOption =
+        ConfigOption::new_i64_with_default(
+            OPT_FUNDING_VERIFICATION_INTERVAL_SECS,
+            60,
+            "Interval in seconds between funding-verification batches when \
+             `sling-verify-channel-funding` is `true`. Default is `60`",
+        )
+        .dynamic();
+    let opt_required_node_feature_bit: DefaultIntegerConfigOption = ConfigOption::new_i64_with_default(
+        OPT_REQUIRED_NODE_FEATURE_BIT,
+        -1,
+        "BOLT 9 feature number a hop's node must advertise (either the compulsory or optional \
+         bit of the pair) to be used as a routing hop; nodes we've never seen a \
+         node_announcement for are let through. `-1` disables the filter. Default is `-1`",
+    )
+    .dynamic();
     match Builder::new(tokio::io::stdin(), tokio::io::stdout())
         .hook("htlc_accepted", htlc_handler)
+        .hook("custommsg", coordination::custommsg_handler)
         .subscribe("block_added", block_added)
+        .notification(CustomNotificationTopic::new(REBALANCE_NOTIFICATION_TOPIC))
         .option(opt_refresh_aliasmap_interval)
         .option(opt_reset_liquidity_interval)
+        .option(opt_liquidity_compact_interval)
+        .option(opt_graph_snapshot_interval)
         .option(opt_depleteuptopercent)
         .option(opt_depleteuptoamount)
         .option(opt_maxhops)
         .option(opt_candidates_min_age)
         .option(opt_paralleljobs)
         .option(opt_timeoutpay)
+        .option(opt_timeout_route_search)
         .option(opt_max_htlc_count)
         .option(opt_stats_delete_failures_age)
         .option(opt_stats_delete_failures_size)
         .option(opt_stats_delete_successes_age)
         .option(opt_stats_delete_successes_size)
         .option(opt_inform_layers)
+        .option(opt_liquidity_halflife)
+        .option(opt_liquidity_max_age)
+        .option(opt_liquidity_penalty_multiplier)
+        .option(opt_liquidity_probabilistic_scoring)
+        .option(opt_min_candidate_success_probability)
+        .option(opt_candidate_fee_weight)
+        .option(opt_coordinate_rebalances)
+        .option(opt_coord_negotiation_timeout_secs)
+        .option(opt_backoff_base_secs)
+        .option(opt_backoff_max_secs)
+        .option(opt_job_retry_base_secs)
+        .option(opt_job_retry_max_secs)
+        .option(opt_job_retry_max_attempts)
+        .option(opt_tranquility)
+        .option(opt_metrics_bind)
+        .option(opt_route_workers)
+        .option(opt_send_workers)
+        .option(opt_candidate_workers)
+        .option(opt_dijkstra_bidirectional)
+        .option(opt_probe_enabled)
+        .option(opt_probe_interval_secs)
+        .option(opt_askrene_publish_enabled)
+        .option(opt_askrene_publish_layer)
+        .option(opt_rgs_url)
+        .option(opt_rgs_interval_secs)
+        .option(opt_stale_channel_horizon_secs)
+        .option(opt_incomplete_channel_timeout_secs)
+        .option(opt_verify_channel_funding)
+        .option(opt_funding_verification_batch_size)
+        .option(opt_funding_verification_interval_secs)
+        .option(opt_required_node_feature_bit)
         .option(OPT_AUTOGO)
         .setconfig_callback(setconfig_callback)
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-job"), slingjob)
                 .description("Add a sling job")
                 .usage(
-                    "-k scid direction amount maxppm [outppm] [target] [maxhops] \
-                [candidates] [depleteuptopercent] [depleteuptoamount] [paralleljobs]",
+                    "-k scid direction amount maxppm [maxfee] [outppm] [target] [maxhops] \
+                [candidates] [depleteuptopercent] [depleteuptoamount] [paralleljobs] \
+                [schedule]",
                 ),
         )
         .rpcmethod_from_builder(
@@ -200,6 +555,11 @@ async fn main() -> Result<(), anyhow::Error> {
             .description("Show settings of sling job(s)")
             .usage("[scid]"),
         )
+        .rpcmethod_from_builder(
+            RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-status"), slingstatus)
+                .description("Show live worker status of sling job(s)")
+                .usage("[scid]"),
+        )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-deletejob"), slingdeletejob)
                 .description("Delete sling job(s)")
@@ -208,41 +568,70 @@ async fn main() -> Result<(), anyhow::Error> {
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-go"), slinggo)
                 .description("Start sling job(s)")
-                .usage("[scid]"),
+                .usage("[scid]... | {direction|peer_alias} (all jobs if omitted)"),
         )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-stop"), slingstop)
                 .description("Stop sling job(s)")
+                .usage("[scid]... | {direction|peer_alias} (all running jobs if omitted)"),
+        )
+        .rpcmethod_from_builder(
+            RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-pause"), slingpause)
+                .description("Pause sling job(s) without stopping them")
+                .usage("[scid]"),
+        )
+        .rpcmethod_from_builder(
+            RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-resume"), slingresume)
+                .description("Resume paused sling job(s)")
                 .usage("[scid]"),
         )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-once"), slingonce)
                 .description("Run sling rebalance once with fixed amount")
                 .usage(
-                    "-k scid direction amount maxppm onceamount [outppm] [maxhops] \
+                    "-k scid direction amount maxppm onceamount [maxfee] [outppm] [maxhops] \
         [candidates] [depleteuptopercent] [depleteuptoamount] [paralleljobs]",
                 ),
         )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-stats"), slingstats)
                 .description("Show stats on channel(s)")
-                .usage("[scid] [json]"),
+                .usage(
+                    "[scid] [json] -k[start] [limit] [since] (scid required, paginated raw \
+                     records) [bucket=day|hour] [csv] (scid required, time series)",
+                ),
+        )
+        .rpcmethod_from_builder(
+            RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-history"), slinghistory)
+                .description("Query raw per-attempt rebalance history for a channel")
+                .usage("-k scid [since] [start] [limit] [outcome=success|failure]"),
         )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-except-chan"), slingexceptchan)
                 .description("Manage channels to avoid for all jobs")
-                .usage("command [scid]"),
+                .usage(
+                    "command [scid]... [pull|push|both] -k[expires_in|expires_at] \
+                     (add-only, permanent if omitted)",
+                ),
         )
         .rpcmethod_from_builder(
             RpcMethodBuilder::new(&(PLUGIN_NAME.to_string() + "-except-peer"), slingexceptpeer)
                 .description("Manage peers to avoid for all jobs")
-                .usage("command [id]"),
+                .usage(
+                    "command [id]... -k[expires_in|expires_at] \
+                     (add-only, permanent if omitted)",
+                ),
         )
         .rpcmethod(
             &(PLUGIN_NAME.to_string() + "-version"),
             "Print sling plugin version",
             slingversion,
         )
+        .rpcmethod(
+            &(PLUGIN_NAME.to_string() + "-listconfigs"),
+            "Show effective config values with their default, validated range and source",
+            slinglistconfigs,
+        )
         .subscribe("shutdown", shutdown_handler)
         .dynamic()
         .configure()
@@ -264,25 +653,77 @@ async fn main() -> Result<(), anyhow::Error> {
                 Ok(o) => o,
                 Err(e) => return plugin.disable(format!("{e}").as_str()).await,
             };
+            let now = now_secs();
+            let mut exclude_chans_pull = HashSet::new();
+            let mut exclude_chans_push = HashSet::new();
+            for (scid, except) in except_chans {
+                if except.is_expired(now) {
+                    continue;
+                }
+                match except.direction {
+                    ExceptDirection::Pull => {
+                        exclude_chans_pull.insert(scid);
+                    }
+                    ExceptDirection::Push => {
+                        exclude_chans_push.insert(scid);
+                    }
+                    ExceptDirection::Both => {
+                        exclude_chans_pull.insert(scid);
+                        exclude_chans_push.insert(scid);
+                    }
+                }
+            }
             let except_peers = match read_except_peers(&sling_dir).await {
                 Ok(o) => o
                     .into_iter()
-                    .map(|p| PubKeyBytes::from_pubkey(&p))
+                    .filter(|(_, expiry)| !expiry.is_some_and(|e| now >= e))
+                    .map(|(p, _)| PubKeyBytes::from_pubkey(&p))
                     .collect(),
                 Err(e) => return plugin.disable(format!("{e}").as_str()).await,
             };
-            let liquidity = match read_liquidity(&sling_dir).await {
+            // Read directly rather than through `Config`, which isn't built yet at this point
+            // (same reasoning as the worker pool sizes just below).
+            let liquidity_max_age = match plugin.option_str(OPT_LIQUIDITY_MAX_AGE) {
+                Ok(Some(v)) => match v.as_i64() {
+                    Some(v) => match options_value_to_u64(OPT_LIQUIDITY_MAX_AGE, v, 0, None) {
+                        Ok(v) => v,
+                        Err(e) => return plugin.disable(format!("{e}").as_str()).await,
+                    },
+                    None => {
+                        return plugin
+                            .disable(format!("{OPT_LIQUIDITY_MAX_AGE} must be an integer").as_str())
+                            .await
+                    }
+                },
+                Ok(None) => 0,
+                Err(e) => return plugin.disable(format!("{e}").as_str()).await,
+            };
+            let liquidity = match read_liquidity(&sling_dir, liquidity_max_age).await {
                 Ok(o) => o,
                 Err(e) => return plugin.disable(format!("{e}").as_str()).await,
             };
-            let config = Config::new(
+            let mut config = Config::new(
                 getinfo.clone(),
                 rpc_path,
                 sling_dir,
-                except_chans.clone(),
-                except_chans,
+                exclude_chans_pull,
+                exclude_chans_push,
                 except_peers,
             );
+            // Worker pool sizes are only read here, once, since the semaphores they size
+            // are created alongside the rest of `PluginState` and are not resized at runtime.
+            match read_worker_option(&plugin, OPT_ROUTE_WORKERS) {
+                Ok(w) => config.route_workers = w,
+                Err(e) => return plugin.disable(format!("{e}").as_str()).await,
+            };
+            match read_worker_option(&plugin, OPT_SEND_WORKERS) {
+                Ok(w) => config.send_workers = w,
+                Err(e) => return plugin.disable(format!("{e}").as_str()).await,
+            };
+            match read_worker_option(&plugin, OPT_CANDIDATE_WORKERS) {
+                Ok(w) => config.candidate_workers = w,
+                Err(e) => return plugin.disable(format!("{e}").as_str()).await,
+            };
             state = PluginState::new(config, liquidity);
             {
                 *state.blockheight.lock() = getinfo.blockheight;
@@ -298,6 +739,12 @@ async fn main() -> Result<(), anyhow::Error> {
     };
     if let Ok(plugin) = confplugin.start(state).await {
         log::debug!("{:?}", plugin.configuration());
+        let mppreconcileclone = plugin.clone();
+        tokio::spawn(async move {
+            if let Err(e) = tasks::reconcile_mpp_pays(mppreconcileclone.clone()).await {
+                log::warn!("Error reconciling in-flight MPP rebalances: {e:?}");
+            }
+        });
         let peersclone = plugin.clone();
         tokio::spawn(async move {
             match tasks::refresh_listpeerchannels_loop(peersclone.clone()).await {
@@ -330,6 +777,46 @@ async fn main() -> Result<(), anyhow::Error> {
             };
             let _res = liquidityclone.shutdown();
         });
+        let liquidityjournalclone = plugin.clone();
+        tokio::spawn(async move {
+            match tasks::compact_liquidity_journal(liquidityjournalclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in compact_liquidity_journal thread: {e:?}"),
+            };
+            let _res = liquidityjournalclone.shutdown();
+        });
+        let graphsnapshotclone = plugin.clone();
+        tokio::spawn(async move {
+            match tasks::compact_graph_snapshot(graphsnapshotclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in compact_graph_snapshot thread: {e:?}"),
+            };
+            let _res = graphsnapshotclone.shutdown();
+        });
+        let probeclone = plugin.clone();
+        tokio::spawn(async move {
+            match tasks::run_liquidity_probes(probeclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in run_liquidity_probes thread: {e:?}"),
+            };
+            let _res = probeclone.shutdown();
+        });
+        let rgsclone = plugin.clone();
+        tokio::spawn(async move {
+            match rgs::refresh_rgs(rgsclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in refresh_rgs thread: {e:?}"),
+            };
+            let _res = rgsclone.shutdown();
+        });
+        let fundingclone = plugin.clone();
+        tokio::spawn(async move {
+            match funding::verify_pending_funding(fundingclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in verify_pending_funding thread: {e:?}"),
+            };
+            let _res = fundingclone.shutdown();
+        });
         let tempbanclone = plugin.clone();
         tokio::spawn(async move {
             match tasks::clear_tempbans(tempbanclone.clone()).await {
@@ -346,6 +833,32 @@ async fn main() -> Result<(), anyhow::Error> {
             };
             let _res = clearstatsclone.shutdown();
         });
+        let schedulerclone = plugin.clone();
+        tokio::spawn(async move {
+            match scheduler::scheduler_loop(schedulerclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in scheduler thread: {e:?}"),
+            };
+            let _res = schedulerclone.shutdown();
+        });
+        let exceptexpiryclone = plugin.clone();
+        tokio::spawn(async move {
+            match tasks::clear_expired_excepts(exceptexpiryclone.clone()).await {
+                Ok(()) => (),
+                Err(e) => log::warn!("Error in clear_expired_excepts thread: {e:?}"),
+            };
+            let _res = exceptexpiryclone.shutdown();
+        });
+        if plugin.state().config.lock().metrics_bind_addr.is_some() {
+            let metricsclone = plugin.clone();
+            tokio::spawn(async move {
+                match metrics::metrics_server(metricsclone.clone()).await {
+                    Ok(()) => (),
+                    Err(e) => log::warn!("Error in metrics server thread: {e:?}"),
+                };
+                let _res = metricsclone.shutdown();
+            });
+        }
         if plugin.state().config.lock().at_or_above_24_11 {
             let askrene_clone = plugin.clone();
             tokio::spawn(async move {
@@ -355,6 +868,15 @@ async fn main() -> Result<(), anyhow::Error> {
                 };
                 let _res = askrene_clone.shutdown();
             });
+
+            let askrene_publish_clone = plugin.clone();
+            tokio::spawn(async move {
+                match tasks::publish_askrene_liquidity(askrene_publish_clone.clone()).await {
+                    Ok(()) => (),
+                    Err(e) => log::warn!("Error in publish_askrene_liquidity thread: {e:?}"),
+                };
+                let _res = askrene_publish_clone.shutdown();
+            });
         }
 
         if plugin.option(&OPT_AUTOGO).unwrap() {
@@ -0,0 +1,386 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::Error;
+use cln_plugin::Plugin;
+use cln_rpc::primitives::ShortChannelId;
+use hdrhistogram::Histogram;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{
+    model::{FailureReb, PluginState, SuccessReb, PLUGIN_NAME},
+    util::read_jobs,
+};
+
+/// Serves rebalancing metrics in Prometheus text-exposition format on the
+/// address configured via `sling-metrics-bind`. Returns immediately if no
+/// bind address is configured.
+pub async fn metrics_server(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let addr = match plugin.state().config.lock().metrics_bind_addr {
+        Some(a) => a,
+        None => return Ok(()),
+    };
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("metrics: exporter listening on {addr}");
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let plugin = plugin.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            // We only ever serve one document, so the request itself (method,
+            // path, headers) is irrelevant - just drain it off the socket.
+            let _ = stream.read(&mut buf).await;
+
+            let body = match render_metrics(&plugin).await {
+                Ok(body) => body,
+                Err(e) => {
+                    log::warn!("metrics: failed to render metrics: {e}");
+                    return;
+                }
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                log::warn!("metrics: failed to write response: {e}");
+            }
+        });
+    }
+}
+
+async fn render_metrics(plugin: &Plugin<PluginState>) -> Result<String, Error> {
+    let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+    let successes = SuccessReb::read_from_files(&sling_dir, None).await?;
+    let failures = FailureReb::read_from_files(&sling_dir, None).await?;
+    let jobs = read_jobs(&sling_dir, plugin.clone()).await?;
+    let peer_channels = plugin.state().peer_channels.lock().clone();
+
+    let mut scids: BTreeSet<ShortChannelId> = BTreeSet::new();
+    scids.extend(successes.keys());
+    scids.extend(failures.keys());
+    scids.extend(jobs.keys());
+
+    let peer_id_of = |scid: &ShortChannelId| {
+        peer_channels
+            .get(scid)
+            .map(|c| c.peer_id.to_string())
+            .unwrap_or_default()
+    };
+
+    let mut out = String::new();
+
+    out.push_str("# HELP sling_rebalanced_sats_total Total sats successfully rebalanced through a channel.\n");
+    out.push_str("# TYPE sling_rebalanced_sats_total counter\n");
+    for scid in &scids {
+        let sats: u64 = successes
+            .get(scid)
+            .map(|v| v.iter().map(|s| s.amount_msat / 1_000).sum())
+            .unwrap_or(0);
+        out.push_str(&format!(
+            "sling_rebalanced_sats_total{{scid=\"{scid}\",id=\"{}\"}} {sats}\n",
+            peer_id_of(scid)
+        ));
+    }
+
+    out.push_str("# HELP sling_fees_paid_msat_total Total fees paid in msat for successful rebalances through a channel.\n");
+    out.push_str("# TYPE sling_fees_paid_msat_total counter\n");
+    for scid in &scids {
+        let fee_msat: u64 = successes
+            .get(scid)
+            .map(|v| {
+                v.iter()
+                    .map(|s| s.amount_msat * s.fee_ppm as u64 / 1_000_000)
+                    .sum()
+            })
+            .unwrap_or(0);
+        out.push_str(&format!(
+            "sling_fees_paid_msat_total{{scid=\"{scid}\",id=\"{}\"}} {fee_msat}\n",
+            peer_id_of(scid)
+        ));
+    }
+
+    out.push_str(
+        "# HELP sling_rebalance_attempts_total Total rebalance attempts through a channel, by outcome.\n",
+    );
+    out.push_str("# TYPE sling_rebalance_attempts_total counter\n");
+    for scid in &scids {
+        let id = peer_id_of(scid);
+        let success_count = successes.get(scid).map(|v| v.len()).unwrap_or(0);
+        let failure_count = failures.get(scid).map(|v| v.len()).unwrap_or(0);
+        out.push_str(&format!(
+            "sling_rebalance_attempts_total{{scid=\"{scid}\",id=\"{id}\",outcome=\"success\"}} {success_count}\n"
+        ));
+        out.push_str(&format!(
+            "sling_rebalance_attempts_total{{scid=\"{scid}\",id=\"{id}\",outcome=\"failure\"}} {failure_count}\n"
+        ));
+    }
+
+    out.push_str("# HELP sling_weighted_fee_ppm Amount-weighted average fee-ppm paid for successful rebalances through a channel.\n");
+    out.push_str("# TYPE sling_weighted_fee_ppm gauge\n");
+    for scid in &scids {
+        let Some(succs) = successes.get(scid) else {
+            continue;
+        };
+        let total_amount_msat: u64 = succs.iter().map(|s| s.amount_msat).sum();
+        if total_amount_msat == 0 {
+            continue;
+        }
+        let weighted_fee_ppm: f64 = succs
+            .iter()
+            .map(|s| s.fee_ppm as f64 * s.amount_msat as f64)
+            .sum::<f64>()
+            / total_amount_msat as f64;
+        out.push_str(&format!(
+            "sling_weighted_fee_ppm{{scid=\"{scid}\",id=\"{}\"}} {weighted_fee_ppm}\n",
+            peer_id_of(scid)
+        ));
+    }
+
+    out.push_str("# HELP sling_rebalance_failures_total Rebalance failures through a channel, by reason and failing node.\n");
+    out.push_str("# TYPE sling_rebalance_failures_total counter\n");
+    for scid in &scids {
+        let Some(fails) = failures.get(scid) else {
+            continue;
+        };
+        let id = peer_id_of(scid);
+        let mut by_reason_and_node: std::collections::HashMap<(String, String), u64> =
+            std::collections::HashMap::new();
+        for f in fails {
+            *by_reason_and_node
+                .entry((f.failure_reason.to_string(), f.failure_node.to_string()))
+                .or_insert(0) += 1;
+        }
+        for ((reason, node), count) in by_reason_and_node {
+            out.push_str(&format!(
+                "sling_rebalance_failures_total{{scid=\"{scid}\",id=\"{id}\",failure_reason=\"{reason}\",failure_node=\"{node}\"}} {count}\n"
+            ));
+        }
+    }
+
+    out.push_str("# HELP sling_rebalance_hops Distribution of hop counts for rebalance attempts through a channel, by outcome.\n");
+    out.push_str("# TYPE sling_rebalance_hops histogram\n");
+    for scid in &scids {
+        let id = peer_id_of(scid);
+        if let Some(succs) = successes.get(scid) {
+            render_hop_histogram(&mut out, scid, &id, "success", succs.iter().map(|s| s.hops));
+        }
+        if let Some(fails) = failures.get(scid) {
+            render_hop_histogram(&mut out, scid, &id, "failure", fails.iter().map(|f| f.hops));
+        }
+    }
+
+    out.push_str(
+        "# HELP sling_htlcs_attempted_total Total HTLCs attempted (successful and failed) for a channel.\n",
+    );
+    out.push_str("# TYPE sling_htlcs_attempted_total counter\n");
+    for scid in &scids {
+        let htlcs = successes.get(scid).map(|v| v.len()).unwrap_or(0)
+            + failures.get(scid).map(|v| v.len()).unwrap_or(0);
+        out.push_str(&format!(
+            "sling_htlcs_attempted_total{{scid=\"{scid}\",id=\"{}\"}} {htlcs}\n",
+            peer_id_of(scid)
+        ));
+    }
+
+    out.push_str("# HELP sling_job_active Whether a job is currently configured for a channel, by direction.\n");
+    out.push_str("# TYPE sling_job_active gauge\n");
+    for scid in &scids {
+        let id = peer_id_of(scid);
+        let (pull, push) = match jobs.get(scid) {
+            Some(job) => match job.sat_direction {
+                sling::SatDirection::Pull => (1, 0),
+                sling::SatDirection::Push => (0, 1),
+            },
+            None => (0, 0),
+        };
+        out.push_str(&format!(
+            "sling_job_active{{scid=\"{scid}\",id=\"{id}\",direction=\"pull\"}} {pull}\n"
+        ));
+        out.push_str(&format!(
+            "sling_job_active{{scid=\"{scid}\",id=\"{id}\",direction=\"push\"}} {push}\n"
+        ));
+    }
+
+    {
+        let config = plugin.state().config.lock();
+        out.push_str(
+            "# HELP sling_excepted_channels Number of channels currently excepted from rebalancing, by direction.\n",
+        );
+        out.push_str("# TYPE sling_excepted_channels gauge\n");
+        out.push_str(&format!(
+            "sling_excepted_channels{{direction=\"pull\"}} {}\n",
+            config.exclude_chans_pull.len()
+        ));
+        out.push_str(&format!(
+            "sling_excepted_channels{{direction=\"push\"}} {}\n",
+            config.exclude_chans_push.len()
+        ));
+        out.push_str("# HELP sling_excepted_peers Number of peers currently excepted from rebalancing.\n");
+        out.push_str("# TYPE sling_excepted_peers gauge\n");
+        out.push_str(&format!(
+            "sling_excepted_peers {}\n",
+            config.exclude_peers.len()
+        ));
+    }
+
+    {
+        let graph = plugin.state().graph.lock();
+        out.push_str("# HELP sling_graph_public_channels Public channels currently known in sling's routing graph.\n");
+        out.push_str("# TYPE sling_graph_public_channels gauge\n");
+        out.push_str(&format!(
+            "sling_graph_public_channels {}\n",
+            graph.public_channel_count()
+        ));
+        out.push_str("# HELP sling_graph_private_channels Private channels currently known in sling's routing graph.\n");
+        out.push_str("# TYPE sling_graph_private_channels gauge\n");
+        out.push_str(&format!(
+            "sling_graph_private_channels {}\n",
+            graph.private_channel_count()
+        ));
+        out.push_str(
+            "# HELP sling_rejected_announcements Gossip-learned channel announcements dropped for failing on-chain funding-output validation.\n",
+        );
+        out.push_str("# TYPE sling_rejected_announcements counter\n");
+        out.push_str(&format!(
+            "sling_rejected_announcements {}\n",
+            graph.rejected_announcement_count()
+        ));
+    }
+    out.push_str("# HELP sling_liquidity_beliefs Number of directed channels with a learned liquidity belief.\n");
+    out.push_str("# TYPE sling_liquidity_beliefs gauge\n");
+    out.push_str(&format!(
+        "sling_liquidity_beliefs {}\n",
+        plugin.state().liquidity.lock().len()
+    ));
+    out.push_str("# HELP sling_tempbans Number of channels currently temp-banned from routing.\n");
+    out.push_str("# TYPE sling_tempbans gauge\n");
+    out.push_str(&format!(
+        "sling_tempbans {}\n",
+        plugin.state().tempbans.lock().len()
+    ));
+    out.push_str("# HELP sling_peer_channels Number of direct peer channels currently known.\n");
+    out.push_str("# TYPE sling_peer_channels gauge\n");
+    out.push_str(&format!("sling_peer_channels {}\n", peer_channels.len()));
+
+    render_latency_histogram(
+        &mut out,
+        "sling_rebalance_duration_ms",
+        "Time from sendpay dispatch to a successful rebalance completing, in milliseconds.",
+        &plugin.state().rebalance_latency_ms.lock(),
+    );
+    render_latency_histogram(
+        &mut out,
+        "sling_route_search_duration_ms",
+        "Time spent searching for a route for a rebalance attempt, in milliseconds.",
+        &plugin.state().route_search_latency_ms.lock(),
+    );
+
+    {
+        let refresh_durations = plugin.state().refresh_durations_ms.lock();
+        out.push_str("# HELP sling_refresh_duration_ms How long each maintenance loop's latest pass took, in milliseconds.\n");
+        out.push_str("# TYPE sling_refresh_duration_ms histogram\n");
+        for (task_name, hist) in refresh_durations.iter() {
+            render_histogram_series(&mut out, "sling_refresh_duration_ms", task_name, hist);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Renders `hist` as a Prometheus histogram (`_bucket`/`_sum`/`_count`) plus p50/p90/p99
+/// gauges read via `value_at_quantile`, so operators can graph rebalance and route-search
+/// latency over time instead of parsing `sling-stats` JSON. The histogram itself keeps
+/// memory bounded regardless of how many rebalances have run.
+fn render_latency_histogram(out: &mut String, name: &str, help: &str, hist: &Histogram<u64>) {
+    out.push_str(&format!("# HELP {name} {help}\n"));
+    out.push_str(&format!("# TYPE {name} histogram\n"));
+    let mut cumulative_count: u64 = 0;
+    let mut sum_ms: u64 = 0;
+    for v in hist.iter_recorded() {
+        let count = v.count_since_last_iteration();
+        cumulative_count += count;
+        sum_ms += v.value_iterated_to() * count;
+        out.push_str(&format!(
+            "{name}_bucket{{le=\"{}\"}} {cumulative_count}\n",
+            v.value_iterated_to()
+        ));
+    }
+    out.push_str(&format!("{name}_bucket{{le=\"+Inf\"}} {}\n", hist.len()));
+    out.push_str(&format!("{name}_sum {sum_ms}\n"));
+    out.push_str(&format!("{name}_count {}\n", hist.len()));
+
+    for (label, quantile) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99)] {
+        out.push_str(&format!("# HELP {name}_{label} {label} of {help}\n"));
+        out.push_str(&format!("# TYPE {name}_{label} gauge\n"));
+        out.push_str(&format!(
+            "{name}_{label} {}\n",
+            hist.value_at_quantile(quantile)
+        ));
+    }
+}
+
+/// Renders one `scid="{scid}",outcome="{outcome}"` series of `sling_rebalance_hops`'s
+/// `_bucket`/`_sum`/`_count` lines. Hop counts are small (`u8`), so unlike
+/// [`render_latency_histogram`] this buckets the raw values directly rather than going through
+/// an `hdrhistogram::Histogram`.
+fn render_hop_histogram(
+    out: &mut String,
+    scid: &ShortChannelId,
+    id: &str,
+    outcome: &str,
+    hops: impl Iterator<Item = u8>,
+) {
+    let mut counts: std::collections::BTreeMap<u8, u64> = std::collections::BTreeMap::new();
+    for h in hops {
+        *counts.entry(h).or_insert(0) += 1;
+    }
+    let total_count: u64 = counts.values().sum();
+    let sum_hops: u64 = counts.iter().map(|(h, c)| *h as u64 * c).sum();
+    let mut cumulative_count: u64 = 0;
+    for (hop, count) in &counts {
+        cumulative_count += count;
+        out.push_str(&format!(
+            "sling_rebalance_hops_bucket{{scid=\"{scid}\",id=\"{id}\",outcome=\"{outcome}\",le=\"{hop}\"}} {cumulative_count}\n"
+        ));
+    }
+    out.push_str(&format!(
+        "sling_rebalance_hops_bucket{{scid=\"{scid}\",id=\"{id}\",outcome=\"{outcome}\",le=\"+Inf\"}} {total_count}\n"
+    ));
+    out.push_str(&format!(
+        "sling_rebalance_hops_sum{{scid=\"{scid}\",id=\"{id}\",outcome=\"{outcome}\"}} {sum_hops}\n"
+    ));
+    out.push_str(&format!(
+        "sling_rebalance_hops_count{{scid=\"{scid}\",id=\"{id}\",outcome=\"{outcome}\"}} {total_count}\n"
+    ));
+}
+
+/// Renders one `task="{task_name}"` series of `name`'s `_bucket`/`_sum`/`_count` lines, for
+/// metrics where several independent histograms (one per maintenance loop) share a single
+/// HELP/TYPE declaration instead of each getting their own like [`render_latency_histogram`].
+fn render_histogram_series(out: &mut String, name: &str, task_name: &str, hist: &Histogram<u64>) {
+    let mut cumulative_count: u64 = 0;
+    let mut sum_ms: u64 = 0;
+    for v in hist.iter_recorded() {
+        let count = v.count_since_last_iteration();
+        cumulative_count += count;
+        sum_ms += v.value_iterated_to() * count;
+        out.push_str(&format!(
+            "{name}_bucket{{task=\"{task_name}\",le=\"{}\"}} {cumulative_count}\n",
+            v.value_iterated_to()
+        ));
+    }
+    out.push_str(&format!(
+        "{name}_bucket{{task=\"{task_name}\",le=\"+Inf\"}} {}\n",
+        hist.len()
+    ));
+    out.push_str(&format!("{name}_sum{{task=\"{task_name}\"}} {sum_ms}\n"));
+    out.push_str(&format!("{name}_count{{task=\"{task_name}\"}} {}\n", hist.len()));
+}
@@ -1,24 +1,39 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fmt::{self, Display, Formatter},
     hash::{Hash, Hasher},
+    net::SocketAddr,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error};
 use cln_rpc::{
-    model::responses::{GetinfoResponse, ListpeerchannelsChannels},
+    model::{
+        requests::SendpayRoute,
+        responses::{GetinfoResponse, ListpeerchannelsChannels},
+    },
     primitives::{Amount, PublicKey, ShortChannelId, ShortChannelIdDir},
 };
+use hdrhistogram::Histogram;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize, Serializer};
-use sling::Job;
+use sling::{Job, SatDirection};
 use tabled::Tabled;
-use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use tokio::sync::{Notify, Semaphore};
 
+use crate::errors::FailureReason;
 use crate::gossip::{ChannelAnnouncement, ChannelUpdate};
+use crate::util::{edge_success_probability, get_direction_from_nodes, node_supports_feature};
+
+/// Serde default for [`SuccessReb::sat_direction`]/[`FailureReb::sat_direction`] fields added
+/// after those structs were already persisted on disk; `Pull` is an arbitrary but harmless
+/// placeholder for a record old enough not to carry the real direction.
+fn default_sat_direction() -> SatDirection {
+    SatDirection::Pull
+}
 
 pub const SUCCESSES_SUFFIX: &str = "successes.json";
 pub const FAILURES_SUFFIX: &str = "failures.json";
@@ -26,9 +41,197 @@ pub const NO_ALIAS_SET: &str = "NO_ALIAS_SET";
 
 pub const PLUGIN_NAME: &str = "sling";
 pub const LIQUIDITY_FILE_NAME: &str = "liquidity.json";
+pub const LIQUIDITY_JOURNAL_FILE_NAME: &str = "liquidity.journal";
+/// Number of equal slices `0..capacity` is split into for [`Liquidity`]'s historical-bucket
+/// outcome histogram. Fixed rather than configurable since a handful of buckets is already
+/// enough to capture recurring patterns a single `[min, max]` window discards, and a fixed
+/// size keeps the journal/snapshot format simple.
+pub const LIQUIDITY_BUCKETS: usize = 8;
 pub const JOB_FILE_NAME: &str = "jobs.json";
 pub const EXCEPTS_CHANS_FILE_NAME: &str = "excepts.json";
 pub const EXCEPTS_PEERS_FILE_NAME: &str = "excepts_peers.json";
+/// Durable record of in-flight [`MppPay`]s (see [`MppPayRecord`]), so a restart can resume
+/// waiting for a multi-part rebalance's remaining parts instead of leaving them permanently
+/// unclaimed.
+pub const MPP_PAYS_FILE_NAME: &str = "mpp_pays.json";
+pub const GRAPH_SNAPSHOT_FILE_NAME: &str = "graph_snapshot.json";
+/// Bumped whenever [`GraphSnapshot`]'s shape changes incompatibly, so a snapshot written by an
+/// older/newer sling binary is recognized and discarded up front instead of failing (or worse,
+/// silently misparsing) partway through `serde_json::from_slice`.
+pub const GRAPH_SNAPSHOT_VERSION: u32 = 1;
+
+/// Lower/upper bounds (in milliseconds) and significant figures for the latency
+/// histograms in [`PluginState`]. 1ms..10min covers everything from a cached route lookup
+/// to a rebalance that barely beats `sling-timeoutpay`, with enough precision for graphing
+/// without letting memory grow with sample count.
+const LATENCY_HIST_LOWEST_MS: u64 = 1;
+const LATENCY_HIST_HIGHEST_MS: u64 = 600_000;
+const LATENCY_HIST_SIGFIG: u8 = 3;
+
+fn new_latency_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(LATENCY_HIST_LOWEST_MS, LATENCY_HIST_HIGHEST_MS, LATENCY_HIST_SIGFIG)
+        .expect("static histogram bounds are valid")
+}
+
+/// Records `elapsed` (clamped into the histogram's fixed `1ms..600_000ms` range) into one
+/// of [`PluginState`]'s latency histograms.
+pub fn record_latency_ms(hist: &Mutex<Histogram<u64>>, elapsed: std::time::Duration) {
+    let ms = (elapsed.as_millis() as u64).clamp(LATENCY_HIST_LOWEST_MS, LATENCY_HIST_HIGHEST_MS);
+    let _ = hist.lock().record(ms);
+}
+
+/// Records `elapsed` into [`PluginState::refresh_durations_ms`]'s histogram for `task_name`
+/// (e.g. `"aliasmap"`, `"graph"`), creating it on first use. Lets [`crate::metrics`] expose how
+/// long each maintenance loop's latest pass took without every loop needing its own named field.
+pub fn record_refresh_duration_ms(
+    plugin: &PluginState,
+    task_name: &'static str,
+    elapsed: std::time::Duration,
+) {
+    let ms = (elapsed.as_millis() as u64).clamp(LATENCY_HIST_LOWEST_MS, LATENCY_HIST_HIGHEST_MS);
+    let _ = plugin
+        .refresh_durations_ms
+        .lock()
+        .entry(task_name)
+        .or_insert_with(new_latency_histogram)
+        .record(ms);
+}
+
+/// What other in-flight rebalances have already committed to a directed channel, tracked in
+/// [`PluginState::reservations`] so concurrent tasks see each other's claims instead of only
+/// their own job's liquidity view.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChannelReservation {
+    pub reserved_msat: u64,
+    pub reserved_htlcs: u64,
+}
+
+/// Atomically reserves every hop of `route` against [`PluginState::reservations`], checking
+/// each hop's existing reservation plus this route's amount against `max_htlc_count` and the
+/// channel's `htlc_maximum_msat` before committing any of them. If any hop would be
+/// over-committed, none of the hops are reserved and an error is returned, so the caller can
+/// back off and let the other job(s) holding the channel finish first. On success, returns
+/// the `(dir_chan, amount_msat)` pairs to pass back into [`release_reservation`] once the
+/// attempt resolves.
+pub fn try_reserve_route(
+    plugin: &PluginState,
+    route: &[SendpayRoute],
+) -> Result<Vec<(ShortChannelIdDir, u64)>, Error> {
+    let max_htlc_count = plugin.config.lock().max_htlc_count;
+    let mut claims = Vec::with_capacity(route.len().saturating_sub(1));
+    for i in 0..route.len().saturating_sub(1) {
+        let direction = get_direction_from_nodes(route[i].id, route[i + 1].id)?;
+        let dir_chan = ShortChannelIdDir {
+            short_channel_id: route[i].channel,
+            direction,
+        };
+        claims.push((dir_chan, Amount::msat(&route[i].amount_msat)));
+    }
+
+    let htlc_maximum_msat = |dir_chan: ShortChannelIdDir| -> u64 {
+        plugin
+            .graph
+            .lock()
+            .get_state(dir_chan)
+            .map_or(u64::MAX, |s| Amount::msat(&s.htlc_maximum_msat))
+    };
+
+    let mut reservations = plugin.reservations.lock();
+    for (dir_chan, amount_msat) in &claims {
+        let existing = reservations.get(dir_chan).copied().unwrap_or_default();
+        if existing.reserved_htlcs + 1 > max_htlc_count {
+            return Err(anyhow!(
+                "channel {}/{} already has {} reservation(s) in flight, at sling-max-htlc-count {max_htlc_count}",
+                dir_chan.short_channel_id,
+                dir_chan.direction,
+                existing.reserved_htlcs
+            ));
+        }
+        if existing.reserved_msat + amount_msat > htlc_maximum_msat(*dir_chan) {
+            return Err(anyhow!(
+                "channel {}/{} already has {}msat reserved, not enough room left for {}msat",
+                dir_chan.short_channel_id,
+                dir_chan.direction,
+                existing.reserved_msat,
+                amount_msat
+            ));
+        }
+    }
+
+    for (dir_chan, amount_msat) in &claims {
+        let entry = reservations.entry(*dir_chan).or_default();
+        entry.reserved_msat += amount_msat;
+        entry.reserved_htlcs += 1;
+    }
+
+    Ok(claims)
+}
+
+/// Releases a route previously reserved via [`try_reserve_route`], regardless of whether the
+/// attempt it backed succeeded or failed. Removes a channel's ledger entry entirely once its
+/// counts return to zero, so [`PluginState::reservations`] doesn't grow unbounded over time.
+pub fn release_reservation(plugin: &PluginState, reserved: &[(ShortChannelIdDir, u64)]) {
+    let mut reservations = plugin.reservations.lock();
+    for (dir_chan, amount_msat) in reserved {
+        if let Some(entry) = reservations.get_mut(dir_chan) {
+            entry.reserved_msat = entry.reserved_msat.saturating_sub(*amount_msat);
+            entry.reserved_htlcs = entry.reserved_htlcs.saturating_sub(1);
+            if entry.reserved_htlcs == 0 {
+                reservations.remove(dir_chan);
+            }
+        }
+    }
+}
+
+/// What `htlc_handler` needs to resolve our own incoming HTLC for a rebalance we initiated:
+/// the preimage to reveal, and which channel (by scid or its local alias) it's expected to
+/// arrive on, so we don't release the preimage to an htlc that merely shares our payment
+/// hash by coincidence.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PayResolveInfo {
+    pub preimage: String,
+    pub incoming_scid: ShortChannelId,
+    pub incoming_alias: Option<ShortChannelId>,
+}
+
+/// Tracks the parts of one multi-part (MPP) rebalance payment sharing a single payment hash,
+/// see [`crate::dijkstra::dijkstra_mpp`]. All parts land on the same incoming channel (the
+/// job's own channel never changes between parts), but `htlc_handler` only reveals
+/// `resolve.preimage` once `received_msat` reaches `target_msat`, so the split behaves as one
+/// all-or-nothing payment even though CLN sees each part as an independent sendpay/htlc. A
+/// part that arrives before its siblings do waits on `notify` instead of claiming early.
+pub struct MppPay {
+    pub resolve: PayResolveInfo,
+    pub target_msat: u64,
+    pub parts_expected: u32,
+    pub received_msat: u64,
+    pub resolved: bool,
+    pub notify: Arc<Notify>,
+    pub part_timeout_secs: u64,
+}
+
+/// The durable subset of [`MppPay`], written to [`MPP_PAYS_FILE_NAME`] on registration and
+/// removed on resolution, so an in-flight multi-part rebalance survives a plugin restart.
+/// `received_msat`/`resolved` aren't persisted: they're re-derived from CLN's own
+/// `listsendpays` at startup (the actual source of truth for which parts landed), see
+/// [`crate::tasks::reconcile_mpp_pays`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MppPayRecord {
+    pub payment_hash: String,
+    pub resolve: PayResolveInfo,
+    pub target_msat: u64,
+    pub parts_expected: u32,
+    pub part_timeout_secs: u64,
+}
+
+/// Tracks one outstanding [`crate::coordination::negotiate_rebalance`] request while we're
+/// waiting on the peer's ack/nack, keyed by `request_id` in
+/// [`PluginState::coord_negotiations`]. Mirrors how [`MppPay`] lets a waiter block on `notify`
+/// instead of polling.
+pub struct CoordNegotiation {
+    pub accepted: Option<bool>,
+    pub notify: Arc<Notify>,
+}
 
 #[derive(Clone)]
 pub struct PluginState {
@@ -36,32 +239,135 @@ pub struct PluginState {
     pub peer_channels: Arc<Mutex<HashMap<ShortChannelId, ListpeerchannelsChannels>>>,
     pub graph: Arc<Mutex<LnGraph>>,
     pub incomplete_channels: Arc<Mutex<IncompleteChannels>>,
+    /// How far into `gossip_store` [`crate::tasks::refresh_graph`] has read so far, updated
+    /// after every poll. Read by [`crate::tasks::compact_graph_snapshot`] so it can persist a
+    /// [`GraphSnapshot`] without needing access to the long-lived gossip_store reader itself.
+    pub gossip_store_offset: Arc<Mutex<u64>>,
     pub liquidity: Arc<Mutex<HashMap<ShortChannelIdDir, Liquidity>>>,
-    pub pays: Arc<RwLock<HashMap<String, String>>>,
+    /// Amount and HTLC count currently committed to each directed channel by in-flight
+    /// rebalances across all jobs, see [`try_reserve_route`]. Lets parallel tasks share a
+    /// channel's real headroom instead of each only checking it against its own job.
+    pub reservations: Arc<Mutex<HashMap<ShortChannelIdDir, ChannelReservation>>>,
+    pub pays: Arc<RwLock<HashMap<String, PayResolveInfo>>>,
+    /// In-flight multi-part rebalance payments, keyed by payment hash. See [`MppPay`].
+    pub mpp_pays: Arc<Mutex<HashMap<String, MppPay>>>,
+    /// In-flight cooperative-rebalance negotiations, keyed by request id. See
+    /// [`CoordNegotiation`] and [`crate::coordination::negotiate_rebalance`].
+    pub coord_negotiations: Arc<Mutex<HashMap<u64, CoordNegotiation>>>,
     pub alias_peer_map: Arc<Mutex<HashMap<PublicKey, String>>>,
+    /// Legacy hard-ban map, superseded by the learned `liquidity` bounds (see
+    /// [`crate::util::liquidity_uncertainty_penalty`]) for ordinary failures and by
+    /// `temp_chan_bans`'s exponential backoff for temporary ones. Kept around for the one case
+    /// neither of those models fits: a peer that's outright not ready to forward yet, where
+    /// there's nothing useful to *learn* and a route through it should simply be skipped.
     pub tempbans: Arc<Mutex<HashMap<ShortChannelId, u64>>>,
+    /// Exponential backoff for a channel that's failed with a temporary, retryable error.
+    /// Unlike `tempbans`, this fully excludes the channel for a while rather than just scoring
+    /// it down, since a channel in backoff is presumed to need time to recover, not merely a
+    /// lower-confidence estimate.
+    pub temp_chan_bans: Arc<Mutex<HashMap<ShortChannelId, ChannelBackoff>>>,
+    pub bad_fwd_nodes: Arc<Mutex<HashMap<PublicKey, u64>>>,
+    pub excluded_scids: Arc<Mutex<HashSet<ShortChannelId>>>,
+    pub excluded_nodes: Arc<Mutex<HashSet<PublicKey>>>,
     pub tasks: Arc<Mutex<Tasks>>,
     pub blockheight: Arc<Mutex<u32>>,
     pub rpc_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Serializes appends to the liquidity journal against its periodic compaction, so a
+    /// compaction can't truncate the journal file out from under an in-flight append (or vice
+    /// versa) and silently drop the update. See [`crate::util::append_liquidity_update`] and
+    /// [`crate::util::write_liquidity`].
+    pub liquidity_journal_lock: Arc<tokio::sync::Mutex<()>>,
+    pub rebalance_latency_ms: Arc<Mutex<Histogram<u64>>>,
+    pub route_search_latency_ms: Arc<Mutex<Histogram<u64>>>,
+    /// One latency histogram per maintenance loop (keyed by task name, e.g. `"aliasmap"`,
+    /// `"graph"`), filled in via [`record_refresh_duration_ms`] and exported by
+    /// [`crate::metrics`] instead of only ever hitting the log.
+    pub refresh_durations_ms: Arc<Mutex<HashMap<&'static str, Histogram<u64>>>>,
+    /// Bounds how many tasks may run a dijkstra search concurrently (`sling-route-workers`),
+    /// decoupling route search from payment execution so a burst of jobs searching for routes
+    /// can't starve tasks that already have a route ready to send, or vice versa.
+    pub route_search_permits: Arc<Semaphore>,
+    /// Bounds how many tasks may have a sendpay/waitsendpay in flight concurrently
+    /// (`sling-send-workers`), see [`PluginState::route_search_permits`].
+    pub send_permits: Arc<Semaphore>,
+    /// Newly gossip-built channels awaiting on-chain funding-output validation, queued by
+    /// [`crate::gossip::read_gossip_store`]/[`crate::rgs::refresh_rgs`] and drained in batches by
+    /// [`crate::funding::verify_pending_funding`]. Only populated when
+    /// `sling-verify-channel-funding` is enabled.
+    pub pending_funding_checks: Arc<Mutex<VecDeque<ShortChannelId>>>,
+    /// Cache of already-resolved funding outputs (`true` = the output exists and is a witness
+    /// output, the channel is one of ours, or the lookup itself failed; whether the output has
+    /// since been spent is not checked) so a channel already seen doesn't get re-queried every
+    /// batch. See [`crate::funding`].
+    pub funding_verification_cache: Arc<Mutex<HashMap<ShortChannelId, bool>>>,
+    /// When/how each table-driven option (see `crate::config::OPTION_SPECS`) last took effect.
+    /// Absent entries mean the built-in default is still in force. Populated by
+    /// `crate::config::get_startup_options`/`setconfig_callback`, read by `sling-listconfigs`.
+    pub option_set_at: Arc<Mutex<HashMap<&'static str, (OptionSource, u64)>>>,
+    /// Signalled whenever something a sleeping job might care about changes — the gossip graph
+    /// going from empty to populated, a local channel balance change, or a tempban/backoff
+    /// expiring — so [`crate::util::my_sleep`] can wake early instead of always riding out its
+    /// full timeout. One shared signal rather than a per-channel one, since any job could be
+    /// unblocked by any of these and there's nothing to gain from routing it more narrowly.
+    pub wake: Arc<Notify>,
+}
+
+/// How an option's current value was last set, reported by `sling-listconfigs` alongside the
+/// value itself so operators can tell a startup-config choice from a live `setconfig` change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionSource {
+    Startup,
+    Runtime,
 }
 impl PluginState {
     pub fn new(config: Config, liquidity: HashMap<ShortChannelIdDir, Liquidity>) -> PluginState {
+        let route_search_permits = Arc::new(Semaphore::new(config.route_workers as usize));
+        let send_permits = Arc::new(Semaphore::new(config.send_workers as usize));
         PluginState {
             config: Arc::new(Mutex::new(config)),
             peer_channels: Arc::new(Mutex::new(HashMap::new())),
             graph: Arc::new(Mutex::new(LnGraph::new())),
             incomplete_channels: Arc::new(Mutex::new(IncompleteChannels::new())),
+            gossip_store_offset: Arc::new(Mutex::new(0)),
             liquidity: Arc::new(Mutex::new(liquidity)),
+            reservations: Arc::new(Mutex::new(HashMap::new())),
             pays: Arc::new(RwLock::new(HashMap::new())),
+            mpp_pays: Arc::new(Mutex::new(HashMap::new())),
+            coord_negotiations: Arc::new(Mutex::new(HashMap::new())),
             alias_peer_map: Arc::new(Mutex::new(HashMap::new())),
             tempbans: Arc::new(Mutex::new(HashMap::new())),
+            temp_chan_bans: Arc::new(Mutex::new(HashMap::new())),
+            bad_fwd_nodes: Arc::new(Mutex::new(HashMap::new())),
+            excluded_scids: Arc::new(Mutex::new(HashSet::new())),
+            excluded_nodes: Arc::new(Mutex::new(HashSet::new())),
             tasks: Arc::new(Mutex::new(Tasks::new())),
             blockheight: Arc::new(Mutex::new(0)),
             rpc_lock: Arc::new(tokio::sync::Mutex::new(())),
+            liquidity_journal_lock: Arc::new(tokio::sync::Mutex::new(())),
+            rebalance_latency_ms: Arc::new(Mutex::new(new_latency_histogram())),
+            route_search_latency_ms: Arc::new(Mutex::new(new_latency_histogram())),
+            refresh_durations_ms: Arc::new(Mutex::new(HashMap::new())),
+            route_search_permits,
+            send_permits,
+            pending_funding_checks: Arc::new(Mutex::new(VecDeque::new())),
+            funding_verification_cache: Arc::new(Mutex::new(HashMap::new())),
+            option_set_at: Arc::new(Mutex::new(HashMap::new())),
+            wake: Arc::new(Notify::new()),
         }
     }
 }
 
+/// A self-healing ban on a channel that recently hit a temporary BOLT #4 failure:
+/// `banned_until` is when we'll try routing through it again, and `consecutive_failures`
+/// (reset to 0 on any success, see [`crate::response::reset_backoff`]) drives the exponential
+/// backoff computed in [`crate::response::backoff_duration_secs`].
+#[derive(Debug, Clone, Copy)]
+pub struct ChannelBackoff {
+    pub banned_until: u64,
+    pub consecutive_failures: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct Tasks {
     tasks: HashMap<ShortChannelId, HashMap<u16, Task>>,
@@ -117,6 +423,46 @@ impl Tasks {
             }
         }
     }
+    pub fn set_error(&mut self, task_ident: &TaskIdentifier, error: String) {
+        if let Some(tasks) = self.tasks.get_mut(&task_ident.get_chan_id()) {
+            if let Some(task) = tasks.get_mut(&task_ident.get_task_id()) {
+                task.set_error(error);
+            }
+        }
+    }
+    pub fn record_exit_failure(
+        &mut self,
+        task_ident: &TaskIdentifier,
+        error: String,
+        job_retry_base_secs: u64,
+        job_retry_max_secs: u64,
+        job_retry_max_attempts: u64,
+    ) {
+        if let Some(tasks) = self.tasks.get_mut(&task_ident.get_chan_id()) {
+            if let Some(task) = tasks.get_mut(&task_ident.get_task_id()) {
+                task.record_exit_failure(
+                    error,
+                    job_retry_base_secs,
+                    job_retry_max_secs,
+                    job_retry_max_attempts,
+                );
+            }
+        }
+    }
+    pub fn set_paused(&mut self, scid: &ShortChannelId, paused: bool) -> usize {
+        let mut count = 0;
+        if let Some(tasks) = self.tasks.get_mut(scid) {
+            for task in tasks.values_mut() {
+                if paused {
+                    task.pause();
+                } else {
+                    task.resume();
+                }
+                count += 1;
+            }
+        }
+        count
+    }
     pub fn get_all_tasks_mut(&mut self) -> &mut HashMap<ShortChannelId, HashMap<u16, Task>> {
         &mut self.tasks
     }
@@ -202,6 +548,13 @@ impl Serialize for PubKeyBytes {
     }
 }
 
+impl<'de> Deserialize<'de> for PubKeyBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        PubKeyBytes::from_str(&hex).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TaskIdentifier {
     short_channel_id: ShortChannelId,
@@ -227,15 +580,37 @@ impl Display for TaskIdentifier {
     }
 }
 
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 #[derive(Clone, Debug)]
 pub struct Task {
     task_ident: TaskIdentifier,
     latest_state: JobMessage,
+    state_changed_at: u64,
+    last_error: Option<String>,
+    last_attempt_ms: Option<u64>,
     active: bool,
     should_stop: bool,
+    paused: bool,
     once: bool,
+    /// Consecutive task-exit failures since the last successful rebalance, driving
+    /// [`Task::record_exit_failure`]'s backoff. Reset by [`Task::record_rebalance_success`].
+    attempt_count: u32,
+    /// When the scheduler should next respawn this task after it exited in
+    /// [`JobMessage::Error`]. `None` while the task is healthy, or once
+    /// `config.job_retry_max_attempts` has been exhausted.
+    next_retry_at: Option<u64>,
     pub parallel_ban: Option<ShortChannelIdDir>,
     pub other_pubkey: PubKeyBytes,
+    /// Alternate routes precomputed by [`crate::dijkstra::k_shortest_paths`] alongside the
+    /// route actually tried, ranked cheapest-first. Drained by `next_route` on a retry so a
+    /// failed sendpay can fall back to the next-best path instead of re-running a full search.
+    pub alt_routes: VecDeque<Vec<SendpayRoute>>,
 }
 impl Display for Task {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -253,11 +628,18 @@ impl Task {
     ) -> Self {
         Task {
             latest_state,
+            state_changed_at: now_secs(),
+            last_error: None,
+            last_attempt_ms: None,
             active: true,
             should_stop: false,
+            paused: false,
             once,
+            attempt_count: 0,
+            next_retry_at: None,
             other_pubkey,
             parallel_ban: None,
+            alt_routes: VecDeque::new(),
             task_ident: TaskIdentifier::new(short_channel_id, task_id),
         }
     }
@@ -267,6 +649,59 @@ impl Task {
     }
     pub fn set_state(&mut self, state: JobMessage) {
         self.latest_state = state;
+        self.state_changed_at = now_secs();
+    }
+    /// Seconds since the task last transitioned into its current state.
+    pub fn state_age_secs(&self) -> u64 {
+        now_secs().saturating_sub(self.state_changed_at)
+    }
+    pub fn get_last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+    pub fn set_error(&mut self, error: String) {
+        self.last_error = Some(error);
+        self.set_state(JobMessage::Error);
+    }
+    pub fn get_attempt_count(&self) -> u32 {
+        self.attempt_count
+    }
+    pub fn get_next_retry_at(&self) -> Option<u64> {
+        self.next_retry_at
+    }
+    /// Marks the task dead in [`JobMessage::Error`] and schedules its retry after
+    /// `min(job_retry_base_secs * 2^attempts, job_retry_max_secs)`, unless
+    /// `job_retry_max_attempts` consecutive failures have already piled up, in which case it's
+    /// left inactive with no retry so a human has to look at it.
+    pub fn record_exit_failure(
+        &mut self,
+        error: String,
+        job_retry_base_secs: u64,
+        job_retry_max_secs: u64,
+        job_retry_max_attempts: u64,
+    ) {
+        self.set_error(error);
+        self.active = false;
+        self.attempt_count = self.attempt_count.saturating_add(1);
+        self.next_retry_at = if u64::from(self.attempt_count) >= job_retry_max_attempts {
+            None
+        } else {
+            let delay = job_retry_base_secs
+                .saturating_mul(2_u64.saturating_pow(self.attempt_count - 1))
+                .min(job_retry_max_secs);
+            Some(now_secs() + delay)
+        };
+    }
+    /// Clears retry bookkeeping on any successful rebalance, so a transient flap doesn't count
+    /// against a task that's since recovered.
+    pub fn record_rebalance_success(&mut self) {
+        self.attempt_count = 0;
+        self.next_retry_at = None;
+    }
+    pub fn get_last_attempt_ms(&self) -> Option<u64> {
+        self.last_attempt_ms
+    }
+    pub fn set_last_attempt_ms(&mut self, millis: u64) {
+        self.last_attempt_ms = Some(millis);
     }
     pub fn stop(&mut self) {
         self.should_stop = true;
@@ -274,6 +709,15 @@ impl Task {
     pub fn should_stop(&self) -> bool {
         self.should_stop
     }
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
     pub fn is_active(&self) -> bool {
         self.active
     }
@@ -303,24 +747,107 @@ pub struct Config {
     pub version: String,
     pub network: String,
     pub refresh_aliasmap_interval: u64,
+    /// Seconds. Configured via `sling-reset-liquidity-interval`, which accepts either a bare
+    /// number of minutes (its historical unit) or a suffixed duration like `"12h"` — see
+    /// [`crate::config::parse_duration_secs`].
     pub reset_liquidity_interval: u64,
+    pub liquidity_compact_interval: u64,
+    pub graph_snapshot_interval: u64,
     pub depleteuptopercent: f64,
     pub depleteuptoamount: u64,
     pub maxhops: u8,
     pub candidates_min_age: u32,
     pub paralleljobs: u16,
     pub timeoutpay: u16,
+    pub timeout_route_search: u64,
     pub max_htlc_count: u64,
+    /// Seconds. Configured via `sling-stats-delete-failures-age`, which accepts either a bare
+    /// number of days (its historical unit) or a suffixed duration like `"7d"` — see
+    /// [`crate::config::parse_duration_secs`].
     pub stats_delete_failures_age: u64,
     pub stats_delete_failures_size: u64,
+    /// Seconds. Configured via `sling-stats-delete-successes-age`, same bare-days-or-suffixed
+    /// parsing as [`Config::stats_delete_failures_age`].
     pub stats_delete_successes_age: u64,
     pub stats_delete_successes_size: u64,
+    pub liquidity_halflife: u64,
+    /// Discard a learned liquidity estimate on startup (see [`crate::util::read_liquidity`]) if
+    /// it's older than this many seconds, instead of keeping and decaying it. `0` disables this.
+    pub liquidity_max_age: u64,
+    pub liquidity_penalty_multiplier: u64,
+    /// Gates the [`crate::util::liquidity_uncertainty_penalty`] routing cost entirely; when
+    /// `false`, `dijkstra` falls back to pure fee-based scoring regardless of
+    /// `liquidity_penalty_multiplier`.
+    pub liquidity_probabilistic_scoring: bool,
+    /// Minimum [`crate::util::edge_success_probability`] a directed channel must clear to be
+    /// offered as a routing candidate at all (see [`LnGraph::edges`]/[`LnGraph::edges_incoming`]),
+    /// replacing the old crude `htlc_maximum_msat / 2 >= amount` admission heuristic.
+    pub min_candidate_success_probability: f64,
+    /// Weight given to a candidate's effective fee ppm in `build_candidatelist`'s ranking,
+    /// relative to its learned success probability. See [`crate::util::candidate_rank_score`].
+    pub candidate_fee_weight: f64,
+    /// Gates the custom-message rebalance negotiation entirely; when `false`, `health_check`
+    /// never sends a [`crate::coordination::negotiate_rebalance`] request and jobs behave as
+    /// before this existed.
+    pub coordinate_rebalances: bool,
+    /// How long [`crate::coordination::negotiate_rebalance`] waits for the peer's ack/nack
+    /// before giving up and treating it as unsupported.
+    pub coord_negotiation_timeout_secs: u64,
+    pub backoff_base_secs: u64,
+    pub backoff_max_secs: u64,
+    /// Base delay before the scheduler retries a task that exited in [`JobMessage::Error`],
+    /// doubling with each consecutive failure (see [`Task::record_exit_failure`]).
+    pub job_retry_base_secs: u64,
+    /// Ceiling for [`Config::job_retry_base_secs`]'s exponential backoff.
+    pub job_retry_max_secs: u64,
+    /// Consecutive task-exit failures after which the task is left in [`JobMessage::Error`]
+    /// instead of being automatically retried again.
+    pub job_retry_max_attempts: u64,
+    pub tranquility: f64,
     pub cltv_delta: u32,
     pub at_or_above_24_11: bool,
     pub inform_layers: Vec<String>,
+    pub metrics_bind_addr: Option<SocketAddr>,
+    pub route_workers: u16,
+    pub send_workers: u16,
+    pub candidate_workers: u16,
+    /// Use [`crate::dijkstra::dijkstra_bidirectional`] instead of the plain single-source
+    /// `dijkstra` search. Off by default so the search behavior of existing setups doesn't
+    /// change under them.
+    pub dijkstra_bidirectional: bool,
     pub exclude_chans_pull: HashSet<ShortChannelId>,
     pub exclude_chans_push: HashSet<ShortChannelId>,
     pub exclude_peers: HashSet<PubKeyBytes>,
+    pub probe_enabled: bool,
+    pub probe_interval_secs: u64,
+    pub askrene_publish_enabled: bool,
+    pub askrene_publish_layer: String,
+    /// Base URL of a Rapid Gossip Sync server (e.g. `https://rapidsync.lightningdevkit.org/snapshot`)
+    /// to bootstrap/refresh the public graph from, instead of only tailing `gossip_store`. Empty
+    /// disables it. See [`crate::rgs::refresh_rgs`].
+    pub rgs_url: String,
+    pub rgs_interval_secs: u64,
+    /// Channel directions whose newest `channel_update` is older than this are pruned from the
+    /// graph after each gossip_store/RGS read, per BOLT 7's "no update in 14 days ⇒ closed" rule.
+    /// See [`crate::gossip::prune_stale_channels`].
+    pub stale_channel_horizon_secs: u64,
+    /// How long an [`IncompleteChannels`] builder can sit without receiving both an
+    /// announcement and an update before [`IncompleteChannels::reap_timed_out`] drops it, e.g.
+    /// a half-announced channel whose `channel_update` never arrives.
+    pub incomplete_channel_timeout_secs: u64,
+    /// Resolve each newly gossip-learned channel's funding output on-chain before trusting it
+    /// for routing, dropping it if the output doesn't exist or isn't a witness output. Whether
+    /// the output has since been spent is not checked.
+    /// Off by default since it needs a working chain backend. See [`crate::funding`].
+    pub verify_channel_funding: bool,
+    /// How many queued channels [`crate::funding::verify_pending_funding`] resolves per run, so
+    /// a burst of newly-learned channels doesn't stall on a long run of chain lookups.
+    pub funding_verification_batch_size: u64,
+    pub funding_verification_interval_secs: u64,
+    /// BOLT 9 feature number a hop's node must advertise (either the even "compulsory" bit or
+    /// the odd "optional" bit of the pair) to be used as a routing hop. `None` disables the
+    /// filter. See [`crate::util::node_supports_feature`].
+    pub required_node_feature_bit: Option<u32>,
 }
 impl Config {
     pub fn new(
@@ -339,24 +866,58 @@ impl Config {
             version: getinfo.version,
             network: getinfo.network,
             refresh_aliasmap_interval: 3600,
-            reset_liquidity_interval: 360,
+            reset_liquidity_interval: 360 * 60,
+            liquidity_compact_interval: 3600,
+            graph_snapshot_interval: 900,
             depleteuptopercent: 0.2,
             depleteuptoamount: 2_000_000_000,
             maxhops: 8,
             candidates_min_age: 0,
             paralleljobs: 1,
             timeoutpay: 120,
+            timeout_route_search: 30,
             max_htlc_count: 5,
-            stats_delete_failures_age: 30,
+            stats_delete_failures_age: 30 * 24 * 60 * 60,
             stats_delete_failures_size: 10_000,
-            stats_delete_successes_age: 30,
+            stats_delete_successes_age: 30 * 24 * 60 * 60,
             stats_delete_successes_size: 10_000,
+            liquidity_halflife: 43_200,
+            liquidity_max_age: 0,
+            liquidity_penalty_multiplier: 200,
+            liquidity_probabilistic_scoring: true,
+            min_candidate_success_probability: 0.05,
+            candidate_fee_weight: 1.0,
+            coordinate_rebalances: false,
+            coord_negotiation_timeout_secs: 5,
+            backoff_base_secs: 30,
+            backoff_max_secs: 3_600,
+            job_retry_base_secs: 60,
+            job_retry_max_secs: 3_600,
+            job_retry_max_attempts: 10,
+            tranquility: 1.0,
             cltv_delta: 144,
             at_or_above_24_11: false,
             inform_layers: vec!["xpay".to_string()],
+            metrics_bind_addr: None,
+            route_workers: 4,
+            send_workers: 4,
+            candidate_workers: 4,
+            dijkstra_bidirectional: false,
             exclude_chans_pull,
             exclude_chans_push,
             exclude_peers,
+            probe_enabled: false,
+            probe_interval_secs: 1_800,
+            askrene_publish_enabled: false,
+            askrene_publish_layer: "sling".to_string(),
+            rgs_url: String::new(),
+            rgs_interval_secs: 3_600,
+            stale_channel_horizon_secs: 60 * 60 * 24 * 14,
+            incomplete_channel_timeout_secs: 60 * 60 * 24,
+            verify_channel_funding: false,
+            funding_verification_batch_size: 25,
+            funding_verification_interval_secs: 60,
+            required_node_feature_bit: None,
         }
     }
 }
@@ -378,6 +939,7 @@ pub enum JobMessage {
     TooExp,
     Stopping,
     Stopped,
+    Paused,
     Error,
     NotStarted,
 }
@@ -399,6 +961,7 @@ impl Display for JobMessage {
             JobMessage::TooExp => write!(f, "NoCheapRoute"),
             JobMessage::Stopping => write!(f, "Stopping"),
             JobMessage::Stopped => write!(f, "Stopped"),
+            JobMessage::Paused => write!(f, "Paused"),
             JobMessage::Error => write!(f, "Error"),
             JobMessage::NotStarted => write!(f, "NotStarted"),
         }
@@ -424,13 +987,46 @@ impl PartialEq for DijkstraNode {
     }
 }
 
+/// Our learned bounds on a directed channel's forwardable balance, modeled on rust-lightning's
+/// `ProbabilisticScorer`: we believe it can move at least `min_liquidity_msat` and at most
+/// `liquidity_msat` (the latter kept under its pre-bounds-scorer name since it already served
+/// as the upper bound everywhere it's filtered on). Both bounds share `liquidity_age` and decay
+/// back toward `[0, capacity]` over time in [`crate::util::liquidity_uncertainty_penalty`] as
+/// the single timestamp goes stale, rather than tracking an age per bound.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Liquidity {
     pub liquidity_msat: u64,
     pub liquidity_age: u64,
+    #[serde(default)]
+    pub min_liquidity_msat: u64,
+    /// `htlc_maximum_msat` this estimate was last recorded against. A gossiped capacity change
+    /// (channel splice, fee policy bump, etc.) invalidates both the bounds above and the
+    /// buckets below, since they were learned as fractions/absolutes of the old capacity — see
+    /// [`crate::util::reset_liquidity_if_capacity_changed`].
+    #[serde(default)]
+    pub capacity_msat: u64,
+    /// Decayed weight of forwards that succeeded while carrying roughly each fraction of
+    /// `htlc_maximum_msat`, bucketed into [`LIQUIDITY_BUCKETS`] equal slices of `0..capacity`.
+    /// Shares `liquidity_age` with the bounds above, so both decay together. See
+    /// [`crate::util::bucket_success_probability`].
+    #[serde(default)]
+    pub success_buckets: [f64; LIQUIDITY_BUCKETS],
+    /// Same bucketing as `success_buckets`, but for attempts that failed.
+    #[serde(default)]
+    pub fail_buckets: [f64; LIQUIDITY_BUCKETS],
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+/// One record of [`LIQUIDITY_JOURNAL_FILE_NAME`]: the full, post-update state of a single
+/// directed channel, appended right after the in-memory update it records. Replaying every
+/// record in order on top of the last compacted snapshot reconstructs the liquidity map
+/// without having to rewrite the whole file on every update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityJournalRecord {
+    pub scid_dir: ShortChannelIdDir,
+    pub liquidity: Liquidity,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ShortChannelIdDirState {
     pub source: PubKeyBytes,
     pub destination: PubKeyBytes,
@@ -457,7 +1053,7 @@ impl ShortChannelIdDirState {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ShortChannelIdDirStateBuilder {
     pub source: Option<PubKeyBytes>,
     destination: Option<PubKeyBytes>,
@@ -470,6 +1066,11 @@ pub struct ShortChannelIdDirStateBuilder {
     delay: Option<u32>,
     last_update: Option<u32>,
     private: Option<bool>,
+    /// When this builder was created, so [`IncompleteChannels::reap_timed_out`] can drop ones
+    /// that never received both an announcement and an update within
+    /// `incomplete_channel_timeout_secs`.
+    #[serde(default)]
+    first_seen: u32,
 }
 pub enum BuildResult {
     Success(ShortChannelIdDirState),
@@ -489,6 +1090,7 @@ impl ShortChannelIdDirStateBuilder {
             delay: None,
             last_update: None,
             private: Some(false),
+            first_seen: now_secs() as u32,
         }
     }
     pub fn has_announcement(&self) -> bool {
@@ -595,7 +1197,7 @@ fn get_node_order(
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncompleteChannels {
     incomplete_channels: HashMap<ShortChannelIdDir, ShortChannelIdDirStateBuilder>,
     updated_channels: HashSet<ShortChannelIdDir>,
@@ -636,15 +1238,19 @@ impl IncompleteChannels {
         self.updated_channels.remove(scid_dir);
         self.incomplete_channels.remove(scid_dir)
     }
-    pub fn update_graph(&mut self, graph: &mut LnGraph) {
-        let mut count_built = 0;
+    /// Promotes every builder with all required fields present into `graph`, and returns the
+    /// directed channels that were newly built this call, e.g. so a caller with
+    /// `sling-verify-channel-funding` enabled can queue them for on-chain validation (see
+    /// [`crate::funding`]) without `update_graph` itself needing to know about that.
+    pub fn update_graph(&mut self, graph: &mut LnGraph) -> Vec<ShortChannelIdDir> {
+        let mut built = Vec::new();
         for updated_chan in self.updated_channels.iter() {
             if let Some(state) = self.incomplete_channels.remove(updated_chan) {
                 match state.build() {
                     BuildResult::Success(state) => {
                         graph.insert(*updated_chan, state);
                         self.incomplete_channels.remove(updated_chan);
-                        count_built += 1;
+                        built.push(*updated_chan);
                     }
                     BuildResult::Failure(builder) => {
                         self.incomplete_channels.insert(*updated_chan, builder);
@@ -654,26 +1260,77 @@ impl IncompleteChannels {
         }
         log::debug!(
             "read_gossip_file: built {}/{} new channels",
-            count_built,
+            built.len(),
             self.updated_channels.len()
         );
         self.updated_channels.clear();
+        built
+    }
+
+    /// Drops builders that never received both an announcement and an update within
+    /// `timeout_secs` of first being seen, so a channel that's half-announced and then
+    /// abandoned (e.g. the counterparty's `channel_update` never arrives) doesn't sit in
+    /// memory forever. Returns how many were reaped, for the same "built X/Y"-style debug
+    /// logging [`Self::update_graph`] does.
+    pub fn reap_timed_out(&mut self, now_secs: u32, timeout_secs: u32) -> usize {
+        let cutoff = now_secs.saturating_sub(timeout_secs);
+        let timed_out: Vec<ShortChannelIdDir> = self
+            .incomplete_channels
+            .iter()
+            .filter(|(_, builder)| builder.first_seen < cutoff)
+            .map(|(scid_dir, _)| *scid_dir)
+            .collect();
+        for scid_dir in &timed_out {
+            self.incomplete_channels.remove(scid_dir);
+            self.updated_channels.remove(scid_dir);
+        }
+        timed_out.len()
     }
 }
 
-#[derive(Debug, Serialize)]
+/// Per-node metadata decoded from a `node_announcement` (see
+/// [`crate::gossip::parse_node_announcement`]), kept alongside the channel graph so routing/
+/// rebalance logic can consult a hop's advertised features and logs can show an alias instead
+/// of a raw pubkey. `features` and `addresses` are kept as the raw advertised bytes since
+/// nothing downstream decodes them yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub features: Vec<u8>,
+    pub last_update: u32,
+    pub rgb_color: [u8; 3],
+    pub alias: String,
+    pub addresses: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LnGraph {
     channels: HashMap<ShortChannelIdDir, ShortChannelIdDirState>,
     graph: HashMap<PubKeyBytes, HashSet<ShortChannelIdDir>>,
+    #[serde(default)]
+    node_info: HashMap<PubKeyBytes, NodeInfo>,
+    /// How many gossip-learned channels [`crate::funding::verify_pending_funding`] has dropped
+    /// for failing on-chain funding-output validation (`sling-verify-channel-funding`).
+    #[serde(default)]
+    rejected_announcements: u64,
 }
 impl LnGraph {
     pub fn new() -> Self {
         LnGraph {
             channels: HashMap::with_capacity(70000),
             graph: HashMap::with_capacity(7000),
+            node_info: HashMap::with_capacity(7000),
+            rejected_announcements: 0,
         }
     }
 
+    pub fn rejected_announcement_count(&self) -> u64 {
+        self.rejected_announcements
+    }
+
+    pub fn record_rejected_announcement(&mut self) {
+        self.rejected_announcements += 1;
+    }
+
     pub fn node_count(&self) -> usize {
         self.graph.len()
     }
@@ -767,6 +1424,10 @@ impl LnGraph {
         self.channels.get_mut(&scid_dir)
     }
 
+    pub fn get_state(&self, scid_dir: ShortChannelIdDir) -> Option<&ShortChannelIdDirState> {
+        self.channels.get(&scid_dir)
+    }
+
     pub fn get_state_no_direction(
         &self,
         source: &PubKeyBytes,
@@ -794,6 +1455,13 @@ impl LnGraph {
         Err(anyhow!("Could not find channel in lngraph: {}", scid))
     }
 
+    /// Candidate edges leaving `source` for pathfinding, filtered on capacity, activity,
+    /// excepts/peer-exclusion, and feature requirements. Also where the liquidity scorer's
+    /// "unusable" cutoff actually bites: [`edge_success_probability`] returns `0.0` once
+    /// `job.amount_msat` exceeds a channel's learned upper bound, so gating on
+    /// `config.min_candidate_success_probability` (instead of only leaning on
+    /// [`crate::util::edge_cost`]'s softer per-edge penalty) drops those channels from the
+    /// candidate set entirely rather than merely discouraging dijkstra from picking them.
     #[allow(clippy::too_many_arguments)]
     pub fn edges(
         &self,
@@ -804,16 +1472,20 @@ impl LnGraph {
         job: &Job,
         excepts: &[ShortChannelIdDir],
         liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+        reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
     ) -> Vec<(&ShortChannelIdDir, &ShortChannelIdDirState)> {
         let mut result = Vec::new();
         if let Some(node_channels) = self.graph.get(source) {
             for dir_chan in node_channels {
                 if let Some(dir_chan_state) = self.channels.get(dir_chan) {
+                    let reserved_msat = reservations.get(dir_chan).map_or(0, |r| r.reserved_msat);
+                    let available_msat =
+                        Amount::msat(&dir_chan_state.htlc_maximum_msat).saturating_sub(reserved_msat);
                     if dir_chan_state.active
                         && dir_chan_state.last_update >= two_weeks_ago
                         && !excepts.contains(dir_chan)
                         && Amount::msat(&dir_chan_state.htlc_minimum_msat) <= job.amount_msat
-                        && Amount::msat(&dir_chan_state.htlc_maximum_msat) >= job.amount_msat
+                        && available_msat >= job.amount_msat
                         && !config.exclude_peers.contains(&dir_chan_state.source)
                         && !config.exclude_peers.contains(&dir_chan_state.destination)
                         && if dir_chan_state.source == config.pubkey_bytes
@@ -825,11 +1497,16 @@ impl LnGraph {
                         } else {
                             true
                         }
-                        && if let Some(liq) = liquidity.get(dir_chan) {
-                            liq.liquidity_msat >= job.amount_msat
-                        } else {
-                            dir_chan_state.htlc_maximum_msat.msat() / 2 >= job.amount_msat
-                        }
+                        && edge_success_probability(
+                            available_msat,
+                            job.amount_msat,
+                            liquidity.get(dir_chan),
+                            config.liquidity_halflife,
+                        ) >= config.min_candidate_success_probability
+                        && self.node_meets_feature_requirement(
+                            &dir_chan_state.destination,
+                            config.required_node_feature_bit,
+                        )
                     {
                         result.push((dir_chan, dir_chan_state));
                     }
@@ -840,6 +1517,107 @@ impl LnGraph {
             Vec::<(&ShortChannelIdDir, &ShortChannelIdDirState)>::new()
         }
     }
+
+    /// Same filtering as `edges()` but walks edges arriving at `destination` instead of
+    /// leaving `source`, for a backward search (e.g. bidirectional dijkstra) meeting a
+    /// forward search in the middle. There is no reverse adjacency index, so this scans
+    /// all known channels rather than a single node's neighbor set.
+    #[allow(clippy::too_many_arguments)]
+    pub fn edges_incoming(
+        &self,
+        destination: &PubKeyBytes,
+        two_weeks_ago: u32,
+        actual_candidates: &[ShortChannelId],
+        config: &Config,
+        job: &Job,
+        excepts: &[ShortChannelIdDir],
+        liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+        reservations: &HashMap<ShortChannelIdDir, ChannelReservation>,
+    ) -> Vec<(&ShortChannelIdDir, &ShortChannelIdDirState)> {
+        let mut result = Vec::new();
+        for (dir_chan, dir_chan_state) in &self.channels {
+            let reserved_msat = reservations.get(dir_chan).map_or(0, |r| r.reserved_msat);
+            let available_msat =
+                Amount::msat(&dir_chan_state.htlc_maximum_msat).saturating_sub(reserved_msat);
+            if dir_chan_state.destination == *destination
+                && dir_chan_state.active
+                && dir_chan_state.last_update >= two_weeks_ago
+                && !excepts.contains(dir_chan)
+                && Amount::msat(&dir_chan_state.htlc_minimum_msat) <= job.amount_msat
+                && available_msat >= job.amount_msat
+                && !config.exclude_peers.contains(&dir_chan_state.source)
+                && !config.exclude_peers.contains(&dir_chan_state.destination)
+                && if dir_chan_state.source == config.pubkey_bytes
+                    || dir_chan_state.destination == config.pubkey_bytes
+                {
+                    actual_candidates
+                        .iter()
+                        .any(|c| c == &dir_chan.short_channel_id)
+                } else {
+                    true
+                }
+                && edge_success_probability(
+                    available_msat,
+                    job.amount_msat,
+                    liquidity.get(dir_chan),
+                    config.liquidity_halflife,
+                ) >= config.min_candidate_success_probability
+                && self.node_meets_feature_requirement(
+                    &dir_chan_state.destination,
+                    config.required_node_feature_bit,
+                )
+            {
+                result.push((dir_chan, dir_chan_state));
+            }
+        }
+        result
+    }
+
+    pub fn set_node_info(&mut self, node: PubKeyBytes, info: NodeInfo) {
+        self.node_info.insert(node, info);
+    }
+
+    pub fn get_node_info(&self, node: &PubKeyBytes) -> Option<&NodeInfo> {
+        self.node_info.get(node)
+    }
+
+    /// Shorthand for `get_node_info(node).map(|info| &info.features)`, for callers (e.g.
+    /// [`Self::edges`]) that only care about a hop's advertised feature bits.
+    pub fn node_features(&self, node: &PubKeyBytes) -> Option<&[u8]> {
+        self.node_info.get(node).map(|info| info.features.as_slice())
+    }
+
+    /// Filter used by [`Self::edges`]/[`Self::edges_incoming`] for `required_node_feature_bit`.
+    /// A node we've never seen a `node_announcement` for is let through rather than excluded,
+    /// since plenty of legitimate hops predate us learning their features.
+    fn node_meets_feature_requirement(&self, node: &PubKeyBytes, required: Option<u32>) -> bool {
+        match required {
+            None => true,
+            Some(bit) => self
+                .node_features(node)
+                .map(|features| node_supports_feature(features, bit))
+                .unwrap_or(true),
+        }
+    }
+}
+
+/// Persisted snapshot of the built [`LnGraph`]/[`IncompleteChannels`], written periodically
+/// (see `graph_snapshot_interval`) and loaded at startup so a plugin restart only has to parse
+/// whatever was appended to `gossip_store` after `offset` instead of reparsing the whole file.
+/// `gossip_store_len` and `gossip_store_prefix_hash` guard against the store having been
+/// rotated or truncated since the snapshot was taken: if either no longer matches, the snapshot
+/// is discarded and sling falls back to a full reparse from the start of the file. `version`
+/// guards against the snapshot's own shape having changed since it was written; see
+/// [`GRAPH_SNAPSHOT_VERSION`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    #[serde(default)]
+    pub version: u32,
+    pub offset: u64,
+    pub gossip_store_len: u64,
+    pub gossip_store_prefix_hash: u64,
+    pub graph: LnGraph,
+    pub incomplete_channels: IncompleteChannels,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -849,6 +1627,17 @@ pub struct SuccessReb {
     pub channel_partner: ShortChannelId,
     pub hops: u8,
     pub completed_at: u64,
+    /// The task within `channel_partner`'s job that ran this attempt, see
+    /// [`TaskIdentifier::get_task_id`]. Defaults to `0` (the lone task most jobs have) when
+    /// deserializing records written before this field existed.
+    #[serde(default)]
+    pub task_id: u16,
+    #[serde(default = "default_sat_direction")]
+    pub sat_direction: SatDirection,
+    /// The scids of every hop the payment actually routed over, in order. Empty for records
+    /// written before this field existed.
+    #[serde(default)]
+    pub route: Vec<ShortChannelId>,
 }
 impl SuccessReb {
     pub async fn write_to_file(
@@ -856,98 +1645,48 @@ impl SuccessReb {
         chan_id: ShortChannelId,
         sling_dir: &Path,
     ) -> Result<(), Error> {
-        let serialized = serde_json::to_string(self)?;
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(sling_dir.join(chan_id.to_string() + "_" + SUCCESSES_SUFFIX))
-            .await?;
-        file.write_all(format!("{}\n", serialized).as_bytes())
-            .await?;
-        Ok(())
+        crate::store::insert_success(sling_dir, chan_id, self)
     }
 
     pub async fn read_from_files(
         sling_dir: &Path,
         search_scid: Option<ShortChannelId>,
     ) -> Result<HashMap<ShortChannelId, Vec<SuccessReb>>, Error> {
-        let mut result = HashMap::new();
-        let mut read_dir = tokio::fs::read_dir(sling_dir).await?;
-        while let Some(file) = read_dir.next_entry().await? {
-            let file_name_os = file.file_name();
-            let file_name = if let Some(f_n) = file_name_os.to_str() {
-                f_n
-            } else {
-                continue;
-            };
-            let file_path = file.path();
-            let file_extension = if let Some(f_e) = file_path.extension() {
-                if let Some(f_e_str) = f_e.to_str() {
-                    f_e_str
-                } else {
-                    continue;
-                }
-            } else {
-                continue;
-            };
-            let (scid_str, suffix) = if let Some(split) = file_name.split_once('_') {
-                split
-            } else {
-                continue;
-            };
-            let scid = if let Ok(id) = ShortChannelId::from_str(scid_str) {
-                id
-            } else {
-                continue;
-            };
-            if let Some(s) = search_scid {
-                if s != scid {
-                    continue;
-                }
-            }
+        crate::store::scan_successes(sling_dir, search_scid)
+    }
 
-            if suffix == SUCCESSES_SUFFIX && file_extension == "json" {
-                log::debug!("Reading success file: {}", file.path().display());
-                let contents = tokio::fs::read_to_string(&file_path).await?;
-                for line in contents.lines() {
-                    let reb: SuccessReb = if let Ok(r) = serde_json::from_str(line) {
-                        r
-                    } else {
-                        continue;
-                    };
-                    match result.entry(scid) {
-                        std::collections::hash_map::Entry::Vacant(e) => {
-                            e.insert(vec![reb]);
-                        }
-                        std::collections::hash_map::Entry::Occupied(mut e) => {
-                            e.get_mut().push(reb);
-                        }
-                    }
-                }
-                if let Some(rebs) = result.remove(&scid) {
-                    if rebs.is_empty() {
-                        log::debug!("Deleting empty success file: {}", file.path().display());
-                        tokio::fs::remove_file(file_path).await?;
-                    } else {
-                        result.insert(scid, rebs);
-                    }
-                } else {
-                    log::debug!("Deleting empty success file: {}", file.path().display());
-                    tokio::fs::remove_file(file_path).await?;
-                }
-            }
-        }
-        Ok(result)
+    /// Pages through `chan_id`'s successes via an indexed range scan rather than loading them
+    /// all into memory, skipping records older than `since` and the first `start` matching
+    /// records, and stopping as soon as `limit` records have been collected. Used by
+    /// `sling-stats`' paginated mode so a channel with tens of thousands of records doesn't have
+    /// to be materialized in full just to serve one page. Returns the page plus whether more
+    /// matching records remain beyond it.
+    pub async fn read_page_from_file(
+        sling_dir: &Path,
+        chan_id: ShortChannelId,
+        since: u64,
+        start: u64,
+        limit: u64,
+    ) -> Result<(Vec<SuccessReb>, bool), Error> {
+        crate::store::page_successes(sling_dir, chan_id, since, start, limit)
     }
 }
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FailureReb {
     pub amount_msat: u64,
-    pub failure_reason: String,
+    pub failure_reason: FailureReason,
     pub failure_node: PublicKey,
     pub channel_partner: ShortChannelId,
     pub hops: u8,
     pub created_at: u64,
+    /// See [`SuccessReb::task_id`].
+    #[serde(default)]
+    pub task_id: u16,
+    #[serde(default = "default_sat_direction")]
+    pub sat_direction: SatDirection,
+    /// See [`SuccessReb::route`].
+    #[serde(default)]
+    pub route: Vec<ShortChannelId>,
 }
 impl FailureReb {
     pub async fn write_to_file(
@@ -955,88 +1694,25 @@ impl FailureReb {
         chan_id: ShortChannelId,
         sling_dir: &Path,
     ) -> Result<(), Error> {
-        let serialized = serde_json::to_string(self)?;
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(sling_dir.join(chan_id.to_string() + "_" + FAILURES_SUFFIX))
-            .await?;
-        file.write_all(format!("{}\n", serialized).as_bytes())
-            .await?;
-        Ok(())
+        crate::store::insert_failure(sling_dir, chan_id, self)
     }
 
     pub async fn read_from_files(
         sling_dir: &Path,
         search_scid: Option<ShortChannelId>,
     ) -> Result<HashMap<ShortChannelId, Vec<FailureReb>>, Error> {
-        let mut result = HashMap::new();
-        let mut read_dir = tokio::fs::read_dir(sling_dir).await?;
-        while let Some(file) = read_dir.next_entry().await? {
-            let file_name_os = file.file_name();
-            let file_name = if let Some(f_n) = file_name_os.to_str() {
-                f_n
-            } else {
-                continue;
-            };
-            let file_path = file.path();
-            let file_extension = if let Some(f_e) = file_path.extension() {
-                if let Some(f_e_str) = f_e.to_str() {
-                    f_e_str
-                } else {
-                    continue;
-                }
-            } else {
-                continue;
-            };
-            let (scid_str, suffix) = if let Some(split) = file_name.split_once('_') {
-                split
-            } else {
-                continue;
-            };
-            let scid = if let Ok(id) = ShortChannelId::from_str(scid_str) {
-                id
-            } else {
-                continue;
-            };
-            if let Some(s) = search_scid {
-                if s != scid {
-                    continue;
-                }
-            }
+        crate::store::scan_failures(sling_dir, search_scid)
+    }
 
-            if suffix == FAILURES_SUFFIX && file_extension == "json" {
-                log::debug!("Reading failure file: {}", file.path().display());
-                let contents = tokio::fs::read_to_string(&file_path).await?;
-                for line in contents.lines() {
-                    let reb: FailureReb = if let Ok(r) = serde_json::from_str(line) {
-                        r
-                    } else {
-                        continue;
-                    };
-                    match result.entry(scid) {
-                        std::collections::hash_map::Entry::Vacant(e) => {
-                            e.insert(vec![reb]);
-                        }
-                        std::collections::hash_map::Entry::Occupied(mut e) => {
-                            e.get_mut().push(reb);
-                        }
-                    }
-                }
-                if let Some(rebs) = result.remove(&scid) {
-                    if rebs.is_empty() {
-                        log::debug!("Deleting empty failure file: {}", file.path().display());
-                        tokio::fs::remove_file(file_path).await?;
-                    } else {
-                        result.insert(scid, rebs);
-                    }
-                } else {
-                    log::debug!("Deleting empty failure file: {}", file.path().display());
-                    tokio::fs::remove_file(file_path).await?;
-                }
-            }
-        }
-        Ok(result)
+    /// Pages through `chan_id`'s failures, see [`SuccessReb::read_page_from_file`].
+    pub async fn read_page_from_file(
+        sling_dir: &Path,
+        chan_id: ShortChannelId,
+        since: u64,
+        start: u64,
+        limit: u64,
+    ) -> Result<(Vec<FailureReb>, bool), Error> {
+        crate::store::page_failures(sling_dir, chan_id, since, start, limit)
     }
 }
 
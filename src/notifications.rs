@@ -1,7 +1,17 @@
 use anyhow::Error;
 use cln_plugin::Plugin;
+use cln_rpc::primitives::{PublicKey, ShortChannelId};
+use serde_json::json;
+use sling::SatDirection;
 
-use crate::{model::PluginState, util::write_liquidity};
+use crate::{
+    errors::FailureReason,
+    model::{JobMessage, PluginState, TaskIdentifier},
+    stats::get_stats_alias,
+    util::write_liquidity,
+};
+
+pub const REBALANCE_NOTIFICATION_TOPIC: &str = "sling_rebalance";
 
 pub async fn shutdown_handler(
     plugin: Plugin<PluginState>,
@@ -11,3 +21,78 @@ pub async fn shutdown_handler(
     write_liquidity(plugin.clone()).await?;
     plugin.shutdown()
 }
+
+/// Publishes a `sling_rebalance` notification so external subscribers (dashboards, alerting)
+/// learn about job lifecycle transitions and completed rebalances without polling `sling-status`.
+pub async fn notify_rebalance(
+    plugin: &Plugin<PluginState>,
+    task_ident: &TaskIdentifier,
+    direction: SatDirection,
+    state: JobMessage,
+    amount_msat: Option<u64>,
+    fee_msat: Option<u64>,
+) {
+    let payload = json!({
+        "scid": task_ident.get_chan_id().to_string(),
+        "task_id": task_ident.get_task_id(),
+        "direction": direction.to_string(),
+        "state": state.to_string(),
+        "amount_msat": amount_msat,
+        "fee_msat": fee_msat,
+    });
+    if let Err(e) = plugin
+        .send_custom_notification(REBALANCE_NOTIFICATION_TOPIC.to_string(), payload)
+        .await
+    {
+        log::warn!(
+            "{task_ident}: failed to emit {REBALANCE_NOTIFICATION_TOPIC} notification: {e}"
+        );
+    }
+}
+
+/// Publishes a `sling_rebalance` notification at the moment a `SuccessReb`/`FailureReb` is
+/// written to disk, carrying the same fields `sling-stats` surfaces for a channel partner so a
+/// subscriber can build a live feed instead of polling `sling-stats`. Reuses
+/// [`REBALANCE_NOTIFICATION_TOPIC`] rather than adding a second topic, distinguished by the
+/// `"outcome"` field.
+pub async fn notify_rebalance_outcome(
+    plugin: &Plugin<PluginState>,
+    task_ident: &TaskIdentifier,
+    channel_partner: ShortChannelId,
+    amount_msat: u64,
+    fee_ppm: u32,
+    hops: u8,
+    failure: Option<(&FailureReason, PublicKey)>,
+) {
+    let (peer_channels, alias_map) = {
+        let peer_channels = plugin.state().peer_channels.lock().clone();
+        let alias_map = plugin.state().alias_peer_map.lock().clone();
+        (peer_channels, alias_map)
+    };
+    let channel_partner_alias = get_stats_alias(&peer_channels, &channel_partner, &alias_map);
+
+    let mut payload = json!({
+        "scid": task_ident.get_chan_id().to_string(),
+        "task_id": task_ident.get_task_id(),
+        "channel_partner": channel_partner.to_string(),
+        "channel_partner_alias": channel_partner_alias,
+        "amount_sats": amount_msat / 1_000,
+        "fee_ppm": fee_ppm,
+        "hops": hops,
+        "outcome": if failure.is_some() { "failure" } else { "success" },
+    });
+    if let Some((reason, node)) = failure {
+        let map = payload.as_object_mut().unwrap();
+        map.insert("failure_reason".to_string(), json!(reason.to_string()));
+        map.insert("failure_node".to_string(), json!(node.to_string()));
+    }
+
+    if let Err(e) = plugin
+        .send_custom_notification(REBALANCE_NOTIFICATION_TOPIC.to_string(), payload)
+        .await
+    {
+        log::warn!(
+            "{task_ident}: failed to emit {REBALANCE_NOTIFICATION_TOPIC} notification: {e}"
+        );
+    }
+}
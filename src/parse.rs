@@ -16,6 +16,7 @@ pub async fn parse_job(
         "direction",
         "amount",
         "maxppm",
+        "maxfee",
         "outppm",
         "target",
         "maxhops",
@@ -23,6 +24,12 @@ pub async fn parse_job(
         "depleteuptopercent",
         "depleteuptoamount",
         "paralleljobs",
+        "minprobability",
+        "maxparts",
+        "amountpart",
+        "randomsplit",
+        "splitonfail",
+        "schedule",
     ];
 
     match args {
@@ -73,6 +80,14 @@ pub async fn parse_job(
 
             let mut job = Job::new(sat_direction, amount_msat, outppm, maxppm);
 
+            if let Some(mf) = ar.get("maxfee") {
+                let maxfee_msat = mf.as_u64().ok_or(anyhow!("maxfee must be an integer"))? * 1_000;
+                if maxfee_msat == 0 {
+                    return Err(anyhow!("maxfee must be greater than 0"));
+                }
+                job.add_maxfee_msat(maxfee_msat);
+            }
+
             if let Some(target) = ar.get("target") {
                 job.add_target(
                     target
@@ -135,6 +150,51 @@ pub async fn parse_job(
                 job.add_paralleljobs(pj);
             }
 
+            let minprobability = match ar.get("minprobability") {
+                Some(mp) => Some(
+                    mp.as_f64()
+                        .ok_or(anyhow!("minprobability must be a floating point"))?,
+                ),
+                None => None,
+            };
+            if let Some(mp) = minprobability {
+                if !(0.0..=1.0).contains(&mp) {
+                    return Err(anyhow!("minprobability must be between 0.0 and 1.0"));
+                }
+                job.add_minprobability(mp);
+            }
+
+            let maxparts = match ar.get("maxparts") {
+                Some(mp) => Some(mp.as_u64().ok_or(anyhow!("maxparts must be an integer"))? as u8),
+                None => None,
+            };
+            if let Some(mp) = maxparts {
+                if mp < 1 {
+                    return Err(anyhow!("maxparts must be atleast 1"));
+                }
+                job.add_maxparts(mp);
+            }
+
+            if let Some(ap) = ar.get("amountpart") {
+                job.add_amountpart_msat(
+                    ap.as_u64()
+                        .ok_or(anyhow!("amountpart must be an integer"))?
+                        * 1_000,
+                );
+            };
+
+            if let Some(rs) = ar.get("randomsplit") {
+                job.add_randomsplit(
+                    rs.as_bool().ok_or(anyhow!("randomsplit must be a bool"))?,
+                );
+            };
+
+            if let Some(sof) = ar.get("splitonfail") {
+                job.add_splitonfail(
+                    sof.as_bool().ok_or(anyhow!("splitonfail must be a bool"))?,
+                );
+            };
+
             let candidatelist = {
                 let mut tmpcandidatelist = Vec::new();
                 match ar.get("candidates") {
@@ -214,6 +274,15 @@ pub async fn parse_job(
                 job.add_candidates(c);
             }
 
+            if let Some(s) = ar.get("schedule") {
+                let schedule = s
+                    .as_str()
+                    .ok_or(anyhow!("schedule must be a string"))?
+                    .to_string();
+                crate::scheduler::parse_schedule(&schedule)?;
+                job.add_schedule(schedule);
+            }
+
             Ok((chan_id, job))
         }
         other => Err(anyhow!("Expected an object! Got {} instead", other)),
@@ -229,6 +298,7 @@ pub async fn parse_once_job(
         "direction",
         "amount",
         "maxppm",
+        "maxfee",
         "outppm",
         "target",
         "maxhops",
@@ -236,6 +306,11 @@ pub async fn parse_once_job(
         "depleteuptopercent",
         "depleteuptoamount",
         "paralleljobs",
+        "minprobability",
+        "maxparts",
+        "amountpart",
+        "randomsplit",
+        "splitonfail",
         "onceamount",
     ];
 
@@ -291,6 +366,14 @@ pub async fn parse_once_job(
 
             let mut job = Job::new(sat_direction, amount_msat, outppm, maxppm);
 
+            if let Some(mf) = ar.get("maxfee") {
+                let maxfee_msat = mf.as_u64().ok_or(anyhow!("maxfee must be an integer"))? * 1_000;
+                if maxfee_msat == 0 {
+                    return Err(anyhow!("maxfee must be greater than 0"));
+                }
+                job.add_maxfee_msat(maxfee_msat);
+            }
+
             let onceamount_msat = match ar.get("onceamount") {
                 Some(amt) => {
                     amt.as_u64()
@@ -373,6 +456,51 @@ pub async fn parse_once_job(
                 job.add_paralleljobs(pj);
             }
 
+            let minprobability = match ar.get("minprobability") {
+                Some(mp) => Some(
+                    mp.as_f64()
+                        .ok_or(anyhow!("minprobability must be a floating point"))?,
+                ),
+                None => None,
+            };
+            if let Some(mp) = minprobability {
+                if !(0.0..=1.0).contains(&mp) {
+                    return Err(anyhow!("minprobability must be between 0.0 and 1.0"));
+                }
+                job.add_minprobability(mp);
+            }
+
+            let maxparts = match ar.get("maxparts") {
+                Some(mp) => Some(mp.as_u64().ok_or(anyhow!("maxparts must be an integer"))? as u8),
+                None => None,
+            };
+            if let Some(mp) = maxparts {
+                if mp < 1 {
+                    return Err(anyhow!("maxparts must be atleast 1"));
+                }
+                job.add_maxparts(mp);
+            }
+
+            if let Some(ap) = ar.get("amountpart") {
+                job.add_amountpart_msat(
+                    ap.as_u64()
+                        .ok_or(anyhow!("amountpart must be an integer"))?
+                        * 1_000,
+                );
+            };
+
+            if let Some(rs) = ar.get("randomsplit") {
+                job.add_randomsplit(
+                    rs.as_bool().ok_or(anyhow!("randomsplit must be a bool"))?,
+                );
+            };
+
+            if let Some(sof) = ar.get("splitonfail") {
+                job.add_splitonfail(
+                    sof.as_bool().ok_or(anyhow!("splitonfail must be a bool"))?,
+                );
+            };
+
             let candidatelist = {
                 let mut tmpcandidatelist = Vec::new();
                 match ar.get("candidates") {
@@ -0,0 +1,315 @@
+//! Pre-flight liquidity probing: learn a channel's liquidity bounds the same way a real
+//! rebalance does, but without ever risking a real payment. A probe builds a route that leaves
+//! through a local channel and loops back into our own node via another one, exactly like a
+//! normal [`sling::SatDirection::Push`] job, and sends it with a payment hash whose preimage
+//! nobody holds. The HTLC can only ever fail, but *where* it fails still proves exactly as much
+//! as a real `waitsendpay_response` outcome would: reaching back to us means every hop on the
+//! route had the capacity to carry the probed amount, and a mid-route failure still bounds the
+//! hops it did get through. See `sling-probe-enabled`/`sling-probe-interval-secs` and
+//! [`crate::tasks::run_liquidity_probes`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Error};
+use cln_plugin::Plugin;
+use cln_rpc::{
+    model::requests::{SendpayRequest, WaitsendpayRequest},
+    primitives::{Amount, PublicKey, ShortChannelId, ShortChannelIdDir},
+    ClnRpc,
+};
+use sling::{Job, SatDirection};
+
+use crate::{
+    dijkstra::dijkstra,
+    errors::{classify_failcode, FailureClass, WaitsendpayErrorData},
+    model::{JobMessage, PluginState, PubKeyBytes, Task},
+    response::{apply_temp_ban, channel_capacity_msat, hop_dir_chan, lower_max_liquidity, raise_min_liquidity},
+    util::{get_direction_from_nodes, get_preimage_paymend_hash_pair, is_channel_normal},
+    Config,
+};
+
+/// Every probe job uses the same throwaway task id: probes never get registered in
+/// `plugin.state().tasks`, so there's no real task slot to collide with.
+const PROBE_TASK_ID: u16 = 0;
+const PROBE_SEARCH_ROUNDS: u32 = 8;
+
+struct ProbeCandidate {
+    home_scid: ShortChannelId,
+    home_peer: PublicKey,
+    candidates: Vec<ShortChannelId>,
+}
+
+/// Picks one local channel whose [`crate::model::Liquidity`] belief is missing or older than
+/// `config.reset_liquidity_interval`, paired with the rest of our usable channels to route the
+/// probe back through, mirroring `slings::build_candidatelist`'s default "no custom candidates"
+/// behaviour. Returns `None` if every channel already has a fresh belief, or we don't have
+/// enough usable channels to route a loop at all.
+fn pick_probe_candidate(plugin: &Plugin<PluginState>, config: &Config) -> Option<ProbeCandidate> {
+    let peer_channels = plugin.state().peer_channels.lock();
+    let liquidity = plugin.state().liquidity.lock();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let usable: Vec<(ShortChannelId, PublicKey)> = peer_channels
+        .values()
+        .filter_map(|channel| {
+            let scid = channel.short_channel_id?;
+            if !channel.peer_connected || is_channel_normal(channel).is_err() {
+                return None;
+            }
+            if config.exclude_chans_push.contains(&scid)
+                || config
+                    .exclude_peers
+                    .contains(&PubKeyBytes::from_pubkey(&channel.peer_id))
+            {
+                return None;
+            }
+            Some((scid, channel.peer_id))
+        })
+        .collect();
+
+    if usable.len() < 2 {
+        return None;
+    }
+
+    usable.iter().find_map(|(scid, peer_id)| {
+        let direction = get_direction_from_nodes(config.pubkey, *peer_id).ok()?;
+        let dir_chan = ShortChannelIdDir {
+            short_channel_id: *scid,
+            direction,
+        };
+        let is_fresh = liquidity.get(&dir_chan).is_some_and(|l| {
+            now.saturating_sub(l.liquidity_age) < config.reset_liquidity_interval
+        });
+        if is_fresh {
+            return None;
+        }
+        Some(ProbeCandidate {
+            home_scid: *scid,
+            home_peer: *peer_id,
+            candidates: usable
+                .iter()
+                .filter(|(c, _)| c != scid)
+                .map(|(c, _)| *c)
+                .collect(),
+        })
+    })
+}
+
+/// Sends a single probe of `amount_msat` out `candidate.home_scid` and back through one of its
+/// sibling channels, using a payment hash whose preimage nobody holds so the HTLC can only ever
+/// fail. Returns `Ok(true)` if the failure proves the whole route (including
+/// `candidate.home_scid`) had the capacity to carry `amount_msat`, `Ok(false)` if it failed
+/// somewhere along the way.
+async fn probe_channel(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    candidate: &ProbeCandidate,
+    amount_msat: u64,
+) -> Result<bool, Error> {
+    let mut job = Job::new(SatDirection::Push, amount_msat, None, u32::MAX);
+    job.add_candidates(candidate.candidates.clone());
+    job.add_maxhops(config.maxhops);
+
+    let mut task = Task::new(
+        candidate.home_scid,
+        PROBE_TASK_ID,
+        JobMessage::Rebalancing,
+        true,
+        PubKeyBytes::from_pubkey(&candidate.home_peer),
+    );
+
+    let route = {
+        let graph = plugin.state().graph.lock();
+        let liquidity = plugin.state().liquidity.lock();
+        let reservations = plugin.state().reservations.lock();
+        let mut excepts = Vec::new();
+        dijkstra(
+            config,
+            &graph,
+            &job,
+            &mut task,
+            &candidate.candidates,
+            &mut excepts,
+            &liquidity,
+            &reservations,
+        )?
+    };
+
+    if route.is_empty() {
+        return Err(anyhow!(
+            "no route found to probe {}",
+            candidate.home_scid
+        ));
+    }
+
+    let (_preimage, payment_hash) = get_preimage_paymend_hash_pair();
+
+    let mut rpc = ClnRpc::new(&config.rpc_path).await?;
+    rpc.call_typed(&SendpayRequest {
+        route: route.clone(),
+        payment_hash,
+        label: None,
+        amount_msat: None,
+        bolt11: None,
+        payment_secret: None,
+        partid: None,
+        localinvreqid: None,
+        groupid: None,
+        description: None,
+        payment_metadata: None,
+    })
+    .await?;
+
+    match rpc
+        .call_typed(&WaitsendpayRequest {
+            payment_hash,
+            timeout: Some(u32::from(config.timeoutpay)),
+            partid: None,
+            groupid: None,
+        })
+        .await
+    {
+        Ok(_) => {
+            // A probe payment hash has no invoice registered anywhere on the route, so an
+            // actual `Ok` here should never happen; treat it the same as a full-route success.
+            for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                    continue;
+                }
+                raise_min_liquidity(
+                    plugin,
+                    config,
+                    hop_dir_chan(&route, i)?,
+                    Amount::msat(&hop.amount_msat),
+                )
+                .await?;
+            }
+            Ok(true)
+        }
+        Err(err) => {
+            let Some(ws_code) = err.code else {
+                return Err(anyhow!(
+                    "probe of {}: no WaitsendpayErrorCode, instead: {}",
+                    candidate.home_scid,
+                    err.message
+                ));
+            };
+
+            if ws_code == 200 {
+                // WAITSENDPAY_TIMEOUT: we learned nothing trustworthy about any hop, so just
+                // nudge every bound down a little rather than assuming the probed amount works.
+                for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                    if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                        continue;
+                    }
+                    lower_max_liquidity(plugin, config, hop_dir_chan(&route, i)?, 0).await?;
+                }
+                return Ok(false);
+            }
+
+            let Some(d) = err.data else {
+                return Err(anyhow!(
+                    "probe of {}: unexpected waitsendpay failure: {}",
+                    candidate.home_scid,
+                    err.message
+                ));
+            };
+            let ws_error = serde_json::from_value::<WaitsendpayErrorData>(d)?;
+
+            if ws_error.failcodename.eq("WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS")
+                && ws_error.erring_node == config.pubkey
+            {
+                // The HTLC made it all the way back to us and failed only because nobody holds
+                // this probe's preimage, so every hop on the route had the capacity to carry
+                // `amount_msat`.
+                for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                    if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                        continue;
+                    }
+                    raise_min_liquidity(
+                        plugin,
+                        config,
+                        hop_dir_chan(&route, i)?,
+                        Amount::msat(&hop.amount_msat),
+                    )
+                    .await?;
+                }
+                return Ok(true);
+            }
+
+            // Every hop strictly before the one erring_index blames demonstrably forwarded the
+            // probe, raising our confidence in its min liquidity.
+            for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                if i + 1 >= ws_error.erring_index as usize {
+                    break;
+                }
+                if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                    continue;
+                }
+                raise_min_liquidity(
+                    plugin,
+                    config,
+                    hop_dir_chan(&route, i)?,
+                    Amount::msat(&hop.amount_msat),
+                )
+                .await?;
+            }
+
+            let dir_chan = ShortChannelIdDir {
+                short_channel_id: ws_error.erring_channel,
+                direction: u32::from(ws_error.erring_direction),
+            };
+            let attempted_msat = ws_error.amount_msat.map_or(amount_msat, |a| a.msat());
+            lower_max_liquidity(plugin, config, dir_chan, attempted_msat).await?;
+            if matches!(classify_failcode(ws_error.failcode), FailureClass::Temporary) {
+                apply_temp_ban(plugin, config, ws_error.erring_channel);
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Binary-searches the probe amount for one channel lacking a fresh liquidity belief, running
+/// [`PROBE_SEARCH_ROUNDS`] rounds to converge `min_liquidity_msat`/`liquidity_msat` without ever
+/// probing past the channel's own htlc_maximum_msat. No-op if nothing needs probing right now.
+pub(crate) async fn run_probe_round(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+) -> Result<(), Error> {
+    let Some(candidate) = pick_probe_candidate(plugin, config) else {
+        return Ok(());
+    };
+
+    let dir_chan = ShortChannelIdDir {
+        short_channel_id: candidate.home_scid,
+        direction: get_direction_from_nodes(config.pubkey, candidate.home_peer)?,
+    };
+    let mut low = 0u64;
+    let mut high = channel_capacity_msat(plugin, dir_chan);
+    if high == 0 {
+        return Ok(());
+    }
+
+    for _ in 0..PROBE_SEARCH_ROUNDS {
+        if high <= low + 1 {
+            break;
+        }
+        let amount_msat = low + (high - low) / 2;
+        match probe_channel(plugin, config, &candidate, amount_msat).await {
+            Ok(true) => low = amount_msat,
+            Ok(false) => high = amount_msat,
+            Err(e) => {
+                log::debug!(
+                    "probe: giving up on {} after a routing error: {e}",
+                    candidate.home_scid
+                );
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -13,42 +13,210 @@ use cln_rpc::{
         },
         responses::SendpayResponse,
     },
-    primitives::{Amount, Sha256, ShortChannelIdDir},
+    primitives::{Amount, PublicKey, Sha256, ShortChannelId, ShortChannelIdDir},
     ClnRpc,
 };
 use sling::{Job, SatDirection};
 use tokio::time::Instant;
 
 use crate::{
-    errors::WaitsendpayErrorData,
+    errors::{classify_failcode, FailureClass, FailureReason, WaitsendpayErrorData},
     feeppm_effective_from_amts,
-    model::{Liquidity, PayResolveInfo, TaskIdentifier},
-    my_sleep,
-    util::get_direction_from_nodes,
+    model::{
+        record_latency_ms,
+        ChannelBackoff,
+        JobMessage,
+        Liquidity,
+        PayResolveInfo,
+        TaskIdentifier,
+        LIQUIDITY_BUCKETS,
+    },
+    notifications::{notify_rebalance, notify_rebalance_outcome},
+    util::{
+        append_liquidity_update, decay_liquidity_bounds, get_direction_from_nodes,
+        record_liquidity_bucket, reset_liquidity_if_capacity_changed,
+    },
     Config,
     FailureReb,
     PluginState,
     SuccessReb,
 };
 
+/// The directed channel a given hop of `route` routes over, with the direction derived
+/// from who it forwards between. Mirrors the indexing `waitsendpay_response` already used
+/// for the WAITSENDPAY_TIMEOUT case: `route[i]` is the hop landing on `route[i].id`, so the
+/// last entry has no successor to pair it with and isn't representable here.
+pub(crate) fn hop_dir_chan(route: &[SendpayRoute], i: usize) -> Result<ShortChannelIdDir, Error> {
+    let direction = get_direction_from_nodes(route[i].id, route[i + 1].id)?;
+    Ok(ShortChannelIdDir {
+        short_channel_id: route[i].channel,
+        direction,
+    })
+}
+
+pub(crate) fn channel_capacity_msat(plugin: &Plugin<PluginState>, dir_chan: ShortChannelIdDir) -> u64 {
+    plugin
+        .state()
+        .graph
+        .lock()
+        .get_state_mut_direction(dir_chan)
+        .map_or(u64::MAX, |s| Amount::msat(&s.htlc_maximum_msat))
+}
+
+/// Record that `amount_msat` demonstrably forwarded over `dir_chan`, raising our learned
+/// lower bound on its liquidity. Resets the shared age so both bounds are trusted again.
+/// Journals the updated bound so it survives a crash before the next liquidity compaction.
+pub(crate) async fn raise_min_liquidity(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    dir_chan: ShortChannelIdDir,
+    amount_msat: u64,
+) -> Result<(), Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let capacity_msat = channel_capacity_msat(plugin, dir_chan);
+    let mut liquidity = plugin.state().liquidity.lock();
+    let updated = match liquidity.get_mut(&dir_chan) {
+        Some(liq) => {
+            reset_liquidity_if_capacity_changed(liq, capacity_msat);
+            decay_liquidity_bounds(liq, capacity_msat, now, config.liquidity_halflife);
+            liq.min_liquidity_msat = liq.min_liquidity_msat.max(amount_msat);
+            liq.liquidity_age = now;
+            record_liquidity_bucket(liq, amount_msat, capacity_msat, true);
+            *liq
+        }
+        None => {
+            let mut liq = Liquidity {
+                liquidity_msat: capacity_msat,
+                liquidity_age: now,
+                min_liquidity_msat: amount_msat,
+                capacity_msat,
+                success_buckets: [0.0; LIQUIDITY_BUCKETS],
+                fail_buckets: [0.0; LIQUIDITY_BUCKETS],
+            };
+            record_liquidity_bucket(&mut liq, amount_msat, capacity_msat, true);
+            liquidity.insert(dir_chan, liq);
+            liq
+        }
+    };
+    drop(liquidity);
+    append_liquidity_update(plugin, &config.sling_dir, dir_chan, updated).await
+}
+
+/// Record that `attempted_amount_msat` demonstrably failed to forward over `dir_chan`,
+/// lowering our learned upper bound on its liquidity to just under the failed amount.
+/// Journals the updated bound so it survives a crash before the next liquidity compaction.
+pub(crate) async fn lower_max_liquidity(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    dir_chan: ShortChannelIdDir,
+    attempted_amount_msat: u64,
+) -> Result<(), Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let new_max = attempted_amount_msat.saturating_sub(1);
+    let capacity_msat = channel_capacity_msat(plugin, dir_chan);
+    let mut liquidity = plugin.state().liquidity.lock();
+    let updated = match liquidity.get_mut(&dir_chan) {
+        Some(liq) => {
+            reset_liquidity_if_capacity_changed(liq, capacity_msat);
+            decay_liquidity_bounds(liq, capacity_msat, now, config.liquidity_halflife);
+            liq.liquidity_msat = liq.liquidity_msat.min(new_max);
+            liq.min_liquidity_msat = liq.min_liquidity_msat.min(liq.liquidity_msat);
+            liq.liquidity_age = now;
+            record_liquidity_bucket(liq, attempted_amount_msat, capacity_msat, false);
+            *liq
+        }
+        None => {
+            let mut liq = Liquidity {
+                liquidity_msat: new_max,
+                liquidity_age: now,
+                min_liquidity_msat: 0,
+                capacity_msat,
+                success_buckets: [0.0; LIQUIDITY_BUCKETS],
+                fail_buckets: [0.0; LIQUIDITY_BUCKETS],
+            };
+            record_liquidity_bucket(&mut liq, attempted_amount_msat, capacity_msat, false);
+            liquidity.insert(dir_chan, liq);
+            liq
+        }
+    };
+    drop(liquidity);
+    append_liquidity_update(plugin, &config.sling_dir, dir_chan, updated).await
+}
+
+/// How long a channel sits out after `consecutive_failures` temporary failures in a row since
+/// its last success: doubles each time, capped at `config.backoff_max_secs`.
+fn backoff_duration_secs(config: &Config, consecutive_failures: u32) -> u64 {
+    config
+        .backoff_base_secs
+        .saturating_mul(2_u64.saturating_pow(consecutive_failures))
+        .min(config.backoff_max_secs)
+}
+
+/// Record a temporary failure on `scid`, extending its exponential backoff.
+pub(crate) fn apply_temp_ban(plugin: &Plugin<PluginState>, config: &Config, scid: ShortChannelId) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut temp_chan_bans = plugin.state().temp_chan_bans.lock();
+    let consecutive_failures = temp_chan_bans.get(&scid).map_or(0, |b| b.consecutive_failures) + 1;
+    temp_chan_bans.insert(
+        scid,
+        ChannelBackoff {
+            banned_until: now + backoff_duration_secs(config, consecutive_failures),
+            consecutive_failures,
+        },
+    );
+}
+
+/// Clear any backoff state on `scid`, since a successful forward means it's healthy again.
+fn reset_backoff(plugin: &Plugin<PluginState>, scid: ShortChannelId) {
+    plugin.state().temp_chan_bans.lock().remove(&scid);
+}
+
+/// Applies this failure's BOLT04 classification: permanently excludes a dead channel/node for
+/// the rest of this run, or backs off a merely-constrained channel with exponential delay.
+fn handle_erring_channel(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    failcode: u32,
+    erring_channel: ShortChannelId,
+    erring_node: PublicKey,
+) {
+    match classify_failcode(failcode) {
+        FailureClass::Permanent => {
+            plugin.state().excluded_scids.lock().insert(erring_channel);
+            plugin.state().excluded_nodes.lock().insert(erring_node);
+        }
+        FailureClass::Temporary => apply_temp_ban(plugin, config, erring_channel),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn waitsendpay_response(
     plugin: Plugin<PluginState>,
     config: &Config,
     payment_hash: Sha256,
+    partid_groupid: Option<(u64, u64)>,
     task_ident: &TaskIdentifier,
     now: Instant,
     job: &Job,
     route: &[SendpayRoute],
     success_route: &mut Option<Vec<SendpayRoute>>,
-) -> Result<u64, Error> {
+) -> Result<(u64, u64), Error> {
     let mut rpc = ClnRpc::new(&config.rpc_path).await?;
     match rpc
         .call_typed(&WaitsendpayRequest {
             payment_hash,
             timeout: Some(u32::from(config.timeoutpay)),
-            partid: None,
-            groupid: None,
+            partid: partid_groupid.map(|(partid, _)| partid),
+            groupid: partid_groupid.map(|(_, groupid)| groupid),
         })
         .await
     {
@@ -61,23 +229,88 @@ pub async fn waitsendpay_response(
                 Amount::msat(&o.amount_sent_msat) - Amount::msat(&o.amount_msat.unwrap()),
             );
 
-            SuccessReb {
-                amount_msat: Amount::msat(&o.amount_msat.unwrap()),
-                fee_ppm: feeppm_effective_from_amts(
-                    Amount::msat(&o.amount_sent_msat),
-                    Amount::msat(&o.amount_msat.unwrap()),
-                ),
-                channel_partner: match job.sat_direction {
+            let amount_msat = Amount::msat(&o.amount_msat.unwrap());
+            let fee_msat = Amount::msat(&o.amount_sent_msat) - amount_msat;
+
+            record_latency_ms(&plugin.state().rebalance_latency_ms, now.elapsed());
+
+            for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                    continue;
+                }
+                let dir_chan = hop_dir_chan(route, i)?;
+                let hop_amount_msat = Amount::msat(&hop.amount_msat);
+                raise_min_liquidity(&plugin, config, dir_chan, hop_amount_msat).await?;
+                reset_backoff(&plugin, hop.channel);
+
+                if config.at_or_above_24_11 {
+                    for lay in &config.inform_layers {
+                        log::debug!(
+                            "{}: Informing layer `{}` about scid_dir:{} amt:{}msat success",
+                            task_ident,
+                            lay,
+                            dir_chan,
+                            hop_amount_msat
+                        );
+                        rpc.call_typed(&AskreneinformchannelRequest {
+                            amount_msat: Some(hop.amount_msat),
+                            inform: Some(AskreneinformchannelInform::SUCCEEDED),
+                            short_channel_id_dir: Some(dir_chan),
+                            layer: lay.clone(),
+                        })
+                        .await?;
+                    }
+                }
+            }
+
+            if partid_groupid.is_none() {
+                let channel_partner = match job.sat_direction {
                     SatDirection::Pull => route.first().unwrap().channel,
                     SatDirection::Push => route.last().unwrap().channel,
-                },
-                hops: u8::try_from(route.len() - 1)?,
-                completed_at: o.completed_at.unwrap() as u64,
+                };
+                let hops = u8::try_from(route.len() - 1)?;
+                let fee_ppm =
+                    feeppm_effective_from_amts(Amount::msat(&o.amount_sent_msat), amount_msat);
+
+                // MPP parts only resolve together (`htlc_handler` holds every sibling's HTLC
+                // open until all of them land, then releases all at once), so each part's own
+                // `Ok` here already implies the whole group succeeded. Writing one `SuccessReb`
+                // per part would multiply-count the same rebalance; the caller aggregates the
+                // parts into a single record once every part has come back `Ok` instead.
+                SuccessReb {
+                    amount_msat,
+                    fee_ppm,
+                    channel_partner,
+                    hops,
+                    completed_at: o.completed_at.unwrap() as u64,
+                    task_id: task_ident.get_task_id(),
+                    sat_direction: job.sat_direction,
+                    route: route.iter().map(|hop| hop.channel).collect(),
+                }
+                .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
+                .await?;
+                notify_rebalance_outcome(
+                    &plugin,
+                    task_ident,
+                    channel_partner,
+                    amount_msat,
+                    fee_ppm,
+                    hops,
+                    None,
+                )
+                .await;
             }
-            .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
-            .await?;
             *success_route = Some(route.to_vec());
-            Ok(o.amount_msat.unwrap().msat())
+            notify_rebalance(
+                &plugin,
+                task_ident,
+                job.sat_direction,
+                JobMessage::Rebalancing,
+                Some(amount_msat),
+                Some(fee_msat),
+            )
+            .await;
+            Ok((amount_msat, fee_msat))
         }
         Err(err) => {
             *success_route = None;
@@ -104,56 +337,42 @@ pub async fn waitsendpay_response(
                 );
 
                 for (i, hop) in route[..route.len() - 1].iter().enumerate() {
-                    let source = route[i].id;
-                    let destination = route[i + 1].id;
-                    let direction = get_direction_from_nodes(source, destination)?;
-                    if source == config.pubkey {
+                    if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
                         continue;
                     }
-                    if destination == config.pubkey {
-                        continue;
-                    }
-                    let dir_chan = ShortChannelIdDir {
-                        short_channel_id: hop.channel,
-                        direction,
-                    };
-
-                    let mut liquidity = plugin.state().liquidity.lock();
-                    if let Some(liq) = liquidity.get_mut(&dir_chan) {
-                        liq.liquidity_msat = 0;
-                        liq.liquidity_age = SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs();
-                    } else {
-                        liquidity.insert(
-                            dir_chan,
-                            Liquidity {
-                                liquidity_msat: 0,
-                                liquidity_age: SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs(),
-                            },
-                        );
-                    }
+                    lower_max_liquidity(&plugin, config, hop_dir_chan(route, i)?, 0).await?;
                 }
+                let channel_partner = match job.sat_direction {
+                    SatDirection::Pull => route.first().unwrap().channel,
+                    SatDirection::Push => route.last().unwrap().channel,
+                };
+                let hops = u8::try_from(route.len() - 1)?;
                 FailureReb {
                     amount_msat: job.amount_msat,
-                    failure_reason: "WAITSENDPAY_TIMEOUT".to_string(),
+                    failure_reason: FailureReason::Timeout,
                     failure_node: config.pubkey,
-                    channel_partner: match job.sat_direction {
-                        SatDirection::Pull => route.first().unwrap().channel,
-                        SatDirection::Push => route.last().unwrap().channel,
-                    },
-                    hops: u8::try_from(route.len() - 1)?,
+                    channel_partner,
+                    hops,
                     created_at: SystemTime::now()
                         .duration_since(UNIX_EPOCH)
                         .unwrap()
                         .as_secs(),
+                    task_id: task_ident.get_task_id(),
+                    sat_direction: job.sat_direction,
+                    route: route.iter().map(|hop| hop.channel).collect(),
                 }
                 .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
                 .await?;
+                notify_rebalance_outcome(
+                    &plugin,
+                    task_ident,
+                    channel_partner,
+                    job.amount_msat,
+                    0,
+                    hops,
+                    Some((&FailureReason::Timeout, config.pubkey)),
+                )
+                .await;
                 Ok(0)
             } else if let Some(d) = err.data {
                 let ws_error = serde_json::from_value::<WaitsendpayErrorData>(d)?;
@@ -182,19 +401,35 @@ pub async fn waitsendpay_response(
                     _ => (),
                 }
 
+                let channel_partner = match job.sat_direction {
+                    SatDirection::Pull => route.first().unwrap().channel,
+                    SatDirection::Push => route.last().unwrap().channel,
+                };
+                let hops = u8::try_from(route.len() - 1)?;
+                let failure_reason: FailureReason = ws_error.failcodename.parse().unwrap();
                 FailureReb {
                     amount_msat: ws_error.amount_msat.unwrap().msat(),
-                    failure_reason: ws_error.failcodename.clone(),
+                    failure_reason: failure_reason.clone(),
                     failure_node: ws_error.erring_node,
-                    channel_partner: match job.sat_direction {
-                        SatDirection::Pull => route.first().unwrap().channel,
-                        SatDirection::Push => route.last().unwrap().channel,
-                    },
-                    hops: u8::try_from(route.len() - 1)?,
+                    channel_partner,
+                    hops,
                     created_at: ws_error.created_at,
+                    task_id: task_ident.get_task_id(),
+                    sat_direction: job.sat_direction,
+                    route: route.iter().map(|hop| hop.channel).collect(),
                 }
                 .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
                 .await?;
+                notify_rebalance_outcome(
+                    &plugin,
+                    task_ident,
+                    channel_partner,
+                    ws_error.amount_msat.unwrap().msat(),
+                    0,
+                    hops,
+                    Some((&failure_reason, ws_error.erring_node)),
+                )
+                .await;
                 if special_stop {
                     return Err(anyhow!(
                         "{}: UNEXPECTED waitsendpay failure after {}s: {}",
@@ -204,6 +439,24 @@ pub async fn waitsendpay_response(
                     ));
                 }
 
+                // Every hop strictly before the one erring_index blames demonstrably
+                // forwarded the payment on, raising our confidence in its min liquidity.
+                for (i, hop) in route[..route.len() - 1].iter().enumerate() {
+                    if i + 1 >= ws_error.erring_index as usize {
+                        break;
+                    }
+                    if hop.id == config.pubkey || route[i + 1].id == config.pubkey {
+                        continue;
+                    }
+                    raise_min_liquidity(
+                        &plugin,
+                        config,
+                        hop_dir_chan(route, i)?,
+                        Amount::msat(&hop.amount_msat),
+                    )
+                    .await?;
+                }
+
                 if ws_error.erring_channel == route.last().unwrap().channel {
                     log::warn!(
                         "{}: Last peer has a problem or just updated their fees? {}",
@@ -213,32 +466,36 @@ pub async fn waitsendpay_response(
 
                     let last_hop = route.get(route.len() - 2).unwrap().id;
                     if err.message.contains("Too many HTLCs") {
-                        my_sleep(plugin.clone(), 3, task_ident).await;
+                        // A momentarily full HTLC slot count is the same kind of temporary,
+                        // self-healing condition as a disabled channel, so it gets the same
+                        // exponential backoff instead of a flat sleep that never escalates no
+                        // matter how often this channel is saturated.
+                        apply_temp_ban(&plugin, config, route.last().unwrap().channel);
                     } else if plugin.state().bad_fwd_nodes.lock().contains_key(&last_hop) {
                         log::debug!(
                             "{task_ident}: Last hop {last_hop} got temp banned because \
                             of bad forwarding"
                         );
                     } else {
-                        plugin.state().temp_chan_bans.lock().insert(
+                        handle_erring_channel(
+                            &plugin,
+                            config,
+                            ws_error.failcode,
                             route.last().unwrap().channel,
-                            SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
+                            ws_error.erring_node,
                         );
                     }
                 } else if ws_error.erring_channel == route.first().unwrap().channel {
                     log::warn!("{}: First peer has a problem {}", task_ident, err.message);
                     if err.message.contains("Too many HTLCs") {
-                        my_sleep(plugin.clone(), 3, task_ident).await;
+                        apply_temp_ban(&plugin, config, route.first().unwrap().channel);
                     } else {
-                        plugin.state().temp_chan_bans.lock().insert(
+                        handle_erring_channel(
+                            &plugin,
+                            config,
+                            ws_error.failcode,
                             route.first().unwrap().channel,
-                            SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
+                            ws_error.erring_node,
                         );
                     }
                 } else {
@@ -246,34 +503,24 @@ pub async fn waitsendpay_response(
                         short_channel_id: ws_error.erring_channel,
                         direction: u32::from(ws_error.erring_direction),
                     };
+                    let attempted_msat = ws_error.amount_msat.unwrap().msat();
+                    let new_max_msat = attempted_msat.saturating_sub(1);
                     log::debug!(
                         "{}: Adjusting liquidity for {} to constrain it to {}msat",
                         task_ident,
                         dir_chan,
-                        ws_error.amount_msat.unwrap().msat() / 2
+                        new_max_msat
+                    );
+
+                    lower_max_liquidity(&plugin, config, dir_chan, attempted_msat).await?;
+                    handle_erring_channel(
+                        &plugin,
+                        config,
+                        ws_error.failcode,
+                        ws_error.erring_channel,
+                        ws_error.erring_node,
                     );
 
-                    {
-                        let mut liquidity = plugin.state().liquidity.lock();
-                        if let Some(liq) = liquidity.get_mut(&dir_chan) {
-                            liq.liquidity_msat = ws_error.amount_msat.unwrap().msat() / 2;
-                            liq.liquidity_age = SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs();
-                        } else {
-                            liquidity.insert(
-                                dir_chan,
-                                Liquidity {
-                                    liquidity_msat: ws_error.amount_msat.unwrap().msat() / 2,
-                                    liquidity_age: SystemTime::now()
-                                        .duration_since(UNIX_EPOCH)
-                                        .unwrap()
-                                        .as_secs(),
-                                },
-                            );
-                        }
-                    }
                     if config.at_or_above_24_11 {
                         for lay in &config.inform_layers {
                             log::debug!(
@@ -281,12 +528,10 @@ pub async fn waitsendpay_response(
                                 task_ident,
                                 lay,
                                 dir_chan,
-                                ws_error.amount_msat.unwrap().msat() / 2
+                                new_max_msat
                             );
                             rpc.call_typed(&AskreneinformchannelRequest {
-                                amount_msat: Some(Amount::from_msat(
-                                    ws_error.amount_msat.unwrap().msat() / 2,
-                                )),
+                                amount_msat: Some(Amount::from_msat(new_max_msat)),
                                 inform: Some(AskreneinformchannelInform::CONSTRAINED),
                                 short_channel_id_dir: Some(dir_chan),
                                 layer: lay.clone(),
@@ -313,7 +558,8 @@ pub async fn sendpay_response(
     plugin: Plugin<PluginState>,
     config: &Config,
     payment_hash: Sha256,
-    pay_resolve_info: PayResolveInfo,
+    pay_resolve_info: Option<PayResolveInfo>,
+    partid_groupid: Option<(u64, u64)>,
     task_ident: &TaskIdentifier,
     job: &Job,
     route: &[SendpayRoute],
@@ -327,50 +573,70 @@ pub async fn sendpay_response(
             label: None,
             amount_msat: None,
             bolt11: None,
+            // No real invoice to carry a payment_secret for: sling is its own recipient, and
+            // `htlc_handler` reassembles an MPP rebalance by holding every part's HTLC on the
+            // shared `payment_hash` until enough of them land, rather than an external node
+            // matching parts via a BOLT11 payment_secret.
             payment_secret: None,
-            partid: None,
+            partid: partid_groupid.map(|(partid, _)| partid),
             localinvreqid: None,
-            groupid: None,
+            groupid: partid_groupid.map(|(_, groupid)| groupid),
             description: None,
             payment_metadata: None,
         })
         .await
     {
         Ok(resp) => {
-            plugin
-                .state()
-                .pays
-                .write()
-                .insert(payment_hash.to_string(), pay_resolve_info);
+            // MPP parts don't populate `pays`: the incoming HTLC is held/resolved via
+            // `plugin.state().mpp_pays` instead (registered by the caller before any part is
+            // sent), so inserting here too would let the first part's HTLC resolve on its own
+            // instead of waiting for the rest of the split to land.
+            if let Some(pri) = pay_resolve_info {
+                plugin
+                    .state()
+                    .pays
+                    .write()
+                    .insert(payment_hash.to_string(), pri);
+            }
             Ok(Some(resp))
         }
         Err(e) => {
             if e.to_string().contains("First peer not ready") {
                 log::info!("{task_ident}: First peer not ready, banning it for now...");
-                plugin.state().temp_chan_bans.lock().insert(
-                    route.first().unwrap().channel,
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                );
+                apply_temp_ban(&plugin, config, route.first().unwrap().channel);
                 *success_route = None;
+                let channel_partner = match job.sat_direction {
+                    SatDirection::Pull => route.first().unwrap().channel,
+                    SatDirection::Push => route.last().unwrap().channel,
+                };
+                let hops = u8::try_from(route.len() - 1)?;
+                let failure_node = route.first().unwrap().id;
                 FailureReb {
                     amount_msat: job.amount_msat,
-                    failure_reason: "FIRST_PEER_NOT_READY".to_string(),
-                    failure_node: route.first().unwrap().id,
-                    channel_partner: match job.sat_direction {
-                        SatDirection::Pull => route.first().unwrap().channel,
-                        SatDirection::Push => route.last().unwrap().channel,
-                    },
-                    hops: u8::try_from(route.len() - 1)?,
+                    failure_reason: FailureReason::Disconnected,
+                    failure_node,
+                    channel_partner,
+                    hops,
                     created_at: SystemTime::now()
                         .duration_since(UNIX_EPOCH)
                         .unwrap()
                         .as_secs(),
+                    task_id: task_ident.get_task_id(),
+                    sat_direction: job.sat_direction,
+                    route: route.iter().map(|hop| hop.channel).collect(),
                 }
                 .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
                 .await?;
+                notify_rebalance_outcome(
+                    &plugin,
+                    task_ident,
+                    channel_partner,
+                    job.amount_msat,
+                    0,
+                    hops,
+                    Some((&FailureReason::Disconnected, failure_node)),
+                )
+                .await;
                 return Ok(None);
             }
 
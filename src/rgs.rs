@@ -0,0 +1,365 @@
+//! Bootstraps/refreshes the public half of [`crate::model::LnGraph`] from a Rapid Gossip
+//! Sync (RGS) snapshot fetched over HTTP, instead of only ever tailing the node's local
+//! `gossip_store` (see [`crate::gossip::read_gossip_store`]). An RGS server pre-aggregates
+//! the full public network's gossip into one compact binary blob, so a freshly started node
+//! (or one with incomplete gossip) gets a usable routing graph in seconds instead of waiting
+//! for `gossip_store` to fill in on its own. See `sling-rgs-url`/`sling-rgs-interval-secs` and
+//! [`refresh_rgs`].
+//!
+//! # Wire format
+//! - `u8` version/flags byte (top 3 bits must be unset, mirroring `gossip_store`'s own check)
+//! - `[u8; 32]` chain hash
+//! - `u32` (BE) `latest_seen`, the snapshot's timestamp
+//! - node id table: `u32` (BE) count, then that many 33-byte compressed pubkeys
+//! - channel announcements: `u32` (BE) count, then that many records of two
+//!   [`BigSize`](read_bigsize)-encoded indices into the node id table plus a `u64` (BE) short
+//!   channel id, delta-encoded against the previous record's (the first record's is absolute)
+//! - one default-values header: `u16` `cltv_expiry_delta`, `u64` `htlc_minimum_msat`, `u32`
+//!   `fee_base_msat`, `u32` `fee_proportional_millionths`, `u64` `htlc_maximum_msat` (all BE)
+//! - channel updates: `u32` (BE) count, then that many records of a `u64` (BE) short channel id
+//!   (delta-encoded against the previous update record), a flag byte, and whichever of the five
+//!   fields above the flag byte marks present, in that fixed order. Flag bit 0 is the direction,
+//!   bit 1 marks the record as a delta against the default-values header (any field the record
+//!   doesn't carry is taken from the header instead), bits 2..=6 mark cltv/min/base_fee/ppm/max
+//!   present respectively. A non-delta record is expected to carry all five fields itself.
+//!
+//! Decoded announcements/updates are fed into [`ShortChannelIdDirStateBuilder::add_announcement`]/
+//! [`ShortChannelIdDirStateBuilder::add_update`] and [`crate::model::IncompleteChannels::update_graph`]
+//! exactly as `gossip::read_gossip_file_chunk` does for a local `gossip_store`, so a channel only
+//! needs an announcement and an update from *either* source before it's promoted into the graph.
+//!
+//! Full snapshots (requested with `last_sync_timestamp` 0) replace every public channel already
+//! in the graph; incremental snapshots only insert/update, leaving the rest of the graph alone.
+//! Either way the private-channel merge and `retain` pass in [`crate::tasks::refresh_graph`] runs
+//! unchanged afterwards, so locally known private channels are still layered on top.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use bitcoin::secp256k1::PublicKey;
+use cln_plugin::Plugin;
+use cln_rpc::primitives::{Amount, ShortChannelId, ShortChannelIdDir};
+use tokio::time::{self, Instant};
+
+use crate::{
+    gossip::{ChannelAnnouncement, ChannelUpdate},
+    model::{record_refresh_duration_ms, PluginState, ShortChannelIdDirStateBuilder},
+};
+
+const VERSION_AND_CHAIN_HASH_LEN: usize = 1 + 32;
+
+struct DefaultValues {
+    cltv_expiry_delta: u16,
+    htlc_minimum_msat: u64,
+    fee_base_msat: u32,
+    fee_proportional_millionths: u32,
+    htlc_maximum_msat: u64,
+}
+
+/// Periodically bootstraps/refreshes the public graph from `sling-rgs-url`
+/// (`sling-rgs-interval-secs`), requesting a full snapshot the first time and incremental ones
+/// afterwards keyed on the previous snapshot's `latest_seen`. A no-op loop if `sling-rgs-url`
+/// is unset.
+pub async fn refresh_rgs(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let mut last_sync_timestamp: u64 = 0;
+    loop {
+        let (url, interval) = {
+            let config = plugin.state().config.lock();
+            (config.rgs_url.clone(), config.rgs_interval_secs)
+        };
+        if !url.is_empty() {
+            let now = Instant::now();
+            match run_rgs_sync(&plugin, &url, last_sync_timestamp).await {
+                Ok(latest_seen) => {
+                    log::info!(
+                        "Applied RGS snapshot from `{url}` (since {last_sync_timestamp}) in {}ms!",
+                        now.elapsed().as_millis()
+                    );
+                    last_sync_timestamp = latest_seen;
+                    record_refresh_duration_ms(plugin.state(), "rgs", now.elapsed());
+                }
+                Err(e) => log::warn!("Error applying RGS snapshot from `{url}`: {e}"),
+            }
+        }
+        time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+async fn run_rgs_sync(
+    plugin: &Plugin<PluginState>,
+    base_url: &str,
+    last_sync_timestamp: u64,
+) -> Result<u64, Error> {
+    let url = format!("{}/{last_sync_timestamp}", base_url.trim_end_matches('/'));
+    let body = reqwest::get(&url).await?.bytes().await?;
+    apply_rgs_snapshot(plugin, &body, last_sync_timestamp)
+}
+
+fn apply_rgs_snapshot(
+    plugin: &Plugin<PluginState>,
+    data: &[u8],
+    last_sync_timestamp: u64,
+) -> Result<u64, Error> {
+    let mut offset = 0usize;
+
+    if data.len() < VERSION_AND_CHAIN_HASH_LEN + 4 {
+        return Err(anyhow!("RGS snapshot is too short ({} bytes)", data.len()));
+    }
+    if (data[0] & 0b1110_0000) != 0b0000_0000 {
+        return Err(anyhow!("unsupported RGS snapshot version"));
+    }
+    offset += VERSION_AND_CHAIN_HASH_LEN;
+
+    let latest_seen = read_u32(data, &mut offset)?;
+
+    let node_ids = read_node_ids(data, &mut offset)?;
+    let mut announcements = Vec::new();
+    read_announcements(data, &mut offset, &node_ids, &mut announcements)?;
+
+    let defaults = read_default_values(data, &mut offset)?;
+    let updates = read_updates(data, &mut offset, &defaults, latest_seen)?;
+
+    let is_full_snapshot = last_sync_timestamp == 0;
+    let mut graph = plugin.state().graph.lock();
+    let mut incomplete_channels = plugin.state().incomplete_channels.lock();
+
+    if is_full_snapshot {
+        graph.retain(|_, state| state.private);
+    }
+
+    for (scid, chan_ann) in &announcements {
+        for direction in [0u32, 1u32] {
+            let dir_chan = ShortChannelIdDir {
+                short_channel_id: *scid,
+                direction,
+            };
+            if !graph.has_announcement(&dir_chan, chan_ann)? {
+                if let Some(chan_state) = incomplete_channels.get_mut(&dir_chan) {
+                    if !chan_state.has_announcement() {
+                        chan_state.add_announcement(direction, *chan_ann)?;
+                    }
+                } else {
+                    let mut chan_state = ShortChannelIdDirStateBuilder::new();
+                    chan_state.add_announcement(direction, *chan_ann)?;
+                    incomplete_channels.insert(dir_chan, chan_state);
+                }
+            }
+        }
+    }
+
+    for (scid, direction, chan_up) in &updates {
+        let dir_chan = ShortChannelIdDir {
+            short_channel_id: *scid,
+            direction: *direction,
+        };
+        let mut updated = false;
+        if let Some(chan_state) = graph.get_state_mut_direction(dir_chan) {
+            chan_state.update(*chan_up);
+            updated = true;
+        }
+        if !updated {
+            if let Some(chan_state) = incomplete_channels.get_mut(&dir_chan) {
+                chan_state.add_update(*chan_up);
+            } else {
+                let mut chan_state = ShortChannelIdDirStateBuilder::new();
+                chan_state.add_update(*chan_up);
+                incomplete_channels.insert(dir_chan, chan_state);
+            }
+        }
+    }
+
+    let built = incomplete_channels.update_graph(&mut graph);
+
+    log::info!(
+        "rgs: applied {} announcements and {} updates, graph now has {} public channels",
+        announcements.len(),
+        updates.len(),
+        graph.public_channel_count()
+    );
+
+    if plugin.state().config.lock().verify_channel_funding && !built.is_empty() {
+        let mut pending = plugin.state().pending_funding_checks.lock();
+        for dir_chan in built {
+            pending.push_back(dir_chan.short_channel_id);
+        }
+    }
+
+    Ok(latest_seen)
+}
+
+fn read_u16(data: &[u8], offset: &mut usize) -> Result<u16, Error> {
+    let v = u16::from_be_bytes(
+        data.get(*offset..*offset + 2)
+            .ok_or_else(|| anyhow!("RGS snapshot truncated"))?
+            .try_into()?,
+    );
+    *offset += 2;
+    Ok(v)
+}
+
+fn read_u32(data: &[u8], offset: &mut usize) -> Result<u32, Error> {
+    let v = u32::from_be_bytes(
+        data.get(*offset..*offset + 4)
+            .ok_or_else(|| anyhow!("RGS snapshot truncated"))?
+            .try_into()?,
+    );
+    *offset += 4;
+    Ok(v)
+}
+
+fn read_u64(data: &[u8], offset: &mut usize) -> Result<u64, Error> {
+    let v = u64::from_be_bytes(
+        data.get(*offset..*offset + 8)
+            .ok_or_else(|| anyhow!("RGS snapshot truncated"))?
+            .try_into()?,
+    );
+    *offset += 8;
+    Ok(v)
+}
+
+fn read_u8(data: &[u8], offset: &mut usize) -> Result<u8, Error> {
+    let v = *data
+        .get(*offset)
+        .ok_or_else(|| anyhow!("RGS snapshot truncated"))?;
+    *offset += 1;
+    Ok(v)
+}
+
+/// BigSize, the same BOLT variable-length integer used elsewhere in the lightning wire
+/// protocol: values below `0xfd` are a single byte, `0xfd`/`0xfe`/`0xff` prefix a `u16`/`u32`/`u64`.
+fn read_bigsize(data: &[u8], offset: &mut usize) -> Result<u64, Error> {
+    match read_u8(data, offset)? {
+        0xfd => Ok(u64::from(read_u16(data, offset)?)),
+        0xfe => Ok(u64::from(read_u32(data, offset)?)),
+        0xff => read_u64(data, offset),
+        n => Ok(u64::from(n)),
+    }
+}
+
+fn read_node_ids(data: &[u8], offset: &mut usize) -> Result<Vec<PublicKey>, Error> {
+    let count = read_u32(data, offset)?;
+    let mut node_ids = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let bytes = data
+            .get(*offset..*offset + 33)
+            .ok_or_else(|| anyhow!("RGS snapshot truncated in node id table"))?;
+        *offset += 33;
+        node_ids.push(PublicKey::from_slice(bytes)?);
+    }
+    Ok(node_ids)
+}
+
+fn read_announcements(
+    data: &[u8],
+    offset: &mut usize,
+    node_ids: &[PublicKey],
+    announcements: &mut Vec<(ShortChannelId, ChannelAnnouncement)>,
+) -> Result<(), Error> {
+    let count = read_u32(data, offset)?;
+    let mut prev_scid: u64 = 0;
+    for _ in 0..count {
+        let node_a = read_bigsize(data, offset)? as usize;
+        let node_b = read_bigsize(data, offset)? as usize;
+        let scid_delta = read_u64(data, offset)?;
+        prev_scid += scid_delta;
+        let source = *node_ids
+            .get(node_a)
+            .ok_or_else(|| anyhow!("RGS announcement references unknown node index {node_a}"))?;
+        let destination = *node_ids
+            .get(node_b)
+            .ok_or_else(|| anyhow!("RGS announcement references unknown node index {node_b}"))?;
+        announcements.push((
+            ShortChannelId::from(prev_scid),
+            ChannelAnnouncement { source, destination },
+        ));
+    }
+    Ok(())
+}
+
+fn read_default_values(data: &[u8], offset: &mut usize) -> Result<DefaultValues, Error> {
+    Ok(DefaultValues {
+        cltv_expiry_delta: read_u16(data, offset)?,
+        htlc_minimum_msat: read_u64(data, offset)?,
+        fee_base_msat: read_u32(data, offset)?,
+        fee_proportional_millionths: read_u32(data, offset)?,
+        htlc_maximum_msat: read_u64(data, offset)?,
+    })
+}
+
+const FLAG_DIRECTION: u8 = 0b0000_0001;
+const FLAG_IS_DELTA: u8 = 0b0000_0010;
+const FLAG_HAS_CLTV_EXPIRY_DELTA: u8 = 0b0000_0100;
+const FLAG_HAS_HTLC_MINIMUM_MSAT: u8 = 0b0000_1000;
+const FLAG_HAS_FEE_BASE_MSAT: u8 = 0b0001_0000;
+const FLAG_HAS_FEE_PROPORTIONAL_MILLIONTHS: u8 = 0b0010_0000;
+const FLAG_HAS_HTLC_MAXIMUM_MSAT: u8 = 0b0100_0000;
+
+fn read_updates(
+    data: &[u8],
+    offset: &mut usize,
+    defaults: &DefaultValues,
+    latest_seen: u32,
+) -> Result<Vec<(ShortChannelId, u32, ChannelUpdate)>, Error> {
+    let count = read_u32(data, offset)?;
+    let mut updates = Vec::with_capacity(count as usize);
+    let mut prev_scid: u64 = 0;
+    for _ in 0..count {
+        let scid_delta = read_u64(data, offset)?;
+        prev_scid += scid_delta;
+        let flags = read_u8(data, offset)?;
+        let is_delta = flags & FLAG_IS_DELTA != 0;
+
+        let cltv_expiry_delta = if flags & FLAG_HAS_CLTV_EXPIRY_DELTA != 0 {
+            read_u16(data, offset)?
+        } else if is_delta {
+            defaults.cltv_expiry_delta
+        } else {
+            return Err(anyhow!("non-delta RGS update record is missing cltv_expiry_delta"));
+        };
+        let htlc_minimum_msat = if flags & FLAG_HAS_HTLC_MINIMUM_MSAT != 0 {
+            read_u64(data, offset)?
+        } else if is_delta {
+            defaults.htlc_minimum_msat
+        } else {
+            return Err(anyhow!("non-delta RGS update record is missing htlc_minimum_msat"));
+        };
+        let fee_base_msat = if flags & FLAG_HAS_FEE_BASE_MSAT != 0 {
+            read_u32(data, offset)?
+        } else if is_delta {
+            defaults.fee_base_msat
+        } else {
+            return Err(anyhow!("non-delta RGS update record is missing fee_base_msat"));
+        };
+        let fee_proportional_millionths = if flags & FLAG_HAS_FEE_PROPORTIONAL_MILLIONTHS != 0 {
+            read_u32(data, offset)?
+        } else if is_delta {
+            defaults.fee_proportional_millionths
+        } else {
+            return Err(anyhow!(
+                "non-delta RGS update record is missing fee_proportional_millionths"
+            ));
+        };
+        let htlc_maximum_msat = if flags & FLAG_HAS_HTLC_MAXIMUM_MSAT != 0 {
+            read_u64(data, offset)?
+        } else if is_delta {
+            defaults.htlc_maximum_msat
+        } else {
+            return Err(anyhow!("non-delta RGS update record is missing htlc_maximum_msat"));
+        };
+
+        updates.push((
+            ShortChannelId::from(prev_scid),
+            u32::from(flags & FLAG_DIRECTION),
+            ChannelUpdate {
+                active: true,
+                last_update: latest_seen,
+                base_fee_millisatoshi: fee_base_msat,
+                fee_per_millionth: fee_proportional_millionths,
+                delay: u32::from(cltv_expiry_delta),
+                htlc_minimum_msat: Amount::from_msat(htlc_minimum_msat),
+                htlc_maximum_msat: Amount::from_msat(htlc_maximum_msat),
+            },
+        ));
+    }
+    Ok(updates)
+}
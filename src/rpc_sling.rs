@@ -1,28 +1,134 @@
 use std::{
-    cmp::Ordering, collections::BTreeMap, path::Path, str::FromStr, sync::Arc, time::Duration,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
 use anyhow::anyhow;
-use bitcoin::secp256k1::PublicKey;
 use cln_plugin::{Error, Plugin};
-use cln_rpc::primitives::ShortChannelId;
+use cln_rpc::{
+    model::responses::ListpeerchannelsChannels,
+    primitives::{PublicKey, ShortChannelId},
+};
 use parking_lot::Mutex;
 use serde_json::json;
-use sling::Job;
+use sling::{ExceptChan, ExceptDirection, Job, SatDirection};
 use tokio::{fs, time};
 
 use crate::{
+    config::{current_numeric_value, OPTION_SPECS},
     get_normal_channel_from_listpeerchannels,
     model::{PubKeyBytes, TaskIdentifier},
+    notifications::notify_rebalance,
     parse::{parse_job, parse_once_job},
     read_jobs,
     slings::sling,
     tasks::refresh_listpeerchannels,
-    util::{read_except_chans, read_except_peers, write_liquidity},
-    write_excepts, write_job, JobMessage, PluginState, Task, EXCEPTS_CHANS_FILE_NAME,
-    EXCEPTS_PEERS_FILE_NAME, JOB_FILE_NAME, PLUGIN_NAME,
+    util::{
+        now_secs, read_except_chans, read_except_peers, write_except_chans, write_except_peers,
+        write_liquidity,
+    },
+    write_job, JobMessage, PluginState, Task, JOB_FILE_NAME, PLUGIN_NAME,
 };
 
+/// Resolves the scid-selector argument shared by [`slinggo`] and `stop_job`: a single scid
+/// string, a JSON array of scid strings, an object with `scid`/`scids`, or a filter object
+/// matching against the currently loaded `jobs` (`{"direction":"push"|"pull"}` or
+/// `{"peer_alias":"..."}`). Returns `None` for "nothing given", meaning the caller's own
+/// apply-to-everything behavior should kick in; otherwise `Some` of the selected scids (an
+/// explicit `scids: []` yields an empty list). A `direction`/`peer_alias` filter that matches
+/// nothing is an error rather than a silent fallback to everything.
+fn resolve_job_selector(
+    args: &serde_json::Value,
+    jobs: &BTreeMap<ShortChannelId, Job>,
+    peer_channels: &HashMap<ShortChannelId, ListpeerchannelsChannels>,
+    alias_map: &HashMap<PublicKey, String>,
+) -> Result<Option<Vec<ShortChannelId>>, Error> {
+    match args {
+        serde_json::Value::Array(a) => {
+            if a.is_empty() {
+                return Ok(None);
+            }
+            let mut scids = Vec::with_capacity(a.len());
+            for v in a {
+                match v {
+                    serde_json::Value::String(s) => scids.push(ShortChannelId::from_str(s)?),
+                    o => return Err(anyhow!("not a valid ShortChannelId: {}", o)),
+                }
+            }
+            Ok(Some(scids))
+        }
+        serde_json::Value::Object(o) => {
+            if o.is_empty() {
+                return Ok(None);
+            }
+            if let Some(v) = o.get("scid") {
+                return match v {
+                    serde_json::Value::String(s) => Ok(Some(vec![ShortChannelId::from_str(s)?])),
+                    _ => Err(anyhow!("invalid scid")),
+                };
+            }
+            if let Some(v) = o.get("scids") {
+                return match v {
+                    serde_json::Value::Array(a) => {
+                        let mut scids = Vec::with_capacity(a.len());
+                        for v in a {
+                            match v {
+                                serde_json::Value::String(s) => {
+                                    scids.push(ShortChannelId::from_str(s)?)
+                                }
+                                o => return Err(anyhow!("not a valid ShortChannelId: {}", o)),
+                            }
+                        }
+                        Ok(Some(scids))
+                    }
+                    _ => Err(anyhow!("`scids` must be an array of ShortChannelIds")),
+                };
+            }
+            if let Some(serde_json::Value::String(dir)) = o.get("direction") {
+                let direction = SatDirection::from_str(dir)?;
+                let scids: Vec<ShortChannelId> = jobs
+                    .iter()
+                    .filter(|(_, job)| job.sat_direction == direction)
+                    .map(|(scid, _)| *scid)
+                    .collect();
+                return if scids.is_empty() {
+                    Err(anyhow!("no jobs found with direction `{}`", direction))
+                } else {
+                    Ok(Some(scids))
+                };
+            }
+            if let Some(serde_json::Value::String(alias)) = o.get("peer_alias") {
+                let scids: Vec<ShortChannelId> = jobs
+                    .keys()
+                    .filter(|scid| {
+                        peer_channels
+                            .get(scid)
+                            .and_then(|c| alias_map.get(&c.peer_id))
+                            .is_some_and(|a| a == alias)
+                    })
+                    .copied()
+                    .collect();
+                return if scids.is_empty() {
+                    Err(anyhow!("no jobs found for peer alias `{}`", alias))
+                } else {
+                    Ok(Some(scids))
+                };
+            }
+            Err(anyhow!(
+                "expected object with `scid`, `scids`, `direction`, or `peer_alias`"
+            ))
+        }
+        e => Err(anyhow!(
+            "invalid arguments, expected array of ShortChannelIds or a filter object, got: {}",
+            e
+        )),
+    }
+}
+
 pub async fn slingjob(
     plugin: Plugin<PluginState>,
     args: serde_json::Value,
@@ -77,45 +183,16 @@ pub async fn slinggo(
 
     let config = plugin.state().config.lock().clone();
 
-    match args {
-        serde_json::Value::Array(a) => match a.len().cmp(&(1_usize)) {
-            Ordering::Greater => {
-                return Err(anyhow!(
-                    "Please provide exactly one ShortChannelId or nothing"
-                ))
-            }
-            Ordering::Equal => match a.first().unwrap() {
-                serde_json::Value::String(start_id) => {
-                    let scid = ShortChannelId::from_str(start_id)?;
-                    jobs.retain(|chanid, _j| chanid == &scid)
-                }
-                _ => return Err(anyhow!("invalid ShortChannelId")),
-            },
-            Ordering::Less => (),
-        },
-        serde_json::Value::Object(o) => {
-            if let Some(serde_json::Value::String(start_id)) = o.get("scid") {
-                let scid = ShortChannelId::from_str(start_id.as_str())?;
-                jobs.retain(|chanid, _j| chanid == &scid)
-            } else if o.is_empty() {
-            } else {
-                return Err(anyhow!("invalid scid"));
-            }
-        }
-        e => {
-            return Err(anyhow!(
-                "sling-go: invalid arguments, expected array or object with `scid`, got: {}",
-                e
-            ))
-        }
-    }
-
-    if jobs.is_empty() {
-        return Err(anyhow!("Shortchannelid not found in jobs"));
-    }
-
     let _res = refresh_listpeerchannels(plugin.clone()).await;
     let peer_channels = plugin.state().peer_channels.lock().clone();
+    let alias_map = plugin.state().alias_peer_map.lock().clone();
+
+    if let Some(selected) = resolve_job_selector(&args, &jobs, &peer_channels, &alias_map)? {
+        jobs.retain(|chan_id, _| selected.contains(chan_id));
+        if jobs.is_empty() {
+            return Err(anyhow!("Shortchannelid not found in jobs"));
+        }
+    }
 
     for (chan_id, job) in jobs {
         let other_peer = PubKeyBytes::from_pubkey(
@@ -150,6 +227,7 @@ pub async fn slinggo(
                 if task.is_none() || !task.as_ref().unwrap().is_active() {
                     let plugin = plugin.clone();
                     let job_clone = job.clone();
+                    let config_clone = config.clone();
                     spawn_count += 1;
                     log::debug!("{chan_id}/{i}: Spawning job.");
                     match task {
@@ -181,9 +259,23 @@ pub async fn slinggo(
                                 let mut tasks = plugin.state().tasks.lock();
                                 let task = tasks.get_task_mut(&task_ident);
                                 if let Some(t) = task {
-                                    t.set_state(JobMessage::Error);
-                                    t.set_active(false);
+                                    t.record_exit_failure(
+                                        e.to_string(),
+                                        config_clone.job_retry_base_secs,
+                                        config_clone.job_retry_max_secs,
+                                        config_clone.job_retry_max_attempts,
+                                    );
                                 }
+                                drop(tasks);
+                                notify_rebalance(
+                                    &plugin,
+                                    &task_ident,
+                                    job_clone.sat_direction,
+                                    JobMessage::Error,
+                                    None,
+                                    None,
+                                )
+                                .await;
                             }
                         };
                     });
@@ -209,60 +301,41 @@ pub async fn slingstop(
 
 async fn stop_job(plugin: Plugin<PluginState>, args: serde_json::Value) -> Result<usize, Error> {
     let mut stopped_count: usize = 0;
-    let mut scid = None;
 
-    match args {
-        serde_json::Value::Array(a) => match a.len().cmp(&(1_usize)) {
-            Ordering::Greater => {
-                return Err(anyhow!(
-                    "Please provide exactly one ShortChannelId or nothing"
-                ))
-            }
-            Ordering::Equal => match a.first().unwrap() {
-                serde_json::Value::String(stop_id) => {
-                    scid = Some(ShortChannelId::from_str(stop_id)?);
-                }
-                _ => return Err(anyhow!("invalid ShortChannelId")),
-            },
-            Ordering::Less => {}
-        },
-        serde_json::Value::Object(o) => match o.get("scid") {
-            Some(serde_json::Value::String(stop_id)) => {
-                scid = Some(ShortChannelId::from_str(stop_id)?);
-            }
-            None => {}
-            _ => return Err(anyhow!("invalid scid")),
-        },
-        e => {
-            return Err(anyhow!(
-                "sling-stop: invalid arguments, expected array or object with `scid`, got: {}",
-                e
-            ))
-        }
-    };
+    let jobs = read_jobs(
+        &Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME),
+        plugin.clone(),
+    )
+    .await?;
+    let peer_channels = plugin.state().peer_channels.lock().clone();
+    let alias_map = plugin.state().alias_peer_map.lock().clone();
+    let selected = resolve_job_selector(&args, &jobs, &peer_channels, &alias_map)?;
+
     {
-        if let Some(s) = scid {
-            {
-                let mut tasks = plugin.state().tasks.lock();
-                let task_map = tasks.get_scid_tasks_mut(&s);
-                if let Some(tm) = task_map {
-                    stopped_count += tm.len();
-                    for task in tm.values_mut() {
-                        task.set_state(JobMessage::Stopping);
-                        task.stop();
-                        log::debug!("{}: Stopping job...", task.get_identifier());
+        if let Some(scids) = selected {
+            for s in scids {
+                {
+                    let mut tasks = plugin.state().tasks.lock();
+                    let task_map = tasks.get_scid_tasks_mut(&s);
+                    if let Some(tm) = task_map {
+                        stopped_count += tm.len();
+                        for task in tm.values_mut() {
+                            task.set_state(JobMessage::Stopping);
+                            task.stop();
+                            log::debug!("{}: Stopping job...", task.get_identifier());
+                        }
                     }
                 }
-            }
-            loop {
-                {
-                    let tasks = plugin.state().tasks.lock();
-                    if !tasks.is_any_active(&s) {
-                        break;
+                loop {
+                    {
+                        let tasks = plugin.state().tasks.lock();
+                        if !tasks.is_any_active(&s) {
+                            break;
+                        }
                     }
+                    log::trace!("Waiting for task to stop...");
+                    time::sleep(Duration::from_millis(200)).await;
                 }
-                log::trace!("Waiting for task to stop...");
-                time::sleep(Duration::from_millis(200)).await;
             }
         } else {
             let mut stopped_ids = Vec::new();
@@ -303,6 +376,77 @@ async fn stop_job(plugin: Plugin<PluginState>, args: serde_json::Value) -> Resul
     Ok(stopped_count)
 }
 
+pub async fn slingpause(
+    plugin: Plugin<PluginState>,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let _rpc_lock = plugin.state().rpc_lock.lock().await;
+    let paused_count = set_paused_job(plugin.clone(), args, true).await?;
+    Ok(json!({ "paused_count": paused_count }))
+}
+
+pub async fn slingresume(
+    plugin: Plugin<PluginState>,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let _rpc_lock = plugin.state().rpc_lock.lock().await;
+    let resumed_count = set_paused_job(plugin.clone(), args, false).await?;
+    Ok(json!({ "resumed_count": resumed_count }))
+}
+
+async fn set_paused_job(
+    plugin: Plugin<PluginState>,
+    args: serde_json::Value,
+    paused: bool,
+) -> Result<usize, Error> {
+    let mut scids = Vec::new();
+
+    match args {
+        serde_json::Value::Array(a) => match a.len().cmp(&(1_usize)) {
+            Ordering::Greater => {
+                return Err(anyhow!(
+                    "Please provide exactly one ShortChannelId or nothing"
+                ))
+            }
+            Ordering::Equal => match a.first().unwrap() {
+                serde_json::Value::String(scid) => {
+                    scids.push(ShortChannelId::from_str(scid)?);
+                }
+                _ => return Err(anyhow!("invalid ShortChannelId")),
+            },
+            Ordering::Less => {}
+        },
+        serde_json::Value::Object(o) => match o.get("scid") {
+            Some(serde_json::Value::String(scid)) => {
+                scids.push(ShortChannelId::from_str(scid)?);
+            }
+            None => {}
+            _ => return Err(anyhow!("invalid scid")),
+        },
+        e => {
+            return Err(anyhow!(
+                "sling-pause/sling-resume: invalid arguments, expected array or object with \
+                `scid`, got: {}",
+                e
+            ))
+        }
+    };
+
+    let mut tasks = plugin.state().tasks.lock();
+    if scids.is_empty() {
+        scids = tasks.get_all_tasks().keys().copied().collect();
+    }
+    let mut count = 0;
+    for scid in scids {
+        count += tasks.set_paused(&scid, paused);
+        log::debug!(
+            "{scid}: {} job(s)",
+            if paused { "Pausing" } else { "Resuming" }
+        );
+    }
+    Ok(count)
+}
+
 pub async fn slingonce(
     plugin: Plugin<PluginState>,
     args: serde_json::Value,
@@ -444,6 +588,18 @@ pub async fn slingonce(
                                     config.exclude_chans_push.remove(&chan_id)
                                 }
                             };
+                            drop(config);
+                            drop(total_rebalanced);
+                            drop(tasks);
+                            notify_rebalance(
+                                &plugin,
+                                &task_ident,
+                                job.sat_direction,
+                                JobMessage::Stopped,
+                                None,
+                                None,
+                            )
+                            .await;
                             break;
                         }
                         if *total_rebalanced + job.amount_msat > job.onceamount_msat.unwrap() {
@@ -459,6 +615,19 @@ pub async fn slingonce(
                                     config.exclude_chans_push.remove(&chan_id)
                                 }
                             };
+                            let total_rebalanced_msat = *total_rebalanced;
+                            drop(config);
+                            drop(total_rebalanced);
+                            drop(tasks);
+                            notify_rebalance(
+                                &plugin,
+                                &task_ident,
+                                job.sat_direction,
+                                JobMessage::Balanced,
+                                Some(total_rebalanced_msat),
+                                None,
+                            )
+                            .await;
                             break;
                         } else {
                             *total_rebalanced += job.amount_msat;
@@ -475,8 +644,18 @@ pub async fn slingonce(
                             log::warn!("{chan_id}/{e}: Error in once-job: {i}");
                             let mut tasks = plugin.state().tasks.lock();
                             let task = tasks.get_task_mut(&task_ident).unwrap();
-                            task.set_state(JobMessage::Error);
+                            task.set_error(e.to_string());
+                            drop(tasks);
                             *total_rebalanced.lock() -= job.amount_msat;
+                            notify_rebalance(
+                                &plugin,
+                                &task_ident,
+                                job.sat_direction,
+                                JobMessage::Error,
+                                None,
+                                None,
+                            )
+                            .await;
                         }
                     };
 
@@ -547,6 +726,78 @@ pub async fn slingjobsettings(
     Ok(json!(json_jobs))
 }
 
+pub async fn slingstatus(
+    plugin: Plugin<PluginState>,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let mut scid_filter: Option<ShortChannelId> = None;
+    match args {
+        serde_json::Value::Array(a) => {
+            if a.len() > 1 {
+                return Err(anyhow!(
+                    "Please provide exactly one ShortChannelId or nothing for all"
+                ));
+            }
+            if let Some(v) = a.first() {
+                let scid_str = v.as_str().ok_or(anyhow!("invalid input, not a string"))?;
+                scid_filter = Some(ShortChannelId::from_str(scid_str)?);
+            }
+        }
+        serde_json::Value::Object(o) => {
+            if o.len() > 1 {
+                return Err(anyhow!(
+                    "Please provide exactly one ShortChannelId or nothing for all"
+                ));
+            }
+            if let Some(s) = o.get("scid") {
+                let scid_str = s.as_str().ok_or(anyhow!("invalid scid, not a string"))?;
+                scid_filter = Some(ShortChannelId::from_str(scid_str)?);
+            } else if !o.is_empty() {
+                return Err(anyhow!("Expected object with scid field"));
+            }
+        }
+        _ => {
+            return Err(anyhow!(
+                "Invalid: Please provide exactly one ShortChannelId or nothing for all"
+            ))
+        }
+    };
+
+    let tranquility = plugin.state().config.lock().tranquility;
+    let tasks = plugin.state().tasks.lock();
+    let mut status = BTreeMap::new();
+    for (scid, scid_tasks) in tasks.get_all_tasks() {
+        if scid_filter.is_some_and(|f| f != *scid) {
+            continue;
+        }
+        let mut task_ids: Vec<&u16> = scid_tasks.keys().collect();
+        task_ids.sort();
+        let mut task_status = BTreeMap::new();
+        for task_id in task_ids {
+            let task = &scid_tasks[task_id];
+            let effective_delay_ms = task
+                .get_last_attempt_ms()
+                .map(|ms| (tranquility * ms as f64) as u64);
+            task_status.insert(
+                task_id.to_string(),
+                json!({
+                    "state": task.get_state().to_string(),
+                    "active": task.is_active(),
+                    "once": task.is_once(),
+                    "state_age_secs": task.state_age_secs(),
+                    "last_error": task.get_last_error(),
+                    "effective_delay_ms": effective_delay_ms,
+                    "attempt_count": task.get_attempt_count(),
+                    "next_retry_at": task.get_next_retry_at(),
+                }),
+            );
+        }
+        status.insert(scid.to_string(), task_status);
+    }
+
+    Ok(json!(status))
+}
+
 pub async fn slingdeletejob(
     plugin: Plugin<PluginState>,
     args: serde_json::Value,
@@ -594,9 +845,27 @@ pub async fn slingdeletejob(
             plugin.state().tasks.lock().remove_all_tasks();
             log::info!("Deleted all jobs");
             let except_chans = read_except_chans(&sling_dir).await?;
+            let now = now_secs();
             let mut config = plugin.state().config.lock();
-            config.exclude_chans_pull = except_chans.clone();
-            config.exclude_chans_push = except_chans;
+            config.exclude_chans_pull.clear();
+            config.exclude_chans_push.clear();
+            for (scid, except) in except_chans {
+                if except.is_expired(now) {
+                    continue;
+                }
+                match except.direction {
+                    ExceptDirection::Pull => {
+                        config.exclude_chans_pull.insert(scid);
+                    }
+                    ExceptDirection::Push => {
+                        config.exclude_chans_push.insert(scid);
+                    }
+                    ExceptDirection::Both => {
+                        config.exclude_chans_pull.insert(scid);
+                        config.exclude_chans_push.insert(scid);
+                    }
+                }
+            }
         }
         _ => {
             let scid = ShortChannelId::from_str(&input)?;
@@ -616,18 +885,98 @@ pub async fn slingdeletejob(
     Ok(json!({ "result": "success" }))
 }
 
+fn parse_scid_list(
+    single: Option<&serde_json::Value>,
+    multi: Option<&serde_json::Value>,
+) -> Result<Vec<ShortChannelId>, Error> {
+    let mut scids = Vec::new();
+    match single {
+        Some(serde_json::Value::String(s)) => scids.push(ShortChannelId::from_str(s)?),
+        Some(serde_json::Value::Array(a)) => {
+            for v in a {
+                match v {
+                    serde_json::Value::String(s) => scids.push(ShortChannelId::from_str(s)?),
+                    o => return Err(anyhow!("not a valid string: {}", o)),
+                }
+            }
+        }
+        Some(o) => return Err(anyhow!("not a valid string or array for `scid`: {}", o)),
+        None => {}
+    }
+    match multi {
+        Some(serde_json::Value::Array(a)) => {
+            for v in a {
+                match v {
+                    serde_json::Value::String(s) => scids.push(ShortChannelId::from_str(s)?),
+                    o => return Err(anyhow!("not a valid string: {}", o)),
+                }
+            }
+        }
+        Some(o) => return Err(anyhow!("not a valid array for `scids`: {}", o)),
+        None => {}
+    }
+    Ok(scids)
+}
+
+/// Resolves the `expires_in`/`expires_at` keyword args of an except-add command into an
+/// absolute unix timestamp. `None` if neither was given, meaning the exception is permanent.
+fn resolve_expiry(o: &serde_json::Map<String, serde_json::Value>) -> Result<Option<u64>, Error> {
+    let expires_in = match o.get("expires_in") {
+        Some(v) => Some(
+            v.as_u64()
+                .ok_or_else(|| anyhow!("`expires_in` must be a positive number of seconds"))?,
+        ),
+        None => None,
+    };
+    let expires_at = match o.get("expires_at") {
+        Some(v) => Some(
+            v.as_u64()
+                .ok_or_else(|| anyhow!("`expires_at` must be a unix timestamp"))?,
+        ),
+        None => None,
+    };
+    match (expires_in, expires_at) {
+        (Some(_), Some(_)) => Err(anyhow!(
+            "specify either `expires_in` or `expires_at`, not both"
+        )),
+        (Some(secs), None) => Ok(Some(now_secs() + secs)),
+        (None, Some(ts)) => Ok(Some(ts)),
+        (None, None) => Ok(None),
+    }
+}
+
+fn except_chans_list_json(excepts: &BTreeMap<ShortChannelId, ExceptChan>) -> serde_json::Value {
+    let now = now_secs();
+    let out: BTreeMap<String, serde_json::Value> = excepts
+        .iter()
+        .map(|(scid, e)| {
+            let remaining = e.expires_at.map(|exp| exp.saturating_sub(now));
+            (
+                scid.to_string(),
+                json!({
+                    "direction": e.direction,
+                    "expires_at": e.expires_at,
+                    "expires_in_secs": remaining,
+                }),
+            )
+        })
+        .collect();
+    json!(out)
+}
+
 pub async fn slingexceptchan(
     plugin: Plugin<PluginState>,
     args: serde_json::Value,
 ) -> Result<serde_json::Value, Error> {
     let _rpc_lock = plugin.state().rpc_lock.lock().await;
 
-    let (command, scid) = match args {
+    let (command, scids, direction, expires_at) = match args {
         serde_json::Value::Array(a) => {
-            if a.len() > 2 || a.is_empty() {
+            if a.len() > 3 || a.is_empty() {
                 return Err(anyhow!(
-                    "Invalid amount of arguments. Please either provide `add`/`remove` \
-                    and a ShortChannelId or just `list`"
+                    "Invalid amount of arguments. Please either provide `add`/`remove`, \
+                    a ShortChannelId (or array of them) and optionally a `pull`/`push`/`both` \
+                    direction, or just `list`"
                 ));
             }
             let command = match a.first().unwrap() {
@@ -639,17 +988,18 @@ pub async fn slingexceptchan(
                 }
             };
             if command == "list" && a.len() == 1 {
-                (command, None)
-            } else if a.len() == 2 {
-                let scid = match a.get(1).unwrap() {
-                    serde_json::Value::String(s) => ShortChannelId::from_str(s)?,
-                    o => return Err(anyhow!("not a vaild string: {}", o)),
+                (command, Vec::new(), ExceptDirection::Both, None)
+            } else if a.len() >= 2 {
+                let direction = match a.get(2) {
+                    Some(serde_json::Value::String(s)) => ExceptDirection::from_str(s)?,
+                    Some(o) => return Err(anyhow!("not a valid string for `direction`: {}", o)),
+                    None => ExceptDirection::Both,
                 };
-                (command, Some(scid))
+                (command, parse_scid_list(a.get(1), None)?, direction, None)
             } else {
                 return Err(anyhow!(
                     "Invalid amount of arguments. Please either provide `add`/`remove` \
-                    and a ShortChannelId or just `list`"
+                    and a ShortChannelId (or array of them) or just `list`"
                 ));
             }
         }
@@ -662,11 +1012,18 @@ pub async fn slingexceptchan(
                     ))
                 }
             };
-            match o.get("scid") {
-                Some(serde_json::Value::String(s)) => (command, Some(ShortChannelId::from_str(s)?)),
-                None => (command, None),
-                o => return Err(anyhow!("not a vaild string for `scid`: {:?}", o)),
-            }
+            let direction = match o.get("direction") {
+                Some(serde_json::Value::String(s)) => ExceptDirection::from_str(s)?,
+                Some(o) => return Err(anyhow!("not a valid string for `direction`: {}", o)),
+                None => ExceptDirection::Both,
+            };
+            let expires_at = resolve_expiry(&o)?;
+            (
+                command,
+                parse_scid_list(o.get("scid"), o.get("scids"))?,
+                direction,
+                expires_at,
+            )
         }
         e => {
             return Err(anyhow!(
@@ -679,48 +1036,115 @@ pub async fn slingexceptchan(
     let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
     let peer_channels = plugin.state().peer_channels.lock().clone();
     let mut static_excepts = read_except_chans(&sling_dir).await?;
-    if let Some(s) = scid {
-        {
-            let jobs = read_jobs(&sling_dir, plugin.clone()).await?;
-            let mut config = plugin.state().config.lock();
+    if scids.is_empty() {
+        return match command {
+            opt if opt.eq("list") => Ok(except_chans_list_json(&static_excepts)),
+            _ => Err(anyhow!(
+                "unknown commmand, did you misspell `list` or forgot the scid?"
+            )),
+        };
+    }
 
-            let mut contains = false;
-            if config.exclude_chans_pull.contains(&s) || config.exclude_chans_push.contains(&s) {
-                contains = true;
-            }
+    let mut changed: Vec<String> = Vec::new();
+    let mut skipped: Vec<serde_json::Value> = Vec::new();
+    {
+        let jobs = read_jobs(&sling_dir, plugin.clone()).await?;
+        let mut config = plugin.state().config.lock();
 
-            match command {
-                opt if opt.eq("add") => {
-                    if contains {
-                        return Err(anyhow!("{} is already in excepts", s));
-                    }
-                    if jobs.contains_key(&s) {
-                        return Err(anyhow!(
-                            "this channel has a job already and can't be an except too"
-                        ));
-                    }
-                    if peer_channels.contains_key(&s) {
-                        return Err(anyhow!(
-                            "You can't except your own channels. Use the candidate list of a \
-                            job to restrict those."
-                        ));
-                    }
-                    config.exclude_chans_pull.insert(s);
-                    config.exclude_chans_push.insert(s);
-                    static_excepts.insert(s);
-                }
-                opt if opt.eq("remove") => {
-                    if contains {
-                        config.exclude_chans_pull.remove(&s);
-                        config.exclude_chans_push.remove(&s);
-                        static_excepts.remove(&s);
+        for s in scids {
+            let existing = static_excepts.get(&s).copied();
+
+            match command.as_str() {
+                "add" => {
+                    let already_set = existing
+                        .is_some_and(|e| e.direction == direction || e.direction == ExceptDirection::Both);
+                    if already_set && expires_at.is_none() {
+                        skipped.push(json!({"scid": s.to_string(), "reason": "already in excepts"}));
+                    } else if jobs.contains_key(&s) {
+                        skipped.push(json!({"scid": s.to_string(), "reason": "this channel has a job already and can't be an except too"}));
+                    } else if peer_channels.contains_key(&s) {
+                        skipped.push(json!({"scid": s.to_string(), "reason": "can't except your own channels, use the candidate list of a job to restrict those"}));
                     } else {
-                        return Err(anyhow!(
-                            "ShortChannelId {} not in excepts, nothing to remove",
-                            s
-                        ));
+                        let merged_direction = match existing {
+                            Some(e) if e.direction == direction => e.direction,
+                            Some(_) => ExceptDirection::Both,
+                            None => direction,
+                        };
+                        let merged_expiry = expires_at.or(existing.and_then(|e| e.expires_at));
+                        match merged_direction {
+                            ExceptDirection::Pull => {
+                                config.exclude_chans_pull.insert(s);
+                            }
+                            ExceptDirection::Push => {
+                                config.exclude_chans_push.insert(s);
+                            }
+                            ExceptDirection::Both => {
+                                config.exclude_chans_pull.insert(s);
+                                config.exclude_chans_push.insert(s);
+                            }
+                        }
+                        static_excepts.insert(
+                            s,
+                            ExceptChan {
+                                direction: merged_direction,
+                                expires_at: merged_expiry,
+                            },
+                        );
+                        changed.push(s.to_string());
                     }
                 }
+                "remove" => match existing {
+                    None => {
+                        skipped.push(
+                            json!({"scid": s.to_string(), "reason": "not in excepts, nothing to remove"}),
+                        );
+                    }
+                    Some(e) => {
+                        let remaining = match (e.direction, direction) {
+                            (_, ExceptDirection::Both) => None,
+                            (ExceptDirection::Both, ExceptDirection::Pull) => {
+                                Some(ExceptDirection::Push)
+                            }
+                            (ExceptDirection::Both, ExceptDirection::Push) => {
+                                Some(ExceptDirection::Pull)
+                            }
+                            (cur, req) if cur == req => None,
+                            _ => {
+                                skipped.push(json!({"scid": s.to_string(), "reason": format!("not excepted for `{}`, nothing to remove", direction)}));
+                                continue;
+                            }
+                        };
+                        match remaining {
+                            None => {
+                                config.exclude_chans_pull.remove(&s);
+                                config.exclude_chans_push.remove(&s);
+                                static_excepts.remove(&s);
+                            }
+                            Some(ExceptDirection::Pull) => {
+                                config.exclude_chans_push.remove(&s);
+                                static_excepts.insert(
+                                    s,
+                                    ExceptChan {
+                                        direction: ExceptDirection::Pull,
+                                        expires_at: e.expires_at,
+                                    },
+                                );
+                            }
+                            Some(ExceptDirection::Push) => {
+                                config.exclude_chans_pull.remove(&s);
+                                static_excepts.insert(
+                                    s,
+                                    ExceptChan {
+                                        direction: ExceptDirection::Push,
+                                        expires_at: e.expires_at,
+                                    },
+                                );
+                            }
+                            Some(ExceptDirection::Both) => unreachable!(),
+                        }
+                        changed.push(s.to_string());
+                    }
+                },
                 _ => {
                     return Err(anyhow!(
                         "Use `add`/`remove` and a ShortChannelId or just `list`"
@@ -728,16 +1152,46 @@ pub async fn slingexceptchan(
                 }
             }
         }
-        write_excepts(static_excepts, EXCEPTS_CHANS_FILE_NAME, &sling_dir).await?;
-        Ok(json!({ "result": "success" }))
-    } else {
-        match command {
-            opt if opt.eq("list") => Ok(json!(static_excepts)),
-            _ => Err(anyhow!(
-                "unknown commmand, did you misspell `list` or forgot the scid?"
-            )),
+    }
+    write_except_chans(&static_excepts, &sling_dir).await?;
+
+    Ok(match command.as_str() {
+        "add" => json!({ "added": changed, "skipped": skipped }),
+        _ => json!({ "removed": changed, "skipped": skipped }),
+    })
+}
+
+fn parse_pubkey_list(
+    single: Option<&serde_json::Value>,
+    multi: Option<&serde_json::Value>,
+) -> Result<Vec<PubKeyBytes>, Error> {
+    let mut pubkeys = Vec::new();
+    match single {
+        Some(serde_json::Value::String(s)) => pubkeys.push(PubKeyBytes::from_str(s)?),
+        Some(serde_json::Value::Array(a)) => {
+            for v in a {
+                match v {
+                    serde_json::Value::String(s) => pubkeys.push(PubKeyBytes::from_str(s)?),
+                    o => return Err(anyhow!("not a valid string: {}", o)),
+                }
+            }
+        }
+        Some(o) => return Err(anyhow!("not a valid string or array for `id`: {}", o)),
+        None => {}
+    }
+    match multi {
+        Some(serde_json::Value::Array(a)) => {
+            for v in a {
+                match v {
+                    serde_json::Value::String(s) => pubkeys.push(PubKeyBytes::from_str(s)?),
+                    o => return Err(anyhow!("not a valid string: {}", o)),
+                }
+            }
         }
+        Some(o) => return Err(anyhow!("not a valid array for `ids`: {}", o)),
+        None => {}
     }
+    Ok(pubkeys)
 }
 
 pub async fn slingexceptpeer(
@@ -746,12 +1200,12 @@ pub async fn slingexceptpeer(
 ) -> Result<serde_json::Value, Error> {
     let _rpc_lock = plugin.state().rpc_lock.lock().await;
 
-    let (command, pubkey_bytes) = match args {
+    let (command, pubkeys, expires_at) = match args {
         serde_json::Value::Array(a) => {
             if a.len() > 2 || a.is_empty() {
                 return Err(anyhow!(
                     "Invalid amount of arguments. Either provide `add`/`remove` \
-                    and a peer `id` or just `list`"
+                    and a peer `id` (or array of them) or just `list`"
                 ));
             }
             let com = match a.first().unwrap() {
@@ -763,17 +1217,13 @@ pub async fn slingexceptpeer(
                 }
             };
             if com == "list" && a.len() == 1 {
-                (com, None)
+                (com, Vec::new(), None)
             } else if a.len() == 2 {
-                let pb = match a.get(1).unwrap() {
-                    serde_json::Value::String(s) => PubKeyBytes::from_str(s)?,
-                    o => return Err(anyhow!("node_id is not a string: {}", o)),
-                };
-                (com, Some(pb))
+                (com, parse_pubkey_list(a.get(1), None)?, None)
             } else {
                 return Err(anyhow!(
                     "Invalid amount of arguments. Please either provide `add`/`remove` \
-                    and a peer `id` or just `list`"
+                    and a peer `id` (or array of them) or just `list`"
                 ));
             }
         }
@@ -786,11 +1236,8 @@ pub async fn slingexceptpeer(
                     ))
                 }
             };
-            match o.get("id") {
-                Some(serde_json::Value::String(s)) => (command, Some(PubKeyBytes::from_str(s)?)),
-                None => (command, None),
-                o => return Err(anyhow!("not a vaild string for peer `id`: {:?}", o)),
-            }
+            let expires_at = resolve_expiry(&o)?;
+            (command, parse_pubkey_list(o.get("id"), o.get("ids"))?, expires_at)
         }
         e => {
             return Err(anyhow!(
@@ -804,40 +1251,68 @@ pub async fn slingexceptpeer(
     let peer_channels = plugin.state().peer_channels.lock().clone();
     let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
     let mut static_excepts = read_except_peers(&sling_dir).await?;
-    if let Some(pb) = pubkey_bytes {
-        let pubkey = pb.to_pubkey();
-        {
-            let jobs = read_jobs(&sling_dir, plugin.clone()).await?;
-            let mut config = plugin.state().config.lock();
-            match command {
-                opt if opt.eq("add") => {
-                    if config.exclude_peers.contains(&pb) {
-                        return Err(anyhow!("{} is already in excepts", pubkey));
+    if pubkeys.is_empty() {
+        return match command {
+            opt if opt.eq("list") => {
+                let now = now_secs();
+                let out: BTreeMap<String, serde_json::Value> = static_excepts
+                    .iter()
+                    .map(|(id, expiry)| {
+                        let remaining = expiry.map(|exp| exp.saturating_sub(now));
+                        (
+                            id.to_string(),
+                            json!({ "expires_at": expiry, "expires_in_secs": remaining }),
+                        )
+                    })
+                    .collect();
+                Ok(json!(out))
+            }
+            _ => Err(anyhow!(
+                "unknown commmand, use `list` or forgot the peer `id`?"
+            )),
+        };
+    }
+
+    let mut changed: Vec<String> = Vec::new();
+    let mut skipped: Vec<serde_json::Value> = Vec::new();
+    {
+        let jobs = read_jobs(&sling_dir, plugin.clone()).await?;
+        let mut config = plugin.state().config.lock();
+
+        for pb in pubkeys {
+            let pubkey = pb.to_pubkey();
+            match command.as_str() {
+                "add" => {
+                    if config.exclude_peers.contains(&pb) && expires_at.is_none() {
+                        skipped.push(json!({"id": pubkey.to_string(), "reason": "already in excepts"}));
+                        continue;
                     }
                     if config.pubkey_bytes == pb {
-                        return Err(anyhow!("Can't exclude yourself"));
+                        skipped.push(json!({"id": pubkey.to_string(), "reason": "can't exclude yourself"}));
+                        continue;
                     }
-                    for scid in jobs.keys() {
-                        if let Some(peer_chan) = peer_channels.get(scid) {
-                            if peer_chan.peer_id == pubkey {
-                                return Err(anyhow!(
-                                    "this peer has a job already and can't be an except too"
-                                ));
-                            }
-                        };
+                    if jobs.keys().any(|scid| {
+                        peer_channels
+                            .get(scid)
+                            .is_some_and(|peer_chan| peer_chan.peer_id == pubkey)
+                    }) {
+                        skipped.push(json!({"id": pubkey.to_string(), "reason": "this peer has a job already and can't be an except too"}));
+                        continue;
                     }
+                    let merged_expiry = expires_at.or(static_excepts.get(&pubkey).copied().flatten());
                     config.exclude_peers.insert(pb);
-                    static_excepts.insert(pubkey);
+                    static_excepts.insert(pubkey, merged_expiry);
+                    changed.push(pubkey.to_string());
                 }
-                opt if opt.eq("remove") => {
-                    if static_excepts.contains(&pubkey) {
+                "remove" => {
+                    if static_excepts.contains_key(&pubkey) {
                         static_excepts.remove(&pubkey);
                         config.exclude_peers.remove(&pb);
+                        changed.push(pubkey.to_string());
                     } else {
-                        return Err(anyhow!(
-                            "peer `id` {} not in excepts, nothing to remove",
-                            pubkey
-                        ));
+                        skipped.push(
+                            json!({"id": pubkey.to_string(), "reason": "not in excepts, nothing to remove"}),
+                        );
                     }
                 }
                 _ => {
@@ -847,16 +1322,13 @@ pub async fn slingexceptpeer(
                 }
             }
         }
-        write_excepts::<PublicKey>(static_excepts, EXCEPTS_PEERS_FILE_NAME, &sling_dir).await?;
-        Ok(json!({ "result": "success" }))
-    } else {
-        match command {
-            opt if opt.eq("list") => Ok(json!(static_excepts)),
-            _ => Err(anyhow!(
-                "unknown commmand, use `list` or forgot the peer `id`?"
-            )),
-        }
     }
+    write_except_peers(&static_excepts, &sling_dir).await?;
+
+    Ok(match command.as_str() {
+        "add" => json!({ "added": changed, "skipped": skipped }),
+        _ => json!({ "removed": changed, "skipped": skipped }),
+    })
 }
 
 pub async fn slingversion(
@@ -865,3 +1337,35 @@ pub async fn slingversion(
 ) -> Result<serde_json::Value, Error> {
     Ok(json!({ "version": format!("v{}",env!("CARGO_PKG_VERSION")) }))
 }
+
+pub async fn slinglistconfigs(
+    plugin: Plugin<PluginState>,
+    _args: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let config = plugin.state().config.lock().clone();
+    let set_at = plugin.state().option_set_at.lock().clone();
+
+    let configs = OPTION_SPECS
+        .iter()
+        .map(|spec| {
+            let value = current_numeric_value(&config, spec.name).unwrap_or(spec.default);
+            let (source, set_at_ts) = match set_at.get(spec.name) {
+                Some((source, ts)) => (json!(source), json!(ts)),
+                None => (json!("default"), serde_json::Value::Null),
+            };
+            (
+                spec.name.to_string(),
+                json!({
+                    "value": value,
+                    "default": spec.default,
+                    "min": spec.min,
+                    "max": spec.max,
+                    "source": source,
+                    "set_at": set_at_ts,
+                }),
+            )
+        })
+        .collect::<serde_json::Map<String, serde_json::Value>>();
+
+    Ok(json!({ "configs": configs }))
+}
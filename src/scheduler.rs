@@ -0,0 +1,358 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Error};
+use chrono::{Datelike, Local, Timelike};
+use cln_plugin::Plugin;
+use cln_rpc::primitives::ShortChannelId;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::{
+    fs::{self, File},
+    time,
+};
+
+use crate::{model::PluginState, read_jobs, rpc_sling::slinggo, PLUGIN_NAME};
+
+pub const SCHEDULER_FILE_NAME: &str = "scheduler.json";
+const SCHEDULER_TICK_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SchedulerEntry {
+    pub last_run: Option<u64>,
+    pub next_run: u64,
+}
+
+/// A job's `schedule` string, parsed into one of the forms [`parse_schedule`] understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleSpec {
+    /// `every <N>s`: fire once every `N` seconds, tracked via [`SchedulerEntry`]. The original
+    /// (chunk1-3) syntax, kept as-is for jobs that just want a steady cadence rather than a
+    /// time-of-day window.
+    Interval(u64),
+    /// `HH:MM-HH:MM`: minutes-since-midnight the job is allowed to run. `start > end` means the
+    /// window wraps past midnight (e.g. `22:00-06:00`).
+    Window { start_min: u16, end_min: u16 },
+    /// `min hour dow`: a 3-field cron subset, each field either `*` or one exact value (no
+    /// ranges/lists/steps). `dow` follows cron convention: `0` is Sunday.
+    Cron {
+        minute: Option<u8>,
+        hour: Option<u8>,
+        dow: Option<u8>,
+    },
+}
+
+/// Parses the `every <N>s` syntax alone (a plain interval in seconds), used both directly by
+/// [`parse_schedule`] and by existing callers/tests that only care about that form.
+pub fn parse_schedule_interval_secs(schedule: &str) -> Result<u64, Error> {
+    let schedule = schedule.trim();
+    if let Some(secs) = schedule.strip_prefix("every ").and_then(|s| s.strip_suffix('s')) {
+        let secs: u64 = secs
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid interval in schedule `{}`", schedule))?;
+        if secs == 0 {
+            return Err(anyhow!("schedule interval must be greater than 0"));
+        }
+        return Ok(secs);
+    }
+    Err(anyhow!(
+        "unsupported schedule `{}`, expected `every <N>s`",
+        schedule
+    ))
+}
+
+fn parse_clock(s: &str) -> Result<u16, Error> {
+    let (h, m) = s
+        .trim()
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected `HH:MM`, got `{s}`"))?;
+    let h: u16 = h.parse().map_err(|_| anyhow!("invalid hour in `{s}`"))?;
+    let m: u16 = m.parse().map_err(|_| anyhow!("invalid minute in `{s}`"))?;
+    if h > 23 || m > 59 {
+        return Err(anyhow!("hour/minute out of range in `{s}`"));
+    }
+    Ok(h * 60 + m)
+}
+
+fn parse_cron_field(s: &str, max: u8) -> Result<Option<u8>, Error> {
+    if s == "*" {
+        return Ok(None);
+    }
+    let v: u8 = s.parse().map_err(|_| anyhow!("invalid cron field `{s}`"))?;
+    if v > max {
+        return Err(anyhow!("cron field `{s}` out of range, max is {max}"));
+    }
+    Ok(Some(v))
+}
+
+/// Parses a job's `schedule` string into one of [`ScheduleSpec`]'s forms, trying `every <N>s`,
+/// then a `HH:MM-HH:MM` time window, then a `min hour dow` cron subset.
+pub fn parse_schedule(schedule: &str) -> Result<ScheduleSpec, Error> {
+    let trimmed = schedule.trim();
+
+    if trimmed.starts_with("every ") {
+        return Ok(ScheduleSpec::Interval(parse_schedule_interval_secs(
+            trimmed,
+        )?));
+    }
+
+    if let Some((start, end)) = trimmed.split_once('-') {
+        return Ok(ScheduleSpec::Window {
+            start_min: parse_clock(start)?,
+            end_min: parse_clock(end)?,
+        });
+    }
+
+    let fields: Vec<&str> = trimmed.split_whitespace().collect();
+    if let [minute, hour, dow] = fields[..] {
+        return Ok(ScheduleSpec::Cron {
+            minute: parse_cron_field(minute, 59)?,
+            hour: parse_cron_field(hour, 23)?,
+            dow: parse_cron_field(dow, 6)?,
+        });
+    }
+
+    Err(anyhow!(
+        "unsupported schedule `{}`, expected `every <N>s`, `HH:MM-HH:MM`, or `min hour dow`",
+        schedule
+    ))
+}
+
+/// Whether `now_min` (minutes since local midnight) falls inside `[start_min, end_min)`,
+/// wrapping past midnight when `start_min > end_min`. `start_min == end_min` is treated as a
+/// permanently-open window rather than a zero-length one.
+fn window_is_open(now_min: u16, start_min: u16, end_min: u16) -> bool {
+    match start_min.cmp(&end_min) {
+        std::cmp::Ordering::Equal => true,
+        std::cmp::Ordering::Less => now_min >= start_min && now_min < end_min,
+        std::cmp::Ordering::Greater => now_min >= start_min || now_min < end_min,
+    }
+}
+
+/// Whether the current local minute matches a `min hour dow` cron spec. With an exact `minute`
+/// a match only lasts that one minute, so the job runs in short pulses rather than for a
+/// stretch like a [`ScheduleSpec::Window`] does; a `*` minute matches every minute of each
+/// matching hour.
+fn cron_is_open(
+    now_minute: u8,
+    now_hour: u8,
+    now_dow: u8,
+    minute: Option<u8>,
+    hour: Option<u8>,
+    dow: Option<u8>,
+) -> bool {
+    minute.is_none_or(|m| m == now_minute)
+        && hour.is_none_or(|h| h == now_hour)
+        && dow.is_none_or(|d| d == now_dow)
+}
+
+/// Whether `schedule` currently permits the job to run. Only meaningful for
+/// [`ScheduleSpec::Window`] and [`ScheduleSpec::Cron`]; [`ScheduleSpec::Interval`] is driven by
+/// [`SchedulerEntry`] instead and isn't an on/off window.
+fn is_schedule_open(spec: &ScheduleSpec) -> Option<bool> {
+    match *spec {
+        ScheduleSpec::Interval(_) => None,
+        ScheduleSpec::Window { start_min, end_min } => {
+            let now = Local::now().time();
+            let now_min = now.hour() as u16 * 60 + now.minute() as u16;
+            Some(window_is_open(now_min, start_min, end_min))
+        }
+        ScheduleSpec::Cron { minute, hour, dow } => {
+            let now = Local::now();
+            Some(cron_is_open(
+                now.minute() as u8,
+                now.hour() as u8,
+                now.weekday().num_days_from_sunday() as u8,
+                minute,
+                hour,
+                dow,
+            ))
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+async fn read_scheduler_entries(
+    sling_dir: &PathBuf,
+) -> Result<BTreeMap<ShortChannelId, SchedulerEntry>, Error> {
+    let file = sling_dir.join(SCHEDULER_FILE_NAME);
+    match fs::read_to_string(&file).await {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(_) => {
+            File::create(&file).await?;
+            Ok(BTreeMap::new())
+        }
+    }
+}
+
+async fn write_scheduler_entries(
+    sling_dir: &PathBuf,
+    entries: &BTreeMap<ShortChannelId, SchedulerEntry>,
+) -> Result<(), Error> {
+    fs::write(
+        sling_dir.join(SCHEDULER_FILE_NAME),
+        serde_json::to_string_pretty(entries)?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Background tick for `schedule`d jobs: wakes once a minute, evaluates every job's schedule,
+/// and drives `Interval` jobs off [`SchedulerEntry`] while starting/stopping `Window`/`Cron`
+/// jobs as their windows open and close.
+pub async fn scheduler_loop(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    loop {
+        let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+        if let Err(e) = run_due_jobs(&plugin, &sling_dir).await {
+            log::warn!("Error running scheduled jobs: {e:?}");
+        }
+        retry_errored_tasks(&plugin).await;
+        time::sleep(Duration::from_secs(SCHEDULER_TICK_SECS)).await;
+    }
+}
+
+/// Re-launches tasks that exited in [`crate::JobMessage::Error`] once their
+/// [`crate::model::Task::get_next_retry_at`] backoff has elapsed, so a transient failure doesn't
+/// leave a job dead until a human re-runs `sling-go`. One `slinggo` call per scid is enough: it
+/// restarts every inactive task for that channel, not just the one that tripped the retry.
+async fn retry_errored_tasks(plugin: &Plugin<PluginState>) {
+    let now = now_secs();
+    let due_scids: Vec<ShortChannelId> = {
+        let tasks = plugin.state().tasks.lock();
+        tasks
+            .get_all_tasks()
+            .iter()
+            .filter(|(_, scid_tasks)| {
+                scid_tasks
+                    .values()
+                    .any(|t| !t.is_active() && t.get_next_retry_at().is_some_and(|at| now >= at))
+            })
+            .map(|(scid, _)| *scid)
+            .collect()
+    };
+
+    for scid in due_scids {
+        log::info!("{scid}: retrying job after error backoff");
+        if let Err(e) = slinggo(plugin.clone(), json!([scid.to_string()])).await {
+            log::warn!("{scid}: scheduled retry failed to start: {e}");
+        }
+    }
+}
+
+async fn run_due_jobs(plugin: &Plugin<PluginState>, sling_dir: &PathBuf) -> Result<(), Error> {
+    let jobs = read_jobs(sling_dir, plugin.clone()).await?;
+    let mut entries = read_scheduler_entries(sling_dir).await?;
+    let now = now_secs();
+    let mut changed = false;
+
+    entries.retain(|scid, _| jobs.contains_key(scid));
+
+    for (scid, job) in &jobs {
+        let Some(schedule) = job.get_schedule() else {
+            continue;
+        };
+        let spec = match parse_schedule(schedule) {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!("{scid}: invalid schedule `{schedule}`: {e}");
+                continue;
+            }
+        };
+
+        match spec {
+            ScheduleSpec::Interval(interval) => {
+                run_due_interval_job(plugin, scid, interval, now, &mut entries, &mut changed).await;
+            }
+            ScheduleSpec::Window { .. } | ScheduleSpec::Cron { .. } => {
+                let Some(should_run) = is_schedule_open(&spec) else {
+                    continue;
+                };
+                run_windowed_job(plugin, scid, should_run).await;
+            }
+        }
+    }
+
+    if changed {
+        write_scheduler_entries(sling_dir, &entries).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_due_interval_job(
+    plugin: &Plugin<PluginState>,
+    scid: &ShortChannelId,
+    interval: u64,
+    now: u64,
+    entries: &mut BTreeMap<ShortChannelId, SchedulerEntry>,
+    changed: &mut bool,
+) {
+    let entry = entries.entry(*scid).or_insert_with(|| {
+        *changed = true;
+        SchedulerEntry {
+            last_run: None,
+            next_run: now + interval,
+        }
+    });
+
+    if now < entry.next_run {
+        return;
+    }
+
+    if once_job_already_active(plugin, scid) {
+        log::debug!("{scid}: once-job already active, skipping scheduled tick");
+        entry.next_run = now + interval;
+        *changed = true;
+        return;
+    }
+
+    log::info!("{scid}: starting scheduled job");
+    if let Err(e) = slinggo(plugin.clone(), json!([scid.to_string()])).await {
+        log::warn!("{scid}: scheduled job failed to start: {e}");
+    }
+    entry.last_run = Some(now);
+    entry.next_run = now + interval;
+    *changed = true;
+}
+
+/// Starts or stops a `Window`/`Cron`-scheduled job to match `should_run`. Comparing against
+/// [`crate::model::Tasks::is_any_active`] rather than tracking "was this window open last
+/// tick" ourselves means a plugin restart just reconciles to the same desired state instead of
+/// risking a double spawn: if the job is already running when its window is open, there's
+/// nothing to do.
+async fn run_windowed_job(plugin: &Plugin<PluginState>, scid: &ShortChannelId, should_run: bool) {
+    let is_active = plugin.state().tasks.lock().is_any_active(scid);
+
+    if should_run && !is_active {
+        if once_job_already_active(plugin, scid) {
+            return;
+        }
+        log::info!("{scid}: schedule window opened, starting job");
+        if let Err(e) = slinggo(plugin.clone(), json!([scid.to_string()])).await {
+            log::warn!("{scid}: scheduled job failed to start: {e}");
+        }
+    } else if !should_run && is_active {
+        log::info!("{scid}: schedule window closed, stopping job");
+        if let Err(e) = crate::rpc_sling::slingstop(plugin.clone(), json!([scid.to_string()])).await
+        {
+            log::warn!("{scid}: scheduled job failed to stop: {e}");
+        }
+    }
+}
+
+fn once_job_already_active(plugin: &Plugin<PluginState>, scid: &ShortChannelId) -> bool {
+    let tasks = plugin.state().tasks.lock();
+    tasks
+        .get_scid_tasks(scid)
+        .is_some_and(|t| t.values().any(|ta| ta.is_once()) && tasks.is_any_active(scid))
+}
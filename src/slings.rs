@@ -1,24 +1,53 @@
 use std::{
     cmp::{max, min},
-    collections::HashMap,
-    time::Duration,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error};
 use cln_plugin::Plugin;
 use cln_rpc::{
-    model::{requests::SendpayRoute, responses::ListpeerchannelsChannels},
+    model::{
+        requests::{ConnectRequest, DisconnectRequest, ListnodesRequest, SendpayRoute},
+        responses::ListpeerchannelsChannels,
+    },
     primitives::{Amount, PublicKey, ShortChannelId, ShortChannelIdDir},
+    ClnRpc,
 };
 use sling::{Job, SatDirection};
-use tokio::time::{self, Instant};
+use tokio::{
+    sync::Notify,
+    time::{self, Instant},
+};
 
 use crate::{
-    dijkstra::dijkstra,
+    coordination::negotiate_rebalance,
+    dijkstra::{
+        dijkstra_cached, dijkstra_mpp, dijkstra_per_candidate, k_shortest_paths,
+        NEXT_ROUTE_ALT_PATHS,
+    },
     get_remote_feeppm_effective,
-    model::{Config, JobMessage, PayResolveInfo, PluginState, PubKeyBytes, TaskIdentifier},
+    model::{
+        record_latency_ms,
+        release_reservation,
+        try_reserve_route,
+        ChannelBackoff,
+        Config,
+        JobMessage,
+        LnGraph,
+        MppPay,
+        PayResolveInfo,
+        PluginState,
+        PubKeyBytes,
+        Task,
+        TaskIdentifier,
+    },
+    notifications::{notify_rebalance, notify_rebalance_outcome},
     response::{sendpay_response, waitsendpay_response},
     util::{
+        candidate_rank_score,
+        edge_success_probability,
         feeppm_effective,
         feeppm_effective_from_amts,
         get_normal_channel_from_listpeerchannels,
@@ -26,8 +55,12 @@ use crate::{
         get_total_htlc_count,
         is_channel_normal,
         my_sleep,
+        route_success_probability,
+        sync_mpp_pays_to_disk,
+        usable_liquidity_msat,
     },
     wait_for_gossip,
+    SuccessReb,
 };
 
 pub async fn sling(
@@ -54,13 +87,35 @@ pub async fn sling(
             let mut tasks = plugin.state().tasks.lock();
             tasks.set_state(&task_ident, JobMessage::Stopped);
             tasks.set_active(&task_ident, false);
+            drop(tasks);
+            notify_rebalance(
+                &plugin,
+                &task_ident,
+                job.sat_direction,
+                JobMessage::Stopped,
+                None,
+                None,
+            )
+            .await;
             break;
         }
 
+        if task.is_paused() {
+            plugin
+                .state()
+                .tasks
+                .lock()
+                .set_state(&task_ident, JobMessage::Paused);
+            time::sleep(Duration::from_millis(500)).await;
+            continue 'outer;
+        }
+
         let config = plugin.state().config.lock().clone();
 
         let temp_chan_bans = plugin.state().temp_chan_bans.lock().clone();
         let bad_fwd_nodes = plugin.state().bad_fwd_nodes.lock().clone();
+        let excluded_scids = plugin.state().excluded_scids.lock().clone();
+        let excluded_nodes = plugin.state().excluded_nodes.lock().clone();
         let peer_channels = plugin.state().peer_channels.lock().clone();
 
         if let Some(r) = health_check(
@@ -87,7 +142,7 @@ pub async fn sling(
             .lock()
             .set_state(&task_ident, JobMessage::Rebalancing);
 
-        let mut excepts = build_except_chans(&temp_chan_bans, &config, job);
+        let mut excepts = build_except_chans(&temp_chan_bans, &excluded_scids, &config, job);
 
         let actual_candidates = build_candidatelist(
             &plugin,
@@ -96,8 +151,18 @@ pub async fn sling(
             job,
             &excepts,
             &bad_fwd_nodes,
+            &excluded_nodes,
             &config,
         )?;
+        let actual_candidates = filter_reachable_candidates(
+            &plugin,
+            &config,
+            job,
+            &task,
+            &temp_chan_bans,
+            &excepts,
+            actual_candidates,
+        );
 
         log::debug!(
             "{}: Candidates: {}",
@@ -165,18 +230,74 @@ pub async fn sling(
             continue 'outer;
         }
 
-        let route = {
-            let nr = next_route(
-                &plugin,
-                &config,
-                &peer_channels,
-                job,
-                &mut excepts,
-                &task_ident,
-                &mut success_route,
-                &actual_candidates,
+        let parts = {
+            // Bounded by `sling-route-workers`, separately from the send-side permit below, so
+            // a node with many jobs doesn't let route search and payment execution starve each
+            // other. Held as an owned permit since the search itself runs on a blocking thread
+            // (see below), so it keeps counting against the pool until that thread finishes even
+            // if we give up waiting on it.
+            let route_permit = plugin
+                .state()
+                .route_search_permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("route_search_permits semaphore is never closed");
+
+            let route_search_started = Instant::now();
+            // next_route is a synchronous, potentially expensive dijkstra search (huge graph,
+            // deep sling-maxhops). Run it on a blocking thread so sling-timeout-route-search can
+            // actually bound it instead of just racing against a future that never yields.
+            let plugin_owned = plugin.clone();
+            let config_owned = config.clone();
+            let peer_channels_owned = peer_channels.clone();
+            let job_owned = job.clone();
+            let mut excepts_owned = excepts.clone();
+            let mut success_route_owned = success_route.clone();
+            let actual_candidates_owned = actual_candidates.clone();
+            let search = tokio::task::spawn_blocking(move || {
+                let _route_permit = route_permit;
+                let route = next_route(
+                    &plugin_owned,
+                    &config_owned,
+                    &peer_channels_owned,
+                    &job_owned,
+                    &mut excepts_owned,
+                    &task_ident,
+                    &mut success_route_owned,
+                    &actual_candidates_owned,
+                );
+                (route, excepts_owned, success_route_owned)
+            });
+
+            let nr = match time::timeout(
+                Duration::from_secs(config.timeout_route_search),
+                search,
+            )
+            .await
+            {
+                Ok(Ok((route, updated_excepts, updated_success_route))) => {
+                    excepts = updated_excepts;
+                    success_route = updated_success_route;
+                    route
+                }
+                Ok(Err(e)) => Err(anyhow!("{task_ident}: route search task panicked: {e}")),
+                Err(_) => {
+                    log::warn!(
+                        "{task_ident}: route search exceeded {}s, abandoning this attempt",
+                        config.timeout_route_search
+                    );
+                    Err(anyhow!("{task_ident}: route search timed out"))
+                }
+            };
+            record_latency_ms(
+                &plugin.state().route_search_latency_ms,
+                route_search_started.elapsed(),
             );
-            if nr.is_err() || nr.as_ref().unwrap().is_empty() {
+            if nr.is_err()
+                || nr.as_ref().unwrap().is_empty()
+                || nr.as_ref().unwrap().iter().any(Vec::is_empty)
+            {
                 log::info!("{task_ident}: could not find a dijkstra route. Sleeping...");
                 plugin
                     .state()
@@ -194,33 +315,48 @@ pub async fn sling(
             nr.unwrap()
         };
 
-        let fee_ppm_effective = feeppm_effective_from_amts(
-            Amount::msat(&route.first().unwrap().amount_msat),
-            Amount::msat(&route.last().unwrap().amount_msat),
-        );
+        let is_mpp = parts.len() > 1;
+        let representative = parts[0].clone();
+
+        let total_sent_msat: u64 = parts
+            .iter()
+            .map(|p| Amount::msat(&p.first().unwrap().amount_msat))
+            .sum();
+        let total_recv_msat: u64 = parts
+            .iter()
+            .map(|p| Amount::msat(&p.last().unwrap().amount_msat))
+            .sum();
+        let fee_ppm_effective = feeppm_effective_from_amts(total_sent_msat, total_recv_msat);
         log::info!(
-            "{}: Found {}ppm route with {} hops. Total: {}ms",
+            "{}: Found {}ppm route across {} part(s) ({} hops each). Total: {}ms",
             task_ident,
             fee_ppm_effective,
-            route.len() - 1,
+            parts.len(),
+            representative.len() - 1,
             now.elapsed().as_millis()
         );
 
         {
             let alias_map = plugin.state().alias_peer_map.lock();
-            for r in &route {
-                log::debug!(
-                    "{}: route: {} {:4} {:17} {}",
-                    task_ident,
-                    Amount::msat(&r.amount_msat),
-                    r.delay,
-                    r.channel.to_string(),
-                    alias_map.get(&r.id).unwrap_or(&r.id.to_string()),
-                );
+            for (pi, part) in parts.iter().enumerate() {
+                for r in part {
+                    log::debug!(
+                        "{}: part {}: {} {:4} {:17} {}",
+                        task_ident,
+                        pi,
+                        Amount::msat(&r.amount_msat),
+                        r.delay,
+                        r.channel.to_string(),
+                        alias_map.get(&r.id).unwrap_or(&r.id.to_string()),
+                    );
+                }
             }
         }
 
-        if fee_ppm_effective > job.maxppm {
+        let total_fee_msat = total_sent_msat.saturating_sub(total_recv_msat);
+        if fee_ppm_effective > job.maxppm
+            || job.get_maxfee_msat().is_some_and(|maxfee| total_fee_msat > maxfee)
+        {
             log::info!("{task_ident}: route not cheap enough! Sleeping...");
             plugin
                 .state()
@@ -234,7 +370,7 @@ pub async fn sling(
 
         let (preimage, payment_hash) = get_preimage_paymend_hash_pair();
 
-        let last_scid = route.last().unwrap().channel;
+        let last_scid = representative.last().unwrap().channel;
 
         let last_hop = if let Some(l_hop) = peer_channels.get(&last_scid) {
             Some(l_hop)
@@ -248,9 +384,19 @@ pub async fn sling(
                                 let mut tasks = plugin.state().tasks.lock();
                                 tasks.set_state(&task_ident, JobMessage::Error);
                                 tasks.set_active(&task_ident, false);
+                                drop(tasks);
                                 log::warn!(
                                     "{task_ident}: Found multiple matching last hops via alias"
                                 );
+                                notify_rebalance(
+                                    &plugin,
+                                    &task_ident,
+                                    job.sat_direction,
+                                    JobMessage::Error,
+                                    None,
+                                    None,
+                                )
+                                .await;
                                 break 'outer;
                             }
                             find_hop = Some(channel);
@@ -265,7 +411,17 @@ pub async fn sling(
             let mut tasks = plugin.state().tasks.lock();
             tasks.set_state(&task_ident, JobMessage::Error);
             tasks.set_active(&task_ident, false);
+            drop(tasks);
             log::warn!("{task_ident}: Could not find last hop");
+            notify_rebalance(
+                &plugin,
+                &task_ident,
+                job.sat_direction,
+                JobMessage::Error,
+                None,
+                None,
+            )
+            .await;
             break 'outer;
         }
 
@@ -275,66 +431,369 @@ pub async fn sling(
             incoming_alias: last_hop.unwrap().alias.as_ref().and_then(|a| a.remote),
         };
 
-        let send_response = match sendpay_response(
-            plugin.clone(),
-            &config,
-            payment_hash,
-            pay_resolve_info,
-            &task_ident,
-            job,
-            &route,
-            &mut success_route,
-        )
-        .await
-        {
-            Ok(o) => {
-                if let Some(resp) = o {
-                    resp
-                } else {
-                    continue;
+        // Bounded by `sling-send-workers`, held across dispatch and resolution so the number
+        // of HTLCs we have in flight at once is capped regardless of how many jobs are running.
+        let send_permit = plugin
+            .state()
+            .send_permits
+            .acquire()
+            .await
+            .expect("send_permits semaphore is never closed");
+
+        // Claims every hop of every part against the other jobs' in-flight rebalances, so two
+        // tasks routing through the same channel at once can't each think it has the full
+        // htlc_maximum_msat/max_htlc_count headroom to itself. If any part's channel is
+        // already committed elsewhere, back the whole attempt off and let this task try again
+        // next iteration rather than sending some parts and not others.
+        let mut reserved_parts = Vec::with_capacity(parts.len());
+        let mut reserve_err = None;
+        for part in &parts {
+            match try_reserve_route(plugin.state(), part) {
+                Ok(r) => reserved_parts.push(r),
+                Err(e) => {
+                    reserve_err = Some(e);
+                    break;
                 }
             }
-            Err(e) => {
-                let mut tasks = plugin.state().tasks.lock();
-                tasks.set_state(&task_ident, JobMessage::Error);
-                tasks.set_active(&task_ident, false);
-                log::warn!("{e}");
-                break 'outer;
+        }
+        if let Some(e) = reserve_err {
+            for r in &reserved_parts {
+                release_reservation(plugin.state(), r);
             }
-        };
+            log::info!("{task_ident}: {e}. Sleeping...");
+            drop(send_permit);
+            my_sleep(plugin.clone(), 10, &task_ident).await;
+            success_route = None;
+            continue 'outer;
+        }
+
+        if is_mpp {
+            // Registered before any part is sent, so even the first part's HTLC to land finds
+            // the hold in place instead of racing `htlc_handler`.
+            plugin.state().mpp_pays.lock().insert(
+                payment_hash.to_string(),
+                MppPay {
+                    resolve: pay_resolve_info.clone(),
+                    target_msat: total_recv_msat,
+                    parts_expected: u32::try_from(parts.len()).unwrap_or(u32::MAX),
+                    received_msat: 0,
+                    resolved: false,
+                    notify: Arc::new(Notify::new()),
+                    part_timeout_secs: u64::from(config.timeoutpay),
+                },
+            );
+            if let Err(e) = sync_mpp_pays_to_disk(&plugin, &config.sling_dir).await {
+                log::warn!("{task_ident}: could not persist in-flight MPP state: {e}");
+            }
+        }
+
+        // Send every part, tolerating individual sendpay failures for MPP jobs: a part that
+        // never made it out just means this attempt falls short of `total_recv_msat`, so the
+        // hold in `htlc_handler` times the whole attempt out and the outer loop retries with a
+        // freshly split route rather than us having to re-split mid-attempt.
+        let mut sent_parts = Vec::with_capacity(parts.len());
+        for (i, part) in parts.iter().enumerate() {
+            let partid_groupid = is_mpp.then_some((u64::try_from(i).unwrap_or(u64::MAX) + 1, 1));
+            let part_pay_resolve_info = (!is_mpp).then(|| pay_resolve_info.clone());
+            match sendpay_response(
+                plugin.clone(),
+                &config,
+                payment_hash,
+                part_pay_resolve_info,
+                partid_groupid,
+                &task_ident,
+                job,
+                part,
+                &mut success_route,
+            )
+            .await
+            {
+                Ok(Some(resp)) => sent_parts.push((part.clone(), resp)),
+                Ok(None) if is_mpp => {
+                    log::info!(
+                        "{task_ident}: MPP part {}/{} could not be sent, continuing with the rest",
+                        i + 1,
+                        parts.len()
+                    );
+                }
+                Ok(None) => {
+                    if is_mpp {
+                        plugin.state().mpp_pays.lock().remove(&payment_hash.to_string());
+                        if let Err(e) = sync_mpp_pays_to_disk(&plugin, &config.sling_dir).await {
+                            log::warn!(
+                                "{task_ident}: could not persist in-flight MPP state: {e}"
+                            );
+                        }
+                    }
+                    for r in &reserved_parts {
+                        release_reservation(plugin.state(), r);
+                    }
+                    continue 'outer;
+                }
+                Err(e) if is_mpp => {
+                    log::warn!("{task_ident}: MPP part {}/{} errored: {e}", i + 1, parts.len());
+                }
+                Err(e) => {
+                    for r in &reserved_parts {
+                        release_reservation(plugin.state(), r);
+                    }
+                    let mut tasks = plugin.state().tasks.lock();
+                    tasks.record_exit_failure(
+                        &task_ident,
+                        e.to_string(),
+                        config.job_retry_base_secs,
+                        config.job_retry_max_secs,
+                        config.job_retry_max_attempts,
+                    );
+                    drop(tasks);
+                    log::warn!("{e}");
+                    notify_rebalance(
+                        &plugin,
+                        &task_ident,
+                        job.sat_direction,
+                        JobMessage::Error,
+                        None,
+                        None,
+                    )
+                    .await;
+                    break 'outer;
+                }
+            }
+        }
         log::info!(
-            "{}: Sent on route. Total: {}ms",
+            "{}: Sent {}/{} part(s). Total: {}ms",
             task_ident,
+            sent_parts.len(),
+            parts.len(),
             now.elapsed().as_millis()
         );
 
-        match waitsendpay_response(
-            plugin.clone(),
-            &config,
-            send_response.payment_hash,
-            &task_ident,
-            now,
-            job,
-            &route,
-            &mut success_route,
-        )
-        .await
-        {
-            Ok(o) => {
-                rebalanced_msat += o;
-                if job.onceamount_msat.is_some() {
+        if sent_parts.is_empty() {
+            if is_mpp {
+                plugin.state().mpp_pays.lock().remove(&payment_hash.to_string());
+                if let Err(e) = sync_mpp_pays_to_disk(&plugin, &config.sling_dir).await {
+                    log::warn!("{task_ident}: could not persist in-flight MPP state: {e}");
+                }
+            }
+            for r in &reserved_parts {
+                release_reservation(plugin.state(), r);
+            }
+            drop(send_permit);
+            my_sleep(plugin.clone(), 10, &task_ident).await;
+            success_route = None;
+            continue 'outer;
+        }
+
+        if !is_mpp {
+            // Single-part jobs keep the exact pre-MPP behavior: wait inline (no concurrency to
+            // gain with one part) and let `waitsendpay_response` drive the real `success_route`
+            // so a repeat attempt on the same channel can skip re-running dijkstra.
+            let (part, resp) = &sent_parts[0];
+            match waitsendpay_response(
+                plugin.clone(),
+                &config,
+                resp.payment_hash,
+                None,
+                &task_ident,
+                now,
+                job,
+                part,
+                &mut success_route,
+            )
+            .await
+            {
+                Ok((amount_msat, _fee_msat)) => {
+                    rebalanced_msat += amount_msat;
+                    drop(send_permit);
+                    for r in &reserved_parts {
+                        release_reservation(plugin.state(), r);
+                    }
+                    if job.onceamount_msat.is_some() {
+                        break 'outer;
+                    }
+                    let attempt_ms = u64::try_from(now.elapsed().as_millis()).unwrap_or(u64::MAX);
+                    let tranquility_delay = {
+                        let mut tasks = plugin.state().tasks.lock();
+                        if let Some(t) = tasks.get_task_mut(&task_ident) {
+                            t.set_last_attempt_ms(attempt_ms);
+                            t.record_rebalance_success();
+                        }
+                        Duration::from_secs_f64(config.tranquility * attempt_ms as f64 / 1000.0)
+                    };
+                    time::sleep(tranquility_delay).await;
+                }
+                Err(e) => {
+                    for r in &reserved_parts {
+                        release_reservation(plugin.state(), r);
+                    }
+                    let mut tasks = plugin.state().tasks.lock();
+                    tasks.record_exit_failure(
+                        &task_ident,
+                        e.to_string(),
+                        config.job_retry_base_secs,
+                        config.job_retry_max_secs,
+                        config.job_retry_max_attempts,
+                    );
+                    drop(tasks);
+                    log::warn!("{e}");
+                    notify_rebalance(
+                        &plugin,
+                        &task_ident,
+                        job.sat_direction,
+                        JobMessage::Error,
+                        None,
+                        None,
+                    )
+                    .await;
                     break 'outer;
                 }
-                time::sleep(Duration::from_secs(1)).await;
             }
-            Err(e) => {
-                let mut tasks = plugin.state().tasks.lock();
-                tasks.set_state(&task_ident, JobMessage::Error);
-                tasks.set_active(&task_ident, false);
-                log::warn!("{e}");
-                break 'outer;
+            continue 'outer;
+        }
+
+        // MPP parts wait concurrently: each is an independent sendpay/waitsendpay against CLN,
+        // so there's no reason to serialize them, and every sibling needs to be held open at
+        // once for `htlc_handler`'s hold to have a chance of seeing them all land together.
+        let wait_results = {
+            let mut waits = Vec::with_capacity(sent_parts.len());
+            for (i, (part, resp)) in sent_parts.iter().enumerate() {
+                let plugin_owned = plugin.clone();
+                let config_owned = config.clone();
+                let task_ident_owned = task_ident;
+                let job_owned = job.clone();
+                let route_owned = part.clone();
+                let payment_hash_owned = resp.payment_hash;
+                let partid_groupid = Some((u64::try_from(i).unwrap_or(u64::MAX) + 1, 1));
+                waits.push(tokio::spawn(async move {
+                    let mut dummy_success_route: Option<Vec<SendpayRoute>> = None;
+                    waitsendpay_response(
+                        plugin_owned,
+                        &config_owned,
+                        payment_hash_owned,
+                        partid_groupid,
+                        &task_ident_owned,
+                        now,
+                        &job_owned,
+                        &route_owned,
+                        &mut dummy_success_route,
+                    )
+                    .await
+                }));
+            }
+            let mut results = Vec::with_capacity(waits.len());
+            for w in waits {
+                results.push(w.await.unwrap_or_else(|e| {
+                    Err(anyhow!("{task_ident}: waitsendpay task panicked: {e}"))
+                }));
+            }
+            results
+        };
+
+        let mut mpp_received_msat = 0u64;
+        let mut mpp_fee_msat = 0u64;
+        let mut hard_error = None;
+        for r in wait_results {
+            match r {
+                Ok((amount_msat, fee_msat)) => {
+                    mpp_received_msat += amount_msat;
+                    mpp_fee_msat += fee_msat;
+                }
+                Err(e) => {
+                    log::warn!("{task_ident}: MPP part failed: {e}");
+                    hard_error.get_or_insert(e);
+                }
             }
+        }
+
+        plugin
+            .state()
+            .mpp_pays
+            .lock()
+            .remove(&payment_hash.to_string());
+        if let Err(e) = sync_mpp_pays_to_disk(&plugin, &config.sling_dir).await {
+            log::warn!("{task_ident}: could not persist in-flight MPP state: {e}");
+        }
+        for r in &reserved_parts {
+            release_reservation(plugin.state(), r);
+        }
+        drop(send_permit);
+
+        if let Some(e) = hard_error {
+            let mut tasks = plugin.state().tasks.lock();
+            tasks.record_exit_failure(
+                &task_ident,
+                e.to_string(),
+                config.job_retry_base_secs,
+                config.job_retry_max_secs,
+                config.job_retry_max_attempts,
+            );
+            drop(tasks);
+            log::warn!("{e}");
+            notify_rebalance(
+                &plugin,
+                &task_ident,
+                job.sat_direction,
+                JobMessage::Error,
+                None,
+                None,
+            )
+            .await;
+            break 'outer;
+        }
+
+        if mpp_received_msat == 0 {
+            // No part delivered anything, and `waitsendpay_response` has already written a
+            // `FailureReb` for each part that failed, so there is no success to record.
+            continue 'outer;
+        }
+
+        // Every part resolved, so the group as a whole succeeded: write a single aggregate
+        // `SuccessReb` here rather than the one-per-part records `waitsendpay_response` writes
+        // for single-part jobs, so MPP rebalances don't multiply-count in the stats.
+        let channel_partner = match job.sat_direction {
+            SatDirection::Pull => representative.first().unwrap().channel,
+            SatDirection::Push => representative.last().unwrap().channel,
         };
+        let hops = u8::try_from(representative.len() - 1)?;
+        let fee_ppm =
+            feeppm_effective_from_amts(mpp_received_msat + mpp_fee_msat, mpp_received_msat);
+        SuccessReb {
+            amount_msat: mpp_received_msat,
+            fee_ppm,
+            channel_partner,
+            hops,
+            completed_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            task_id: task_ident.get_task_id(),
+            sat_direction: job.sat_direction,
+            route: representative.iter().map(|hop| hop.channel).collect(),
+        }
+        .write_to_file(task_ident.get_chan_id(), &config.sling_dir)
+        .await?;
+        notify_rebalance_outcome(
+            &plugin,
+            &task_ident,
+            channel_partner,
+            mpp_received_msat,
+            fee_ppm,
+            hops,
+            None,
+        )
+        .await;
+
+        rebalanced_msat += mpp_received_msat;
+        if job.onceamount_msat.is_some() && mpp_received_msat > 0 {
+            break 'outer;
+        }
+        let attempt_ms = u64::try_from(now.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let tranquility_delay = {
+            let mut tasks = plugin.state().tasks.lock();
+            if let Some(t) = tasks.get_task_mut(&task_ident) {
+                t.set_last_attempt_ms(attempt_ms);
+                t.record_rebalance_success();
+            }
+            Duration::from_secs_f64(config.tranquility * attempt_ms as f64 / 1000.0)
+        };
+        time::sleep(tranquility_delay).await;
     }
     plugin
         .state()
@@ -348,12 +807,13 @@ pub async fn sling(
 }
 
 fn build_except_chans(
-    tempbans: &HashMap<ShortChannelId, u64>,
+    tempbans: &HashMap<ShortChannelId, ChannelBackoff>,
+    excluded_scids: &HashSet<ShortChannelId>,
     config: &Config,
     job: &Job,
 ) -> Vec<ShortChannelIdDir> {
     let mut excepts = Vec::new();
-    for scid in tempbans.keys() {
+    for scid in tempbans.keys().chain(excluded_scids.iter()) {
         excepts.push(ShortChannelIdDir {
             short_channel_id: *scid,
             direction: 0,
@@ -393,6 +853,32 @@ fn build_except_chans(
     excepts
 }
 
+/// Sets or clears `task.parallel_ban` from `route`'s middle hop, the same heuristic
+/// `next_route` has always used to stop two attempts on the same job from racing each other
+/// down an identical path next iteration. For a multi-part split, only the first part is
+/// considered: the other parts' middle hops are left free for the next attempt to reuse, since
+/// banning all of them tends to starve the split of viable disjoint paths.
+fn update_parallel_ban(task: &mut Task, graph: &LnGraph, config: &Config, route: &[SendpayRoute]) {
+    if route.len() >= 3 {
+        let route_claim_chan = route[route.len() / 2].channel;
+        let route_claim_peer = route[(route.len() / 2) - 1].id;
+        if let Ok((dir_chan, dir_chan_state)) = graph.get_state_no_direction(
+            &PubKeyBytes::from_pubkey(&route_claim_peer),
+            route_claim_chan,
+        ) {
+            if dir_chan_state.source != config.pubkey_bytes
+                && dir_chan_state.destination != config.pubkey_bytes
+            {
+                task.parallel_ban = Some(dir_chan);
+            } else {
+                task.parallel_ban = None;
+            }
+        };
+    } else {
+        task.parallel_ban = None;
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn next_route(
     plugin: &Plugin<PluginState>,
@@ -403,8 +889,10 @@ fn next_route(
     task_ident: &TaskIdentifier,
     success_route: &mut Option<Vec<SendpayRoute>>,
     actual_candidates: &[ShortChannelId],
-) -> Result<Vec<SendpayRoute>, Error> {
+) -> Result<Vec<Vec<SendpayRoute>>, Error> {
     let graph = plugin.state().graph.lock();
+    let liquidity = plugin.state().liquidity.lock();
+    let reservations = plugin.state().reservations.lock();
 
     let mut tasks = plugin.state().tasks.lock();
     let mut task_bans = tasks.get_parallelbans(task_ident.get_chan_id())?;
@@ -412,6 +900,76 @@ fn next_route(
         .get_task_mut(task_ident)
         .ok_or_else(|| anyhow!("no task found"))?;
 
+    if job.get_parts() > 1 {
+        // The single-path `success_route` cache doesn't carry over to a multi-part split (it
+        // would only ever match one of the parts), so MPP jobs always re-run the search.
+        *success_route = None;
+        if let Some(pb) = task.parallel_ban {
+            task_bans.remove(&pb);
+        }
+        task.parallel_ban = None;
+        excepts.extend(task_bans);
+
+        if job.get_splitonfail() {
+            // Only split once a single route for the full amount can't be found, instead of
+            // unconditionally paying the extra fee/HTLC-count overhead of a multi-part send.
+            let whole_route = dijkstra_cached(
+                config,
+                &graph,
+                job,
+                task,
+                actual_candidates,
+                excepts,
+                &liquidity,
+                &reservations,
+            )?;
+            if !whole_route.is_empty() {
+                update_parallel_ban(task, &graph, config, &whole_route);
+                return Ok(vec![whole_route]);
+            }
+        }
+
+        let mut parts = dijkstra_mpp(
+            config,
+            &graph,
+            job,
+            task,
+            actual_candidates,
+            excepts,
+            &liquidity,
+            &reservations,
+        )?;
+
+        let minprobability = job.get_minprobability();
+        if !parts.is_empty() && minprobability > 0.0 {
+            for part in &parts {
+                let probability = route_success_probability(
+                    part,
+                    &graph,
+                    Amount::msat(&part.last().unwrap().amount_msat),
+                    &liquidity,
+                    config.liquidity_halflife,
+                );
+                if probability < minprobability {
+                    log::debug!(
+                        "{task_ident}: discarding split route with estimated success \
+                        probability {probability:.3} below minprobability {minprobability}",
+                    );
+                    parts = Vec::new();
+                    break;
+                }
+            }
+        }
+
+        if let Some(first) = parts.first() {
+            update_parallel_ban(task, &graph, config, first);
+        } else {
+            task.parallel_ban = None;
+        }
+
+        return Ok(parts);
+    }
+
     let mut route = Vec::new();
     if let Some(prev_route) = success_route {
         if match job.sat_direction {
@@ -456,37 +1014,101 @@ fn next_route(
 
         excepts.extend(task_bans);
 
-        let liquidity = plugin.state().liquidity.lock();
-
-        route = dijkstra(
-            config,
-            &graph,
-            job,
-            task,
-            actual_candidates,
-            excepts,
-            &liquidity,
-        )?;
-    }
-    if route.len() >= 3 {
-        let route_claim_chan = route[route.len() / 2].channel;
-        let route_claim_peer = route[(route.len() / 2) - 1].id;
-        if let Ok((dir_chan, dir_chan_state)) = graph.get_state_no_direction(
-            &PubKeyBytes::from_pubkey(&route_claim_peer),
-            route_claim_chan,
-        ) {
-            if dir_chan_state.source != config.pubkey_bytes
-                && dir_chan_state.destination != config.pubkey_bytes
+        // Alt routes were ranked under whatever ban set was current at the `k_shortest_paths`
+        // call that produced them; bans picked up since then (a fresh tempban from the very
+        // failure that triggered this retry, a newly excluded peer, ...) can make a queued
+        // route no longer valid, so drop anything that now crosses a banned channel instead of
+        // retrying straight into the same wall.
+        let banned_scids: HashSet<ShortChannelId> =
+            excepts.iter().map(|e| e.short_channel_id).collect();
+        let mut alt_route = None;
+        while let Some(candidate) = task.alt_routes.pop_front() {
+            if candidate
+                .iter()
+                .any(|hop| banned_scids.contains(&hop.channel))
             {
-                task.parallel_ban = Some(dir_chan);
-            } else {
-                task.parallel_ban = None;
+                continue;
             }
-        };
-    } else {
-        task.parallel_ban = None;
+            alt_route = Some(candidate);
+            break;
+        }
+
+        if let Some(alt_route) = alt_route {
+            // Fall back to a route precomputed by the last full search instead of paying for
+            // another one; `k_shortest_paths` already ranked it behind the route we just tried.
+            route = alt_route;
+        } else {
+            let mut k_paths = k_shortest_paths(
+                config,
+                &graph,
+                job,
+                task,
+                actual_candidates,
+                excepts,
+                &liquidity,
+                &reservations,
+                NEXT_ROUTE_ALT_PATHS + 1,
+            )?;
+            route = if k_paths.is_empty() {
+                Vec::new()
+            } else {
+                task.alt_routes.extend(k_paths.drain(1..));
+                k_paths.remove(0)
+            };
+        }
+    }
+
+    let minprobability = job.get_minprobability();
+    if !route.is_empty() && minprobability > 0.0 {
+        let probability =
+            route_success_probability(&route, &graph, job.amount_msat, &liquidity, config.liquidity_halflife);
+        if probability < minprobability {
+            log::debug!(
+                "{task_ident}: discarding route with estimated success probability {:.3} below minprobability {}",
+                probability,
+                minprobability
+            );
+            route = Vec::new();
+        }
     }
-    Ok(route)
+
+    update_parallel_ban(task, &graph, config, &route);
+    Ok(vec![route])
+}
+
+/// Heals a channel partner's connection before sling gives up and sleeps: CLN only reconnects
+/// on its own when it was the side that dropped the socket, so a peer stuck with
+/// `peer_connected: false` can otherwise sit there until the remote end reconnects on its own
+/// schedule. Mirrors the autoreconnect-on-startup behavior LDK-based nodes perform by forcing a
+/// disconnect to clear any stale socket state, looking the peer's advertised address up via
+/// `listnodes`, then reconnecting to it directly.
+async fn heal_peer_connection(config: &Config, peer_id: PublicKey) -> Result<(), Error> {
+    let mut rpc = ClnRpc::new(&config.rpc_path).await?;
+
+    let address = rpc
+        .call_typed(&ListnodesRequest { id: Some(peer_id) })
+        .await?
+        .nodes
+        .into_iter()
+        .find(|node| node.nodeid == peer_id)
+        .and_then(|node| node.addresses)
+        .into_iter()
+        .flatten()
+        .find_map(|addr| addr.address.map(|host| (host, addr.port)))
+        .ok_or_else(|| anyhow!("no advertised address for peer {peer_id}"))?;
+
+    // Best-effort: the peer may already be disconnected, in which case this just errors.
+    let _ = rpc.call_typed(&DisconnectRequest { id: peer_id }).await;
+
+    rpc.call_typed(&ConnectRequest {
+        id: peer_id.to_string(),
+        host: Some(address.0),
+        port: Some(address.1),
+    })
+    .await?;
+
+    log::info!("healed connection to peer {peer_id}");
+    Ok(())
 }
 
 async fn health_check(
@@ -543,7 +1165,10 @@ async fn health_check(
         .find(|x| x.peer_id == other_peer.to_pubkey())
     {
         if !p.peer_connected {
-            log::info!("{task_ident}: not connected. Taking a break...");
+            log::info!("{task_ident}: not connected. Attempting to heal connection...");
+            if let Err(e) = heal_peer_connection(config, other_peer.to_pubkey()).await {
+                log::warn!("{task_ident}: failed to heal connection to peer: {e}");
+            }
             plugin
                 .state()
                 .tasks
@@ -571,6 +1196,18 @@ async fn health_check(
                 .set_state(task_ident, JobMessage::PeerBad);
             my_sleep(plugin.clone(), 60, task_ident).await;
             Ok(Some(true))
+        } else if config.coordinate_rebalances
+            && !negotiate_rebalance(&plugin, config, task_ident, job, other_peer.to_pubkey())
+                .await?
+        {
+            log::info!("{task_ident}: peer declined rebalance coordination. Taking a break...");
+            plugin
+                .state()
+                .tasks
+                .lock()
+                .set_state(task_ident, JobMessage::PeerNotReady);
+            my_sleep(plugin.clone(), 20, task_ident).await;
+            Ok(Some(true))
         } else {
             Ok(None)
         }
@@ -583,6 +1220,7 @@ async fn health_check(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_candidatelist(
     plugin: &Plugin<PluginState>,
     peer_channels: &HashMap<ShortChannelId, ListpeerchannelsChannels>,
@@ -590,10 +1228,13 @@ fn build_candidatelist(
     job: &Job,
     excepts: &[ShortChannelIdDir],
     bad_fwd_nodes: &HashMap<PublicKey, u64>,
+    excluded_nodes: &HashSet<PublicKey>,
     config: &Config,
 ) -> Result<Vec<ShortChannelId>, Error> {
     let blockheight = *plugin.state().blockheight.lock();
-    let mut candidatelist = Vec::<ShortChannelId>::new();
+    let graph = plugin.state().graph.lock();
+    let reservations = plugin.state().reservations.lock();
+    let mut candidates: Vec<(ShortChannelId, ShortChannelIdDir, u64)> = Vec::new();
     let custom_candidates = job.get_candidates();
 
     let depleteuptopercent = job.get_depleteuptopercent(config.depleteuptopercent);
@@ -614,6 +1255,14 @@ fn build_candidatelist(
             continue;
         }
 
+        if excluded_nodes.contains(&channel.peer_id) {
+            log::trace!(
+                "{task_ident}: build_candidatelist: {} is a permanently excluded node",
+                channel.peer_id
+            );
+            continue;
+        }
+
         if !custom_candidates.is_empty() {
             if custom_candidates.contains(&scid) {
                 log::trace!("{task_ident}: build_candidatelist: found custom candidate {scid}");
@@ -683,8 +1332,21 @@ fn build_candidatelist(
             }
         };
 
-        let to_us_msat = Amount::msat(&channel.to_us_msat.unwrap());
+        let scid_dir = match job.sat_direction {
+            SatDirection::Pull => ShortChannelIdDir {
+                short_channel_id: scid,
+                direction,
+            },
+            SatDirection::Push => ShortChannelIdDir {
+                short_channel_id: scid,
+                direction: direction ^ 1,
+            },
+        };
+        let reserved = reservations.get(&scid_dir).copied().unwrap_or_default();
+
         let total_msat = Amount::msat(&channel.total_msat.unwrap());
+        let usable_msat =
+            usable_liquidity_msat(channel, job.sat_direction).saturating_sub(reserved.reserved_msat);
         let chan_out_ppm = feeppm_effective(
             channel.fee_proportional_millionths.unwrap(),
             u32::try_from(Amount::msat(&channel.fee_base_msat.unwrap()))?,
@@ -700,10 +1362,10 @@ fn build_candidatelist(
                         depleteuptoamount,
                     ),
                 );
-                if to_us_msat <= liquidity_target {
+                if usable_msat <= liquidity_target {
                     log::trace!(
                         "{task_ident}: build_candidatelist: {scid} does not have enough \
-                        liquidity: {to_us_msat}<={liquidity_target}"
+                        usable liquidity: {usable_msat}<={liquidity_target}"
                     );
                     continue;
                 }
@@ -725,12 +1387,12 @@ fn build_candidatelist(
                         depleteuptoamount,
                     ),
                 );
-                if total_msat - to_us_msat <= liquidity_target {
+                if usable_msat <= liquidity_target {
                     log::trace!(
-                        "{}: build_candidatelist: {} does not have enough liquidity: {}<={}",
+                        "{}: build_candidatelist: {} does not have enough usable liquidity: {}<={}",
                         task_ident,
                         scid,
-                        total_msat - to_us_msat,
+                        usable_msat,
                         liquidity_target
                     );
                     continue;
@@ -757,13 +1419,114 @@ fn build_candidatelist(
             }
         }
 
-        if get_total_htlc_count(channel) > config.max_htlc_count {
-            log::trace!("{task_ident}: build_candidatelist: {scid} has too many pending htlcs");
+        if get_total_htlc_count(channel) + reserved.reserved_htlcs > config.max_htlc_count {
+            log::trace!(
+                "{task_ident}: build_candidatelist: {scid} has too many pending htlcs \
+                (including {} reserved by other jobs)",
+                reserved.reserved_htlcs
+            );
             continue;
         }
 
-        candidatelist.push(scid);
+        // A channel can have the liquidity but still be unable to move `job.amount_msat` in a
+        // single hop if the amount falls outside its advertised per-direction HTLC bounds.
+        if let Some(state) = graph.get_state(scid_dir) {
+            let htlc_maximum_msat = Amount::msat(&state.htlc_maximum_msat);
+            let htlc_minimum_msat = Amount::msat(&state.htlc_minimum_msat);
+            if job.amount_msat < htlc_minimum_msat || job.amount_msat > htlc_maximum_msat {
+                log::trace!(
+                    "{task_ident}: build_candidatelist: {scid_dir} amount {} is outside \
+                    htlc bounds [{htlc_minimum_msat}, {htlc_maximum_msat}]",
+                    job.amount_msat
+                );
+                continue;
+            }
+        }
+
+        candidates.push((scid, scid_dir, chan_out_ppm));
+    }
+
+    // Prefer candidates our blended liquidity estimate (learned bounds + historical-bucket
+    // histogram, see `edge_success_probability`) believes are most likely to carry
+    // `job.amount_msat`, weighed against their effective fee ppm (`sling-candidate-fee-weight`,
+    // see `candidate_rank_score`), so dijkstra's own per-candidate search order isn't the only
+    // place that benefits from what we've learned about a channel's typical liquidity.
+    let liquidity = plugin.state().liquidity.lock();
+    candidates.sort_by(|(_, a, a_ppm), (_, b, b_ppm)| {
+        let score = |scid_dir: &ShortChannelIdDir, ppm: u64| {
+            let htlc_maximum_msat = graph
+                .get_state(*scid_dir)
+                .map_or(0, |s| Amount::msat(&s.htlc_maximum_msat));
+            let probability = edge_success_probability(
+                htlc_maximum_msat,
+                job.amount_msat,
+                liquidity.get(scid_dir),
+                config.liquidity_halflife,
+            );
+            candidate_rank_score(probability, ppm, config.candidate_fee_weight)
+        };
+        score(a, *a_ppm)
+            .partial_cmp(&score(b, *b_ppm))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    drop(liquidity);
+    drop(graph);
+    drop(reservations);
+
+    Ok(candidates.into_iter().map(|(scid, ..)| scid).collect())
+}
+
+/// Confirms `build_candidatelist`'s heuristic ranking against a real route search, dropping any
+/// candidate `dijkstra_per_candidate` can't actually reach (e.g. a channel that scores well on
+/// learned liquidity but whose only path out is blocked by `excepts`/tempbans), instead of
+/// letting `next_route` discover that one candidate at a time. Runs the per-candidate searches
+/// on `config.candidate_workers` threads, which is otherwise unused once there's only one
+/// candidate left to try.
+#[allow(clippy::too_many_arguments)]
+fn filter_reachable_candidates(
+    plugin: &Plugin<PluginState>,
+    config: &Config,
+    job: &Job,
+    task: &Task,
+    temp_chan_bans: &HashMap<ShortChannelId, ChannelBackoff>,
+    excepts: &[ShortChannelIdDir],
+    candidates: Vec<ShortChannelId>,
+) -> Vec<ShortChannelId> {
+    if candidates.len() <= 1 {
+        return candidates;
     }
 
-    Ok(candidatelist)
+    let graph = plugin.state().graph.lock();
+    let liquidity = plugin.state().liquidity.lock();
+    let reservations = plugin.state().reservations.lock();
+    let tempbans: HashMap<ShortChannelId, u64> = temp_chan_bans
+        .iter()
+        .map(|(scid, backoff)| (*scid, backoff.banned_until))
+        .collect();
+    let parallel_bans: HashSet<ShortChannelIdDir> = excepts.iter().copied().collect();
+
+    let results = dijkstra_per_candidate(
+        config,
+        &graph,
+        job,
+        task,
+        &tempbans,
+        &parallel_bans,
+        &liquidity,
+        &reservations,
+        &candidates,
+        config.candidate_workers,
+    );
+    drop(reservations);
+    drop(liquidity);
+    drop(graph);
+
+    let reachable: HashSet<ShortChannelId> = results
+        .into_iter()
+        .filter_map(|(scid, route)| route.map(|_| scid))
+        .collect();
+    candidates
+        .into_iter()
+        .filter(|scid| reachable.contains(scid))
+        .collect()
 }
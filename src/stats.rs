@@ -1,6 +1,9 @@
-use std::cmp::max;
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::{collections::HashMap, path::Path, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    str::FromStr,
+};
 
 use anyhow::{anyhow, Error};
 use chrono::Local;
@@ -12,8 +15,8 @@ use cln_rpc::primitives::{PublicKey, ShortChannelId};
 use num_format::{Locale, ToFormattedString};
 use serde_json::json;
 use sling::{
-    ChannelPartnerStats, FailureReasonCount, FailuresInTimeWindow, PeerPartnerStats, SlingStats,
-    SuccessesInTimeWindow,
+    BucketedRebalanceStats, ChannelPartnerStats, FailureReasonCount, FailuresInTimeWindow,
+    LifetimeStats, PeerPartnerStats, SlingStats, SuccessesInTimeWindow,
 };
 use tabled::settings::{Panel, Rotate};
 use tabled::Table;
@@ -30,7 +33,7 @@ pub async fn slingstats(
 
     let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
 
-    let (scid, json_summary) = match args {
+    let (scid, json_summary, start, limit, since, bucket, csv) = match args {
         serde_json::Value::Array(a) => {
             if a.len() > 2 {
                 return Err(anyhow!(
@@ -38,9 +41,9 @@ pub async fn slingstats(
                 ));
             }
             if a.is_empty() {
-                (None, false)
+                (None, false, 0, 0, 0, None, false)
             } else if let Some(flag) = a.first().unwrap().as_bool() {
-                (None, flag)
+                (None, flag, 0, 0, 0, None, false)
             } else {
                 let scid = match a.first().unwrap() {
                     serde_json::Value::String(i) => ShortChannelId::from_str(i)?,
@@ -55,7 +58,7 @@ pub async fn slingstats(
                         ))
                     }
                 };
-                (Some(scid), json_flag)
+                (Some(scid), json_flag, 0, 0, 0, None, false)
             }
         }
         serde_json::Value::Object(o) => {
@@ -73,7 +76,51 @@ pub async fn slingstats(
                     ))
                 }
             };
-            (scid, json_summary)
+            let start = match o.get("start") {
+                Some(v) => v
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("invalid `start`, not a number"))?,
+                None => 0,
+            };
+            let limit = match o.get("limit") {
+                Some(v) => v
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("invalid `limit`, not a number"))?,
+                None => 0,
+            };
+            let since = match o.get("since") {
+                Some(v) => v
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("invalid `since`, not a number"))?,
+                None => 0,
+            };
+            if (start != 0 || limit != 0 || since != 0) && scid.is_none() {
+                return Err(anyhow!(
+                    "`start`, `limit`, and `since` require `scid` to be set"
+                ));
+            }
+            let bucket = match o.get("bucket") {
+                Some(serde_json::Value::String(b)) => match b.as_str() {
+                    "day" => Some(24 * 60 * 60),
+                    "hour" => Some(60 * 60),
+                    _ => return Err(anyhow!("invalid `bucket`, must be `day` or `hour`")),
+                },
+                None => None,
+                _ => return Err(anyhow!("invalid `bucket`, not a string")),
+            };
+            let csv = match o.get("csv") {
+                Some(serde_json::Value::Bool(i)) => *i,
+                None => false,
+                _ => {
+                    return Err(anyhow!(
+                        "invalid `csv` flag, not a bool. Use `true` or `false`"
+                    ))
+                }
+            };
+            if bucket.is_some() && scid.is_none() {
+                return Err(anyhow!("`bucket` requires `scid` to be set"));
+            }
+            (scid, json_summary, start, limit, since, bucket, csv)
         }
         e => {
             return Err(anyhow!(
@@ -90,6 +137,22 @@ pub async fn slingstats(
     let peer_channels = plugin.state().peer_channels.lock().clone();
 
     if let Some(s) = scid {
+        if limit > 0 {
+            // Streams the requested page straight off disk instead of materializing the
+            // channel's whole history, so a large `sling-stats-delete-*-size` doesn't spike
+            // allocator pressure just to answer one paginated query.
+            let (successes_page, successes_more) =
+                SuccessReb::read_page_from_file(&sling_dir, s, since, start, limit).await?;
+            let (failures_page, failures_more) =
+                FailureReb::read_page_from_file(&sling_dir, s, since, start, limit).await?;
+            return Ok(json!({
+                "successes": successes_page,
+                "failures": failures_page,
+                "next_start": start + limit,
+                "has_more": successes_more || failures_more,
+            }));
+        }
+
         let successes = SuccessReb::read_from_files(&sling_dir, Some(s))
             .await
             .unwrap_or_default();
@@ -113,6 +176,40 @@ pub async fn slingstats(
         };
         log::debug!("failures: {} scid:{}", successes.len(), s);
 
+        if let Some(bucket_secs) = bucket {
+            let rows = bucketed_stats(&successes_vec, &failures_vec, bucket_secs);
+            return Ok(if csv {
+                json!({"format-hint": "simple", "result": bucketed_stats_to_csv(&rows)})
+            } else if json_summary {
+                json!(rows)
+            } else {
+                let mut table = Table::new(rows);
+                table.with(Panel::header("Rebalance stats by time bucket"));
+                json!({"format-hint": "simple", "result": table.to_string()})
+            });
+        }
+
+        let total_rebalances = successes_vec.len() as u64;
+        let total_failed_attempts = failures_vec.len() as u64;
+        let lifetime_stats = if total_rebalances == 0 && total_failed_attempts == 0 {
+            None
+        } else {
+            Some(LifetimeStats {
+                total_rebalances,
+                total_failed_attempts,
+                total_rebalanced_sats: successes_vec.iter().map(|s| s.amount_msat / 1_000).sum(),
+                total_fees_paid_sats: successes_vec
+                    .iter()
+                    .map(|s| (s.amount_msat / 1_000) * s.fee_ppm as u64 / 1_000_000)
+                    .sum(),
+                avg_attempts_per_success: if total_rebalances == 0 {
+                    0.0
+                } else {
+                    (total_rebalances + total_failed_attempts) as f64 / total_rebalances as f64
+                },
+            })
+        };
+
         let success_stats = success_stats(
             successes_vec,
             stats_delete_successes_age,
@@ -130,6 +227,7 @@ pub async fn slingstats(
             let sling_stats = SlingStats {
                 successes_in_time_window: success_stats,
                 failures_in_time_window: failure_stats,
+                lifetime_stats,
             };
 
             Ok(json!(sling_stats))
@@ -150,8 +248,16 @@ pub async fn slingstats(
             } else {
                 String::new()
             };
+            let lifetime_str = if let Some(ls) = lifetime_stats {
+                let mut lifetime_tabled = Table::new(vec![ls]);
+                lifetime_tabled.with(Rotate::Left);
+                lifetime_tabled.with(Panel::header("Lifetime stats"));
+                lifetime_tabled.to_string()
+            } else {
+                String::new()
+            };
 
-            Ok(json!({"format-hint":"simple","result":format!("{}\n{}", succ_str, fail_str)}))
+            Ok(json!({"format-hint":"simple","result":format!("{}\n{}\n{}", succ_str, fail_str, lifetime_str)}))
         }
     } else {
         let successes = SuccessReb::read_from_files(&sling_dir, None).await?;
@@ -284,7 +390,189 @@ pub async fn slingstats(
     }
 }
 
-fn success_stats(
+/// Raw per-attempt rebalance history for `scid`, paged straight off the on-disk store rather
+/// than the pre-aggregated top-5 views [`slingstats`] returns. Unlike `sling-stats`' paginated
+/// mode, `outcome` lets a caller fetch only successes or only failures instead of always paying
+/// for both pages.
+pub async fn slinghistory(
+    plugin: Plugin<PluginState>,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Error> {
+    let _rpc_lock = plugin.state().rpc_lock.lock().await;
+    let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+
+    let o = match args {
+        serde_json::Value::Object(o) => o,
+        e => {
+            return Err(anyhow!(
+                "sling-history: invalid arguments, expected object with `scid`, got: {}",
+                e
+            ))
+        }
+    };
+    let scid = match o.get("scid") {
+        Some(serde_json::Value::String(i)) => ShortChannelId::from_str(i)?,
+        _ => return Err(anyhow!("sling-history: `scid` is required")),
+    };
+    let since = match o.get("since") {
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| anyhow!("invalid `since`, not a number"))?,
+        None => 0,
+    };
+    let start = match o.get("start") {
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| anyhow!("invalid `start`, not a number"))?,
+        None => 0,
+    };
+    let limit = match o.get("limit") {
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| anyhow!("invalid `limit`, not a number"))?,
+        None => 1_000,
+    };
+    let outcome = match o.get("outcome") {
+        Some(serde_json::Value::String(s)) => match s.as_str() {
+            "success" | "failure" => Some(s.as_str()),
+            _ => {
+                return Err(anyhow!(
+                    "invalid `outcome`, expected `success` or `failure`"
+                ))
+            }
+        },
+        None => None,
+        _ => return Err(anyhow!("invalid `outcome`, not a string")),
+    };
+
+    let mut successes = Vec::new();
+    let mut failures = Vec::new();
+    let mut has_more = false;
+    if outcome != Some("failure") {
+        let (page, more) =
+            SuccessReb::read_page_from_file(&sling_dir, scid, since, start, limit).await?;
+        successes = page;
+        has_more |= more;
+    }
+    if outcome != Some("success") {
+        let (page, more) =
+            FailureReb::read_page_from_file(&sling_dir, scid, since, start, limit).await?;
+        failures = page;
+        has_more |= more;
+    }
+
+    Ok(json!({
+        "successes": successes,
+        "failures": failures,
+        "next_start": start + limit,
+        "has_more": has_more,
+    }))
+}
+
+/// Jain & Chlamtac's P² algorithm: estimates the `p`-quantile of a stream in O(1) memory instead
+/// of buffering every observation and sorting, at the cost of an approximate (rather than exact)
+/// result once five or more samples have been seen. Buffers the first five raw samples to
+/// initialize its five markers; `success_stats` reuses that same buffer to report an exact value
+/// when a channel's whole history is under five rebalances.
+struct P2Quantile {
+    p: f64,
+    initial: Vec<f64>,
+    q: [f64; 5],
+    n: [i64; 5],
+    np: [f64; 5],
+    dn: [f64; 5],
+}
+
+impl P2Quantile {
+    fn new(p: f64) -> Self {
+        Self {
+            p,
+            initial: Vec::with_capacity(5),
+            q: [0.0; 5],
+            n: [0; 5],
+            np: [0.0; 5],
+            dn: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn add(&mut self, x: f64) {
+        if self.initial.len() < 5 {
+            self.initial.push(x);
+            if self.initial.len() == 5 {
+                self.initial.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                for i in 0..5 {
+                    self.q[i] = self.initial[i];
+                    self.n[i] = i as i64 + 1;
+                }
+                self.np = [
+                    1.0,
+                    1.0 + 2.0 * self.p,
+                    1.0 + 4.0 * self.p,
+                    3.0 + 2.0 * self.p,
+                    5.0,
+                ];
+            }
+            return;
+        }
+
+        let k = if x < self.q[0] {
+            self.q[0] = x;
+            0
+        } else if x >= self.q[4] {
+            self.q[4] = x;
+            3
+        } else {
+            (0..4)
+                .find(|&i| self.q[i] <= x && x < self.q[i + 1])
+                .unwrap_or(3)
+        };
+        for n_i in self.n.iter_mut().skip(k + 1) {
+            *n_i += 1;
+        }
+        for i in 0..5 {
+            self.np[i] += self.dn[i];
+        }
+
+        for i in 1..4 {
+            let d = self.np[i] - self.n[i] as f64;
+            if (d >= 1.0 && self.n[i + 1] - self.n[i] > 1)
+                || (d <= -1.0 && self.n[i - 1] - self.n[i] < -1)
+            {
+                let sign: i64 = if d >= 1.0 { 1 } else { -1 };
+                let sign_f = sign as f64;
+                let parabolic = self.q[i]
+                    + sign_f / (self.n[i + 1] - self.n[i - 1]) as f64
+                        * ((self.n[i] - self.n[i - 1] + sign) as f64 * (self.q[i + 1] - self.q[i])
+                            / (self.n[i + 1] - self.n[i]) as f64
+                            + (self.n[i + 1] - self.n[i] - sign) as f64
+                                * (self.q[i] - self.q[i - 1])
+                                / (self.n[i] - self.n[i - 1]) as f64);
+                self.q[i] = if self.q[i - 1] < parabolic && parabolic < self.q[i + 1] {
+                    parabolic
+                } else {
+                    let neighbor = (i as i64 + sign) as usize;
+                    self.q[i]
+                        + sign_f * (self.q[neighbor] - self.q[i])
+                            / (self.n[neighbor] - self.n[i]) as f64
+                };
+                self.n[i] += sign;
+            }
+        }
+    }
+
+    /// The estimated (or, with fewer than five samples seen, exact) `p`-quantile.
+    fn value(&self) -> f64 {
+        if self.initial.len() < 5 {
+            let mut sorted = self.initial.clone();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let idx = ((sorted.len() as f64 * self.p).ceil() as i64 - 1).max(0) as usize;
+            return sorted[idx.min(sorted.len() - 1)];
+        }
+        self.q[2]
+    }
+}
+
+pub fn success_stats(
     successes: Vec<SuccessReb>,
     time_window: u64,
     alias_map: &HashMap<PublicKey, String>,
@@ -299,7 +587,12 @@ fn success_stats(
     let mut most_recent_completed_at = 0;
     let mut total_transactions = 0;
     let mut weighted_fee_ppm = 0;
-    let mut fee_ppms = Vec::new();
+    let mut feeppm_min = u32::MAX;
+    let mut feeppm_max = 0;
+    let mut p50 = P2Quantile::new(0.5);
+    let mut p90 = P2Quantile::new(0.9);
+    let mut p95 = P2Quantile::new(0.95);
+    let mut p99 = P2Quantile::new(0.99);
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
@@ -315,14 +608,19 @@ fn success_stats(
             *hop_counts.entry(success_reb.hops).or_insert(0) += 1;
             most_recent_completed_at =
                 std::cmp::max(most_recent_completed_at, success_reb.completed_at);
-            fee_ppms.push(success_reb.fee_ppm);
+            feeppm_min = feeppm_min.min(success_reb.fee_ppm);
+            feeppm_max = feeppm_max.max(success_reb.fee_ppm);
+            let fee_ppm = success_reb.fee_ppm as f64;
+            p50.add(fee_ppm);
+            p90.add(fee_ppm);
+            p95.add(fee_ppm);
+            p99.add(fee_ppm);
             total_transactions += 1_u64;
         }
     }
     if total_transactions == 0 {
         return None;
     }
-    fee_ppms.sort();
     let most_common_hop_count = hop_counts
         .into_iter()
         .max_by_key(|&(_, count)| count)
@@ -338,8 +636,6 @@ fn success_stats(
     } else {
         &channel_partners[..]
     };
-    let feeppm_90th_percentile =
-        fee_ppms[max(0, (fee_ppms.len() as f64 * 0.9).ceil() as i32 - 1) as usize];
     let time_of_last_rebalance = Local
         .timestamp_opt(most_recent_completed_at as i64, 0)
         .unwrap()
@@ -354,10 +650,12 @@ fn success_stats(
         },
         total_amount_sats: total_amount_msat / 1_000,
         feeppm_weighted_avg: weighted_fee_ppm,
-        feeppm_min: *fee_ppms.iter().min().unwrap(),
-        feeppm_max: *fee_ppms.iter().max().unwrap(),
-        feeppm_median: fee_ppms[fee_ppms.len() / 2],
-        feeppm_90th_percentile,
+        feeppm_min,
+        feeppm_max,
+        feeppm_median: p50.value().round() as u32,
+        feeppm_90th_percentile: p90.value().round() as u32,
+        feeppm_95th: p95.value().round() as u32,
+        feeppm_99th: p99.value().round() as u32,
         top_5_channel_partners: top_5_channel_partners
             .iter()
             .map(|(partner, count)| ChannelPartnerStats {
@@ -375,7 +673,7 @@ fn success_stats(
     Some(successes_in_time_window)
 }
 
-fn failure_stats(
+pub fn failure_stats(
     failures: Vec<FailureReb>,
     time_window: u64,
     alias_map: &HashMap<PublicKey, String>,
@@ -460,7 +758,7 @@ fn failure_stats(
         top_5_failure_reasons: top_5_failure_reasons
             .iter()
             .map(|(reason, count)| FailureReasonCount {
-                failure_reason: reason.clone(),
+                failure_reason: reason.to_string(),
                 failure_count: *count,
             })
             .collect::<Vec<_>>(),
@@ -491,7 +789,87 @@ fn failure_stats(
     Some(failures_in_time_window)
 }
 
-fn get_stats_alias(
+struct RebalanceBucket {
+    total_amount_msat: u64,
+    weighted_fee_ppm_numerator: u64,
+    total_rebalances: u64,
+    total_failed_attempts: u64,
+}
+
+/// Partitions `successes`/`failures` into fixed-width `bucket_secs` windows keyed by
+/// `completed_at`/`created_at`, for `sling-stats`' `bucket=day`/`bucket=hour` mode. Unlike
+/// [`success_stats`]/[`failure_stats`], which collapse a whole window into one row, this keeps
+/// one row per bucket so the series can be charted.
+fn bucketed_stats(
+    successes: &[SuccessReb],
+    failures: &[FailureReb],
+    bucket_secs: u64,
+) -> Vec<BucketedRebalanceStats> {
+    let mut buckets: BTreeMap<u64, RebalanceBucket> = BTreeMap::new();
+
+    for success in successes {
+        let entry = buckets
+            .entry(success.completed_at / bucket_secs)
+            .or_insert(RebalanceBucket {
+                total_amount_msat: 0,
+                weighted_fee_ppm_numerator: 0,
+                total_rebalances: 0,
+                total_failed_attempts: 0,
+            });
+        entry.total_amount_msat += success.amount_msat;
+        entry.weighted_fee_ppm_numerator += success.fee_ppm as u64 * success.amount_msat;
+        entry.total_rebalances += 1;
+    }
+    for failure in failures {
+        let entry = buckets
+            .entry(failure.created_at / bucket_secs)
+            .or_insert(RebalanceBucket {
+                total_amount_msat: 0,
+                weighted_fee_ppm_numerator: 0,
+                total_rebalances: 0,
+                total_failed_attempts: 0,
+            });
+        entry.total_failed_attempts += 1;
+    }
+
+    buckets
+        .into_iter()
+        .map(|(bucket, b)| BucketedRebalanceStats {
+            time_bucket: Local
+                .timestamp_opt((bucket * bucket_secs) as i64, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "invalid".to_string()),
+            total_amount_sats: b.total_amount_msat / 1_000,
+            feeppm_weighted_avg: if b.total_amount_msat == 0 {
+                0
+            } else {
+                b.weighted_fee_ppm_numerator / b.total_amount_msat
+            },
+            total_rebalances: b.total_rebalances,
+            total_failed_attempts: b.total_failed_attempts,
+        })
+        .collect()
+}
+
+fn bucketed_stats_to_csv(rows: &[BucketedRebalanceStats]) -> String {
+    let mut csv = String::from(
+        "time_bucket,total_amount_sats,feeppm_weighted_avg,total_rebalances,total_failed_attempts\n",
+    );
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            row.time_bucket,
+            row.total_amount_sats,
+            row.feeppm_weighted_avg,
+            row.total_rebalances,
+            row.total_failed_attempts
+        ));
+    }
+    csv
+}
+
+pub(crate) fn get_stats_alias(
     peer_channels: &HashMap<ShortChannelId, ListpeerchannelsChannels>,
     partner: &ShortChannelId,
     alias_map: &HashMap<PublicKey, String>,
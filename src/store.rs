@@ -0,0 +1,387 @@
+//! Sled-backed storage for [`crate::model::SuccessReb`]/[`crate::model::FailureReb`] history.
+//!
+//! Replaces the append-only `*_successes.json`/`*_failures.json` files: every write used to
+//! require opening (or creating) a per-channel file, and every read loaded that whole file into
+//! memory and re-parsed every line. Here, each kind gets its own sled tree keyed by
+//! `scid || 0x00 || timestamp || seq`, so a per-channel scan or a time-ranged query is an
+//! indexed range lookup instead of a full-file load, and writes go through sled's crash-safe
+//! log rather than a bare append.
+//!
+//! [`migrate_legacy_files`] does a one-time import of the old JSON-lines files into the store
+//! the first time it's opened, then renames them aside so the import doesn't re-run.
+
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Error};
+use cln_rpc::primitives::ShortChannelId;
+use parking_lot::Mutex;
+use sled::Db;
+use std::sync::OnceLock;
+
+use crate::model::{FailureReb, SuccessReb, FAILURES_SUFFIX, SUCCESSES_SUFFIX};
+
+pub const STATS_DB_DIR_NAME: &str = "stats_db";
+const SUCCESSES_TREE: &str = "successes";
+const FAILURES_TREE: &str = "failures";
+
+fn stats_db_handle() -> &'static Mutex<Option<Db>> {
+    static HANDLE: OnceLock<Mutex<Option<Db>>> = OnceLock::new();
+    HANDLE.get_or_init(|| Mutex::new(None))
+}
+
+/// Returns the process-wide handle to the stats sled database rooted at `sling_dir`, opening it
+/// (and migrating any legacy JSON-lines files in) on first use.
+fn open_db(sling_dir: &Path) -> Result<Db, Error> {
+    let mut guard = stats_db_handle().lock();
+    if let Some(db) = guard.as_ref() {
+        return Ok(db.clone());
+    }
+    let db_path = sling_dir.join(STATS_DB_DIR_NAME);
+    let db = sled::open(&db_path)
+        .map_err(|e| anyhow!("failed to open stats store at {}: {e}", db_path.display()))?;
+    migrate_legacy_files(sling_dir, &db)?;
+    *guard = Some(db.clone());
+    Ok(db)
+}
+
+/// `scid || 0x00 || timestamp (big-endian) || seq (big-endian)`. The `0x00` separator can't
+/// appear in a scid's string form, so `scid_prefix` below is an unambiguous prefix for every key
+/// belonging to that channel, and keys for one channel sort in timestamp order.
+fn make_key(scid: ShortChannelId, timestamp: u64, seq: u64) -> Vec<u8> {
+    let mut key = scid.to_string().into_bytes();
+    key.push(0);
+    key.extend_from_slice(&timestamp.to_be_bytes());
+    key.extend_from_slice(&seq.to_be_bytes());
+    key
+}
+
+fn scid_prefix(scid: ShortChannelId) -> Vec<u8> {
+    let mut prefix = scid.to_string().into_bytes();
+    prefix.push(0);
+    prefix
+}
+
+fn scid_from_key(key: &[u8]) -> Option<ShortChannelId> {
+    let sep = key.iter().position(|&b| b == 0)?;
+    ShortChannelId::from_str(std::str::from_utf8(&key[..sep]).ok()?).ok()
+}
+
+pub fn insert_success(sling_dir: &Path, scid: ShortChannelId, reb: &SuccessReb) -> Result<(), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(SUCCESSES_TREE)?;
+    let key = make_key(scid, reb.completed_at, db.generate_id()?);
+    tree.insert(key, serde_json::to_vec(reb)?)?;
+    tree.flush()?;
+    Ok(())
+}
+
+pub fn insert_failure(sling_dir: &Path, scid: ShortChannelId, reb: &FailureReb) -> Result<(), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(FAILURES_TREE)?;
+    let key = make_key(scid, reb.created_at, db.generate_id()?);
+    tree.insert(key, serde_json::to_vec(reb)?)?;
+    tree.flush()?;
+    Ok(())
+}
+
+/// Scans the successes tree, restricted to `search_scid`'s key range when given (an indexed
+/// prefix scan) or the whole tree otherwise.
+pub fn scan_successes(
+    sling_dir: &Path,
+    search_scid: Option<ShortChannelId>,
+) -> Result<HashMap<ShortChannelId, Vec<SuccessReb>>, Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(SUCCESSES_TREE)?;
+    let mut result: HashMap<ShortChannelId, Vec<SuccessReb>> = HashMap::new();
+    let entries = match search_scid {
+        Some(scid) => tree.scan_prefix(scid_prefix(scid)),
+        None => tree.iter(),
+    };
+    for entry in entries {
+        let (key, value) = entry?;
+        let Some(scid) = scid_from_key(&key) else {
+            continue;
+        };
+        let Ok(reb) = serde_json::from_slice::<SuccessReb>(&value) else {
+            continue;
+        };
+        result.entry(scid).or_default().push(reb);
+    }
+    Ok(result)
+}
+
+/// Scans the failures tree, restricted to `search_scid`'s key range when given (an indexed
+/// prefix scan) or the whole tree otherwise.
+pub fn scan_failures(
+    sling_dir: &Path,
+    search_scid: Option<ShortChannelId>,
+) -> Result<HashMap<ShortChannelId, Vec<FailureReb>>, Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(FAILURES_TREE)?;
+    let mut result: HashMap<ShortChannelId, Vec<FailureReb>> = HashMap::new();
+    let entries = match search_scid {
+        Some(scid) => tree.scan_prefix(scid_prefix(scid)),
+        None => tree.iter(),
+    };
+    for entry in entries {
+        let (key, value) = entry?;
+        let Some(scid) = scid_from_key(&key) else {
+            continue;
+        };
+        let Ok(reb) = serde_json::from_slice::<FailureReb>(&value) else {
+            continue;
+        };
+        result.entry(scid).or_default().push(reb);
+    }
+    Ok(result)
+}
+
+/// Pages through `chan_id`'s successes in timestamp order via an indexed prefix scan, skipping
+/// records older than `since` and the first `start` matching records, stopping once `limit`
+/// records have been collected. Returns the page plus whether more matching records remain.
+pub fn page_successes(
+    sling_dir: &Path,
+    chan_id: ShortChannelId,
+    since: u64,
+    start: u64,
+    limit: u64,
+) -> Result<(Vec<SuccessReb>, bool), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(SUCCESSES_TREE)?;
+    let mut skipped = 0_u64;
+    let mut page = Vec::new();
+    let mut has_more = false;
+    for entry in tree.scan_prefix(scid_prefix(chan_id)) {
+        let (_, value) = entry?;
+        let Ok(reb) = serde_json::from_slice::<SuccessReb>(&value) else {
+            continue;
+        };
+        if since != 0 && reb.completed_at < since {
+            continue;
+        }
+        if skipped < start {
+            skipped += 1;
+            continue;
+        }
+        if page.len() as u64 >= limit {
+            has_more = true;
+            break;
+        }
+        page.push(reb);
+    }
+    Ok((page, has_more))
+}
+
+/// Pages through `chan_id`'s failures, see [`page_successes`].
+pub fn page_failures(
+    sling_dir: &Path,
+    chan_id: ShortChannelId,
+    since: u64,
+    start: u64,
+    limit: u64,
+) -> Result<(Vec<FailureReb>, bool), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(FAILURES_TREE)?;
+    let mut skipped = 0_u64;
+    let mut page = Vec::new();
+    let mut has_more = false;
+    for entry in tree.scan_prefix(scid_prefix(chan_id)) {
+        let (_, value) = entry?;
+        let Ok(reb) = serde_json::from_slice::<FailureReb>(&value) else {
+            continue;
+        };
+        if since != 0 && reb.created_at < since {
+            continue;
+        }
+        if skipped < start {
+            skipped += 1;
+            continue;
+        }
+        if page.len() as u64 >= limit {
+            has_more = true;
+            break;
+        }
+        page.push(reb);
+    }
+    Ok((page, has_more))
+}
+
+/// Distinct scids with at least one persisted success, read straight off the keys (sled keeps
+/// them sorted by scid prefix) so callers that only need "which channels have history", such as
+/// [`crate::tasks::clear_stats`], aren't forced to deserialize every record's body just to find
+/// out which channels exist.
+pub fn success_scids(sling_dir: &Path) -> Result<Vec<ShortChannelId>, Error> {
+    distinct_scids(sling_dir, SUCCESSES_TREE)
+}
+
+/// Distinct scids with at least one persisted failure, see [`success_scids`].
+pub fn failure_scids(sling_dir: &Path) -> Result<Vec<ShortChannelId>, Error> {
+    distinct_scids(sling_dir, FAILURES_TREE)
+}
+
+fn distinct_scids(sling_dir: &Path, tree_name: &str) -> Result<Vec<ShortChannelId>, Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(tree_name)?;
+    let mut scids = Vec::new();
+    let mut last_prefix: Option<Vec<u8>> = None;
+    for key in tree.iter().keys() {
+        let key = key?;
+        let Some(scid) = scid_from_key(&key) else {
+            continue;
+        };
+        let prefix = scid_prefix(scid);
+        if last_prefix.as_deref() != Some(prefix.as_slice()) {
+            scids.push(scid);
+            last_prefix = Some(prefix);
+        }
+    }
+    Ok(scids)
+}
+
+/// Prunes `chan_id`'s successes: entries older than `age_cutoff` (if given) are dropped, then
+/// the oldest remaining entries beyond `max_count` (if given) are dropped too. Since a channel's
+/// keys already sort in timestamp order, both passes are plain key-range deletes rather than a
+/// read-modify-rewrite of the whole file, as the old per-channel JSON-lines compaction needed.
+/// Returns `(removed_for_age, removed_for_size)`.
+pub fn prune_successes(
+    sling_dir: &Path,
+    chan_id: ShortChannelId,
+    age_cutoff: Option<u64>,
+    max_count: Option<u64>,
+) -> Result<(usize, usize), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(SUCCESSES_TREE)?;
+    let mut kept_keys = Vec::new();
+    let mut removed_for_age = 0;
+    for entry in tree.scan_prefix(scid_prefix(chan_id)) {
+        let (key, value) = entry?;
+        let keep = match age_cutoff {
+            Some(cutoff) => match serde_json::from_slice::<SuccessReb>(&value) {
+                Ok(reb) => reb.completed_at >= cutoff,
+                Err(_) => true,
+            },
+            None => true,
+        };
+        if keep {
+            kept_keys.push(key);
+        } else {
+            tree.remove(&key)?;
+            removed_for_age += 1;
+        }
+    }
+    let removed_for_size = prune_oldest_beyond(&tree, &kept_keys, max_count)?;
+    tree.flush()?;
+    Ok((removed_for_age, removed_for_size))
+}
+
+/// Prunes `chan_id`'s failures, see [`prune_successes`].
+pub fn prune_failures(
+    sling_dir: &Path,
+    chan_id: ShortChannelId,
+    age_cutoff: Option<u64>,
+    max_count: Option<u64>,
+) -> Result<(usize, usize), Error> {
+    let db = open_db(sling_dir)?;
+    let tree = db.open_tree(FAILURES_TREE)?;
+    let mut kept_keys = Vec::new();
+    let mut removed_for_age = 0;
+    for entry in tree.scan_prefix(scid_prefix(chan_id)) {
+        let (key, value) = entry?;
+        let keep = match age_cutoff {
+            Some(cutoff) => match serde_json::from_slice::<FailureReb>(&value) {
+                Ok(reb) => reb.created_at >= cutoff,
+                Err(_) => true,
+            },
+            None => true,
+        };
+        if keep {
+            kept_keys.push(key);
+        } else {
+            tree.remove(&key)?;
+            removed_for_age += 1;
+        }
+    }
+    let removed_for_size = prune_oldest_beyond(&tree, &kept_keys, max_count)?;
+    tree.flush()?;
+    Ok((removed_for_age, removed_for_size))
+}
+
+/// `kept_keys` is already in timestamp order (sled keys sort by byte order, and
+/// [`make_key`] puts the big-endian timestamp right after the scid), so trimming down to
+/// `max_count` is just removing the earliest entries, no re-sort needed.
+fn prune_oldest_beyond(
+    tree: &sled::Tree,
+    kept_keys: &[sled::IVec],
+    max_count: Option<u64>,
+) -> Result<usize, Error> {
+    let Some(max_count) = max_count else {
+        return Ok(0);
+    };
+    let max_count = max_count as usize;
+    if kept_keys.len() <= max_count {
+        return Ok(0);
+    }
+    let overflow = kept_keys.len() - max_count;
+    for key in &kept_keys[..overflow] {
+        tree.remove(key)?;
+    }
+    Ok(overflow)
+}
+
+/// One-time import of the legacy `*_successes.json`/`*_failures.json` files into `db`, run the
+/// first time the store is opened in a given `sling_dir`. Imported files are renamed with a
+/// `.migrated` suffix rather than deleted, so the import is both idempotent (a renamed file is
+/// no longer picked up by the `_successes.json`/`_failures.json` glob below) and reversible.
+fn migrate_legacy_files(sling_dir: &Path, db: &Db) -> Result<(), Error> {
+    let read_dir = match std::fs::read_dir(sling_dir) {
+        Ok(d) => d,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in read_dir {
+        let entry = entry?;
+        let file_name_os = entry.file_name();
+        let Some(file_name) = file_name_os.to_str() else {
+            continue;
+        };
+        let Some((scid_str, suffix)) = file_name.split_once('_') else {
+            continue;
+        };
+        let Ok(scid) = ShortChannelId::from_str(scid_str) else {
+            continue;
+        };
+        let is_successes = suffix == SUCCESSES_SUFFIX;
+        let is_failures = suffix == FAILURES_SUFFIX;
+        if !is_successes && !is_failures {
+            continue;
+        }
+        let path = entry.path();
+        let contents = std::fs::read_to_string(&path)?;
+        if is_successes {
+            let tree = db.open_tree(SUCCESSES_TREE)?;
+            for line in contents.lines() {
+                let Ok(reb) = serde_json::from_str::<SuccessReb>(line) else {
+                    continue;
+                };
+                let key = make_key(scid, reb.completed_at, db.generate_id()?);
+                tree.insert(key, serde_json::to_vec(&reb)?)?;
+            }
+        } else {
+            let tree = db.open_tree(FAILURES_TREE)?;
+            for line in contents.lines() {
+                let Ok(reb) = serde_json::from_str::<FailureReb>(line) else {
+                    continue;
+                };
+                let key = make_key(scid, reb.created_at, db.generate_id()?);
+                tree.insert(key, serde_json::to_vec(&reb)?)?;
+            }
+        }
+        log::info!(
+            "stats store: migrated legacy file {} into sled",
+            path.display()
+        );
+        std::fs::rename(&path, path.with_extension("json.migrated"))?;
+    }
+    db.flush()?;
+    Ok(())
+}
@@ -1,26 +1,59 @@
 use std::{
-    collections::hash_map,
+    collections::{hash_map, HashMap},
     fs::File,
-    io::BufReader,
+    io::{BufReader, Seek, SeekFrom},
     path::Path,
+    str::FromStr,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error};
 use cln_plugin::Plugin;
 use cln_rpc::{
-    model::requests::{AskrenelistlayersRequest, ListnodesRequest, ListpeerchannelsRequest},
-    primitives::{Amount, ChannelState, ShortChannelIdDir},
+    model::{
+        requests::{
+            AskreneinformchannelInform,
+            AskreneinformchannelRequest,
+            AskrenecreatelayerRequest,
+            AskrenelistlayersRequest,
+            ListnodesRequest,
+            ListpeerchannelsRequest,
+            ListsendpaysRequest,
+        },
+        responses::{ListpeerchannelsChannels, ListsendpaysPaymentsStatus},
+    },
+    primitives::{Amount, ChannelState, Sha256, ShortChannelId, ShortChannelIdDir},
     ClnRpc,
 };
 
 use tokio::{
-    fs::OpenOptions,
-    io::AsyncWriteExt,
+    sync::Notify,
     time::{self, Instant},
 };
 
-use crate::{gossip::read_gossip_store, model::*};
+use sling::ExceptDirection;
+
+use crate::{
+    gossip::read_gossip_store,
+    model::*,
+    probe::run_probe_round,
+    response::channel_capacity_msat,
+    store,
+    util::{
+        append_liquidity_update,
+        load_graph_snapshot,
+        now_secs,
+        read_except_chans,
+        read_except_peers,
+        read_mpp_pay_records,
+        reset_liquidity_if_capacity_changed,
+        save_graph_snapshot,
+        write_except_chans,
+        write_except_peers,
+        write_liquidity,
+    },
+};
 
 pub async fn refresh_aliasmap(plugin: Plugin<PluginState>) -> Result<(), Error> {
     loop {
@@ -44,6 +77,7 @@ pub async fn refresh_aliasmap(plugin: Plugin<PluginState>) -> Result<(), Error>
             "Refreshing alias map done in {}ms!",
             now.elapsed().as_millis()
         );
+        record_refresh_duration_ms(plugin.state(), "aliasmap", now.elapsed());
         time::sleep(Duration::from_secs(interval)).await;
     }
 }
@@ -71,7 +105,7 @@ pub async fn refresh_listpeerchannels(plugin: Plugin<PluginState>) -> Result<(),
     let mut rpc = ClnRpc::new(&rpc_path).await?;
 
     let now = Instant::now();
-    *plugin.state().peer_channels.lock() = rpc
+    let new_channels: HashMap<ShortChannelId, ListpeerchannelsChannels> = rpc
         .call_typed(&ListpeerchannelsRequest {
             id: None,
             short_channel_id: None,
@@ -81,6 +115,21 @@ pub async fn refresh_listpeerchannels(plugin: Plugin<PluginState>) -> Result<(),
         .into_iter()
         .filter_map(|channel| channel.short_channel_id.map(|id| (id, channel)))
         .collect();
+
+    let mut peer_channels = plugin.state().peer_channels.lock();
+    let balance_changed = new_channels.iter().any(|(scid, chan)| {
+        peer_channels
+            .get(scid)
+            .is_none_or(|old| old.spendable_msat != chan.spendable_msat)
+    });
+    *peer_channels = new_channels;
+    drop(peer_channels);
+    if balance_changed {
+        // A local balance moved since the last poll, e.g. a forward or a settled rebalance, so a
+        // job sleeping on NoRoute/NoCandidates might now have a path it didn't before.
+        plugin.state().wake.notify_waiters();
+    }
+
     log::trace!("Peerchannels refreshed in {}ms", now.elapsed().as_millis());
     Ok(())
 }
@@ -89,11 +138,27 @@ pub async fn refresh_graph(plugin: Plugin<PluginState>) -> Result<(), Error> {
     let my_pubkey = plugin.state().config.lock().pubkey_bytes;
     let mut is_startup = true;
 
-    let gossip_file =
-        File::open(Path::new(&plugin.configuration().lightning_dir).join("gossip_store"))?;
+    let gossip_store_path = Path::new(&plugin.configuration().lightning_dir).join("gossip_store");
+    let gossip_file = File::open(&gossip_store_path)?;
 
     let mut reader = BufReader::new(gossip_file);
 
+    let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+    if let Some(snapshot) = load_graph_snapshot(&sling_dir, &gossip_store_path).await {
+        if reader.seek(SeekFrom::Start(snapshot.offset)).is_ok() {
+            *plugin.state().graph.lock() = snapshot.graph;
+            *plugin.state().incomplete_channels.lock() = snapshot.incomplete_channels;
+            *plugin.state().gossip_store_offset.lock() = snapshot.offset;
+            is_startup = false;
+            log::info!(
+                "Loaded graph snapshot, resuming gossip_store parse at offset {}",
+                snapshot.offset
+            );
+        } else {
+            log::warn!("could not seek gossip_store to saved graph snapshot offset, doing a full reparse");
+        }
+    }
+
     loop {
         let interval;
         {
@@ -105,11 +170,15 @@ pub async fn refresh_graph(plugin: Plugin<PluginState>) -> Result<(), Error> {
             {
                 log::debug!("Getting all channels in gossip_store...");
                 read_gossip_store(plugin.clone(), &mut reader, &mut is_startup).await?;
+                if let Ok(offset) = reader.stream_position() {
+                    *plugin.state().gossip_store_offset.lock() = offset;
+                }
                 log::debug!(
                     "Reading gossip store done after {}ms!",
                     now.elapsed().as_millis()
                 );
 
+                let was_empty = plugin.state().graph.lock().is_empty();
                 let mut lngraph = plugin.state().graph.lock();
                 log::debug!(
                     "{} public channels in sling graph after {}ms!",
@@ -220,44 +289,158 @@ pub async fn refresh_graph(plugin: Plugin<PluginState>) -> Result<(), Error> {
                     lngraph.private_channel_count(),
                     now.elapsed().as_millis()
                 );
+
+                if was_empty && !lngraph.is_empty() {
+                    // A job asleep on GraphEmpty/ChanNotInGraph can now actually search for a
+                    // route, so don't make it wait out the rest of its sleep to find that out.
+                    plugin.state().wake.notify_waiters();
+                }
             }
             log::debug!("Refreshed graph in {}ms!", now.elapsed().as_millis());
+            record_refresh_duration_ms(plugin.state(), "graph", now.elapsed());
         }
         time::sleep(Duration::from_secs(interval)).await;
     }
 }
 
+/// Periodically probes one channel lacking a fresh liquidity belief with an unreachable
+/// payment hash (`sling-probe-enabled`/`sling-probe-interval-secs`), so the graph gets learned
+/// proactively during idle periods instead of only from real rebalance attempts. See
+/// [`crate::probe::run_probe_round`].
+pub async fn run_liquidity_probes(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    loop {
+        let config = plugin.state().config.lock().clone();
+        if config.probe_enabled {
+            if let Err(e) = run_probe_round(&plugin, &config).await {
+                log::warn!("Error running liquidity probe: {e}");
+            }
+        }
+        time::sleep(Duration::from_secs(config.probe_interval_secs)).await;
+    }
+}
+
+/// Instead of dropping a stale [`Liquidity`] belief outright, decays both of its bounds toward
+/// full uncertainty (`min_liquidity_msat`→0, `liquidity_msat`→the channel's capacity) by the
+/// same `0.5^(elapsed/sling-liquidity-halflife)` factor [`crate::util::edge_success_probability`]
+/// already applies live at query time, and rolls that elapsed decay into the stored bounds
+/// (resetting `liquidity_age` so it isn't applied twice). This way a belief older than
+/// `sling-reset-liquidity-interval` softens gradually the longer nothing refreshes it, instead
+/// of vanishing and forcing every consumer back to total uncertainty in one step.
 pub async fn refresh_liquidity(plugin: Plugin<PluginState>) -> Result<(), Error> {
     loop {
         {
-            let interval = plugin.state().config.lock().reset_liquidity_interval;
+            let (interval, halflife, sling_dir) = {
+                let config = plugin.state().config.lock();
+                (
+                    config.reset_liquidity_interval,
+                    config.liquidity_halflife,
+                    config.sling_dir.clone(),
+                )
+            };
             let now = Instant::now();
             let timestamp = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs();
-            let mut liquidity = plugin.state().liquidity.lock();
-            liquidity.retain(|_, v| v.liquidity_age > timestamp - interval * 60);
+
+            let stale: Vec<(ShortChannelIdDir, Liquidity)> = plugin
+                .state()
+                .liquidity
+                .lock()
+                .iter()
+                .filter(|(_, v)| timestamp.saturating_sub(v.liquidity_age) > interval)
+                .map(|(k, v)| (*k, *v))
+                .collect();
+
+            for (dir_chan, liq) in &stale {
+                let capacity_msat = channel_capacity_msat(&plugin, *dir_chan) as f64;
+                let age = timestamp.saturating_sub(liq.liquidity_age) as f64;
+                let decay = if halflife == 0 {
+                    0.0
+                } else {
+                    0.5_f64.powf(age / halflife as f64)
+                };
+                let max_msat = (liq.liquidity_msat as f64).min(capacity_msat);
+                let mut success_buckets = liq.success_buckets;
+                let mut fail_buckets = liq.fail_buckets;
+                for weight in success_buckets.iter_mut().chain(fail_buckets.iter_mut()) {
+                    *weight *= decay;
+                }
+                let mut decayed = Liquidity {
+                    liquidity_msat: (capacity_msat - (capacity_msat - max_msat) * decay) as u64,
+                    liquidity_age: timestamp,
+                    min_liquidity_msat: (liq.min_liquidity_msat as f64 * decay) as u64,
+                    capacity_msat: liq.capacity_msat,
+                    success_buckets,
+                    fail_buckets,
+                };
+                reset_liquidity_if_capacity_changed(&mut decayed, capacity_msat as u64);
+                plugin.state().liquidity.lock().insert(*dir_chan, decayed);
+                append_liquidity_update(&plugin, &sling_dir, *dir_chan, decayed).await?;
+            }
+
             log::info!(
-                "Refreshed {} liquidity beliefs in {}ms!",
-                liquidity.len(),
+                "Decayed {} stale liquidity beliefs in {}ms!",
+                stale.len(),
                 now.elapsed().as_millis()
             );
+            record_refresh_duration_ms(plugin.state(), "liquidity", now.elapsed());
         }
         time::sleep(Duration::from_secs(120)).await;
     }
 }
 
+/// Periodically folds the liquidity journal into a fresh snapshot
+/// (`sling-liquidity-compact-interval`), so it doesn't grow unbounded between the
+/// crash-recovery journal replays in [`crate::util::read_liquidity`].
+pub async fn compact_liquidity_journal(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    loop {
+        let interval = plugin.state().config.lock().liquidity_compact_interval;
+        write_liquidity(plugin.clone()).await?;
+        time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Periodically persists a [`crate::model::GraphSnapshot`] (`sling-graph-snapshot-interval`),
+/// so the next plugin restart can load it via [`load_graph_snapshot`] and resume parsing
+/// `gossip_store` from the recorded offset instead of reparsing the whole file from the start.
+pub async fn compact_graph_snapshot(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    loop {
+        let interval = plugin.state().config.lock().graph_snapshot_interval;
+        save_graph_snapshot(plugin.clone()).await?;
+        time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
 pub async fn clear_tempbans(plugin: Plugin<PluginState>) -> Result<(), Error> {
     loop {
         {
-            plugin.state().tempbans.lock().retain(|_c, t| {
-                *t > SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    - 600
-            })
+            let now = now_secs();
+            let mut any_expired = false;
+
+            let mut tempbans = plugin.state().tempbans.lock();
+            let before = tempbans.len();
+            tempbans.retain(|_c, t| *t > now - 600);
+            any_expired |= tempbans.len() != before;
+            drop(tempbans);
+
+            let mut temp_chan_bans = plugin.state().temp_chan_bans.lock();
+            let before = temp_chan_bans.len();
+            temp_chan_bans.retain(|_c, b| b.banned_until > now);
+            any_expired |= temp_chan_bans.len() != before;
+            drop(temp_chan_bans);
+
+            plugin
+                .state()
+                .bad_fwd_nodes
+                .lock()
+                .retain(|_n, t| *t > now - 600);
+
+            if any_expired {
+                // A channel that was unroutable a moment ago might be fine again, so let a
+                // sleeping job check instead of waiting out the rest of its timeout.
+                plugin.state().wake.notify_waiters();
+            }
         }
         time::sleep(Duration::from_secs(100)).await;
     }
@@ -268,8 +451,8 @@ pub async fn clear_stats(plugin: Plugin<PluginState>) -> Result<(), Error> {
     loop {
         {
             let now = Instant::now();
-            let successes = SuccessReb::read_from_files(&sling_dir, None).await?;
-            let failures = FailureReb::read_from_files(&sling_dir, None).await?;
+            let success_scids = store::success_scids(&sling_dir)?;
+            let failure_scids = store::failure_scids(&sling_dir)?;
 
             let stats_delete_successes_age =
                 plugin.state().config.lock().stats_delete_successes_age;
@@ -282,97 +465,30 @@ pub async fn clear_stats(plugin: Plugin<PluginState>) -> Result<(), Error> {
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs();
-            let succ_age = sys_time_now - stats_delete_successes_age * 24 * 60 * 60;
-            let fail_age = sys_time_now - stats_delete_failures_age * 24 * 60 * 60;
-            for (chan_id, rebs) in successes {
-                let rebs_len = rebs.len();
-                let filtered_rebs = if stats_delete_successes_age > 0 {
-                    rebs.into_iter()
-                        .filter(|c| c.completed_at >= succ_age)
-                        .collect::<Vec<SuccessReb>>()
-                } else {
-                    rebs
-                };
-                let filtered_rebs_len = filtered_rebs.len();
+            let succ_age = sys_time_now - stats_delete_successes_age;
+            let fail_age = sys_time_now - stats_delete_failures_age;
+            let succ_age_cutoff = (stats_delete_successes_age > 0).then_some(succ_age);
+            let succ_max_count = (stats_delete_successes_size > 0).then_some(stats_delete_successes_size);
+            for chan_id in &success_scids {
+                let (removed_age, removed_size) =
+                    store::prune_successes(&sling_dir, *chan_id, succ_age_cutoff, succ_max_count)?;
                 log::debug!(
-                    "{}: filtered {} success entries because of age",
-                    chan_id,
-                    rebs_len - filtered_rebs_len
+                    "{chan_id}: pruned {removed_age} success entries because of age, \
+                     {removed_size} because of size"
                 );
-                let pruned_rebs = if stats_delete_successes_size > 0
-                    && filtered_rebs_len as u64 > stats_delete_successes_size
-                {
-                    filtered_rebs
-                        .into_iter()
-                        .skip(filtered_rebs_len - stats_delete_successes_size as usize)
-                        .collect::<Vec<SuccessReb>>()
-                } else {
-                    filtered_rebs
-                };
-                log::debug!(
-                    "{}: filtered {} success entries because of size",
-                    chan_id,
-                    filtered_rebs_len - pruned_rebs.len()
-                );
-                let mut content: Vec<u8> = vec![];
-                for reb in &pruned_rebs {
-                    let serialized = serde_json::to_string(&reb)?;
-                    content.extend(format!("{}\n", serialized).as_bytes());
-                }
-
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(sling_dir.join(chan_id.to_string() + "_" + SUCCESSES_SUFFIX))
-                    .await?;
-                file.write_all(&content).await?;
             }
-            for (chan_id, rebs) in failures {
-                let rebs_len = rebs.len();
-                let filtered_rebs = if stats_delete_failures_age > 0 {
-                    rebs.into_iter()
-                        .filter(|c| c.created_at >= fail_age)
-                        .collect::<Vec<FailureReb>>()
-                } else {
-                    rebs
-                };
-                let filtered_rebs_len = filtered_rebs.len();
+            let fail_age_cutoff = (stats_delete_failures_age > 0).then_some(fail_age);
+            let fail_max_count = (stats_delete_failures_size > 0).then_some(stats_delete_failures_size);
+            for chan_id in &failure_scids {
+                let (removed_age, removed_size) =
+                    store::prune_failures(&sling_dir, *chan_id, fail_age_cutoff, fail_max_count)?;
                 log::debug!(
-                    "{}: filtered {} failure entries because of age",
-                    chan_id,
-                    rebs_len - filtered_rebs_len
+                    "{chan_id}: pruned {removed_age} failure entries because of age, \
+                     {removed_size} because of size"
                 );
-                let pruned_rebs = if stats_delete_failures_size > 0
-                    && filtered_rebs_len as u64 > stats_delete_failures_size
-                {
-                    filtered_rebs
-                        .into_iter()
-                        .skip(filtered_rebs_len - stats_delete_failures_size as usize)
-                        .collect::<Vec<FailureReb>>()
-                } else {
-                    filtered_rebs
-                };
-                log::debug!(
-                    "{}: filtered {} failure entries because of size",
-                    chan_id,
-                    filtered_rebs_len - pruned_rebs.len()
-                );
-                let mut content: Vec<u8> = vec![];
-                for reb in &pruned_rebs {
-                    let serialized = serde_json::to_string(&reb)?;
-                    content.extend(format!("{}\n", serialized).as_bytes());
-                }
-
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(sling_dir.join(chan_id.to_string() + "_" + FAILURES_SUFFIX))
-                    .await?;
-                file.write_all(&content).await?;
             }
             log::debug!("Pruned stats successfully in {}s!", now.elapsed().as_secs());
+            record_refresh_duration_ms(plugin.state(), "stats", now.elapsed());
         }
         time::sleep(Duration::from_secs(21_600)).await;
     }
@@ -380,7 +496,7 @@ pub async fn clear_stats(plugin: Plugin<PluginState>) -> Result<(), Error> {
 
 pub async fn read_askrene_liquidity(plugin: Plugin<PluginState>) -> Result<(), Error> {
     let rpc_path = plugin.state().config.lock().rpc_path.clone();
-    let interval = plugin.state().config.lock().reset_liquidity_interval * 60;
+    let interval = plugin.state().config.lock().reset_liquidity_interval;
     let mut rpc = ClnRpc::new(&rpc_path).await?;
     loop {
         {
@@ -424,9 +540,19 @@ pub async fn read_askrene_liquidity(plugin: Plugin<PluginState>) -> Result<(), E
                 match liquidity.entry(scid_dir) {
                     hash_map::Entry::Occupied(mut occupied_entry) => {
                         if occupied_entry.get().liquidity_age < belief_timestamp {
+                            // askrene doesn't carry a learned min bound or bucket histogram,
+                            // so keep whatever our own waitsendpay probes have established.
+                            let min_liquidity_msat = occupied_entry.get().min_liquidity_msat;
+                            let capacity_msat = occupied_entry.get().capacity_msat;
+                            let success_buckets = occupied_entry.get().success_buckets;
+                            let fail_buckets = occupied_entry.get().fail_buckets;
                             *occupied_entry.get_mut() = Liquidity {
                                 liquidity_msat: belief_maximum_msat,
                                 liquidity_age: belief_timestamp,
+                                min_liquidity_msat,
+                                capacity_msat,
+                                success_buckets,
+                                fail_buckets,
                             };
                         }
                     }
@@ -434,6 +560,10 @@ pub async fn read_askrene_liquidity(plugin: Plugin<PluginState>) -> Result<(), E
                         vacant_entry.insert(Liquidity {
                             liquidity_msat: belief_maximum_msat,
                             liquidity_age: belief_timestamp,
+                            min_liquidity_msat: 0,
+                            capacity_msat: 0,
+                            success_buckets: [0.0; LIQUIDITY_BUCKETS],
+                            fail_buckets: [0.0; LIQUIDITY_BUCKETS],
                         });
                     }
                 };
@@ -443,7 +573,250 @@ pub async fn read_askrene_liquidity(plugin: Plugin<PluginState>) -> Result<(), E
                 counter,
                 now.elapsed().as_millis()
             );
+            record_refresh_duration_ms(plugin.state(), "askrene_read", now.elapsed());
         }
         time::sleep(Duration::from_secs(60)).await;
     }
 }
+
+/// The mirror image of [`read_askrene_liquidity`]: instead of pulling the `xpay` layer's
+/// constraints in, pushes sling's own learned beliefs out into `sling-askrene-publish-layer`
+/// (`sling-askrene-publish-enabled`) as a min/max constraint per [`ShortChannelIdDir`], so
+/// askrene's other pathfinders (xpay, `getroutes`) can use what sling has learned too. Only
+/// (re-)publishes a belief once its `liquidity_age` is newer than what's already in the layer,
+/// so an idle belief isn't re-informed every tick.
+pub async fn publish_askrene_liquidity(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let rpc_path = plugin.state().config.lock().rpc_path.clone();
+    let mut rpc = ClnRpc::new(&rpc_path).await?;
+    let mut layer_created = false;
+    loop {
+        let (enabled, layer) = {
+            let config = plugin.state().config.lock();
+            (config.askrene_publish_enabled, config.askrene_publish_layer.clone())
+        };
+
+        if enabled {
+            if !layer_created {
+                if let Err(e) = rpc
+                    .call_typed(&AskrenecreatelayerRequest {
+                        layer: layer.clone(),
+                        persistent: Some(true),
+                    })
+                    .await
+                {
+                    log::debug!("publish_askrene_liquidity: could not create layer `{layer}` (it may already exist): {e}");
+                }
+                layer_created = true;
+            }
+
+            let now = Instant::now();
+            let already_published: HashMap<ShortChannelIdDir, u64> = rpc
+                .call_typed(&AskrenelistlayersRequest {
+                    layer: Some(layer.clone()),
+                })
+                .await?
+                .layers
+                .first()
+                .map(|l| {
+                    l.constraints
+                        .iter()
+                        .filter_map(|c| Some((c.short_channel_id_dir?, c.timestamp?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let beliefs: Vec<(ShortChannelIdDir, Liquidity)> = plugin
+                .state()
+                .liquidity
+                .lock()
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .collect();
+
+            let mut counter = 0;
+            for (dir_chan, liq) in beliefs {
+                if already_published
+                    .get(&dir_chan)
+                    .is_some_and(|ts| *ts >= liq.liquidity_age)
+                {
+                    continue;
+                }
+                rpc.call_typed(&AskreneinformchannelRequest {
+                    amount_msat: Some(Amount::from_msat(liq.min_liquidity_msat)),
+                    inform: Some(AskreneinformchannelInform::SUCCEEDED),
+                    short_channel_id_dir: Some(dir_chan),
+                    layer: layer.clone(),
+                })
+                .await?;
+                rpc.call_typed(&AskreneinformchannelRequest {
+                    amount_msat: Some(Amount::from_msat(liq.liquidity_msat)),
+                    inform: Some(AskreneinformchannelInform::CONSTRAINED),
+                    short_channel_id_dir: Some(dir_chan),
+                    layer: layer.clone(),
+                })
+                .await?;
+                counter += 1;
+            }
+            log::info!(
+                "Published {} liquidity beliefs to askrene layer `{}` in {}ms!",
+                counter,
+                layer,
+                now.elapsed().as_millis()
+            );
+            record_refresh_duration_ms(plugin.state(), "askrene_publish", now.elapsed());
+        }
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+const EXCEPT_EXPIRY_TICK_SECS: u64 = 60;
+
+/// Periodically sweeps time-boxed chan/peer exceptions, removing any that have passed
+/// their `expires_at` from both the persisted excepts files and the live `Config` sets.
+/// Permanent exceptions (no TTL) are never touched here.
+pub async fn clear_expired_excepts(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+    loop {
+        if let Err(e) = sweep_expired_excepts(&plugin, &sling_dir).await {
+            log::warn!("Error clearing expired excepts: {e:?}");
+        }
+        time::sleep(Duration::from_secs(EXCEPT_EXPIRY_TICK_SECS)).await;
+    }
+}
+
+async fn sweep_expired_excepts(
+    plugin: &Plugin<PluginState>,
+    sling_dir: &Path,
+) -> Result<(), Error> {
+    let now = now_secs();
+
+    let mut except_chans = read_except_chans(sling_dir).await?;
+    let expired_chans: Vec<_> = except_chans
+        .iter()
+        .filter(|(_, e)| e.is_expired(now))
+        .map(|(scid, e)| (*scid, e.direction))
+        .collect();
+    if !expired_chans.is_empty() {
+        let mut config = plugin.state().config.lock();
+        for (scid, direction) in &expired_chans {
+            except_chans.remove(scid);
+            match direction {
+                ExceptDirection::Pull => {
+                    config.exclude_chans_pull.remove(scid);
+                }
+                ExceptDirection::Push => {
+                    config.exclude_chans_push.remove(scid);
+                }
+                ExceptDirection::Both => {
+                    config.exclude_chans_pull.remove(scid);
+                    config.exclude_chans_push.remove(scid);
+                }
+            }
+            log::info!("{scid}: channel exception expired, re-enabling for rebalancing");
+        }
+        drop(config);
+        write_except_chans(&except_chans, sling_dir).await?;
+    }
+
+    let mut except_peers = read_except_peers(sling_dir).await?;
+    let expired_peers: Vec<_> = except_peers
+        .iter()
+        .filter(|(_, expiry)| expiry.is_some_and(|e| now >= e))
+        .map(|(id, _)| *id)
+        .collect();
+    if !expired_peers.is_empty() {
+        let mut config = plugin.state().config.lock();
+        for id in &expired_peers {
+            except_peers.remove(id);
+            config.exclude_peers.remove(&PubKeyBytes::from_pubkey(id));
+            log::info!("{id}: peer exception expired, re-enabling for rebalancing");
+        }
+        drop(config);
+        write_except_peers(&except_peers, sling_dir).await?;
+    }
+
+    Ok(())
+}
+
+/// Runs once at startup, after `sling_dir` is known but before jobs resume: reloads
+/// [`MppPayRecord`]s persisted by [`crate::util::sync_mpp_pays_to_disk`] and re-derives how
+/// much of each in-flight multi-part rebalance already landed from CLN's own `listsendpays`,
+/// rather than trusting `received_msat`/`resolved` from disk, since `listsendpays` is the
+/// actual source of truth for which parts of a split payment completed. Rebuilds
+/// [`PluginState::mpp_pays`] with a fresh [`Notify`] per entry so `htlc_handler` can keep
+/// tracking and resolving the remaining parts of jobs that were still mid-flight when the
+/// plugin restarted.
+pub async fn reconcile_mpp_pays(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let (sling_dir, rpc_path) = {
+        let config = plugin.state().config.lock();
+        (config.sling_dir.clone(), config.rpc_path.clone())
+    };
+
+    let records = read_mpp_pay_records(&sling_dir).await?;
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    let mut rpc = ClnRpc::new(&rpc_path).await?;
+    let mut mpp_pays = HashMap::new();
+    for (payment_hash_str, record) in records {
+        let payment_hash = match Sha256::from_str(&payment_hash_str) {
+            Ok(h) => h,
+            Err(e) => {
+                log::warn!(
+                    "reconcile_mpp_pays: dropping unparsable payment_hash {payment_hash_str}: {e}"
+                );
+                continue;
+            }
+        };
+
+        let received_msat = match rpc
+            .call_typed(&ListsendpaysRequest {
+                bolt11: None,
+                payment_hash: Some(payment_hash),
+                status: None,
+                index: None,
+                start: None,
+                limit: None,
+            })
+            .await
+        {
+            Ok(resp) => resp
+                .payments
+                .iter()
+                .filter(|p| p.status == ListsendpaysPaymentsStatus::COMPLETE)
+                .filter_map(|p| p.amount_msat.as_ref().map(Amount::msat))
+                .sum(),
+            Err(e) => {
+                log::warn!(
+                    "reconcile_mpp_pays: listsendpays failed for {payment_hash_str}: {e}, \
+                     assuming no parts landed yet"
+                );
+                0
+            }
+        };
+        let resolved = received_msat >= record.target_msat;
+
+        log::info!(
+            "reconcile_mpp_pays: restored in-flight MPP rebalance {payment_hash_str}, \
+             {received_msat}/{}msat landed so far",
+            record.target_msat
+        );
+
+        mpp_pays.insert(
+            payment_hash_str,
+            MppPay {
+                resolve: record.resolve,
+                target_msat: record.target_msat,
+                parts_expected: record.parts_expected,
+                received_msat,
+                resolved,
+                notify: Arc::new(Notify::new()),
+                part_timeout_secs: record.part_timeout_secs,
+            },
+        );
+    }
+
+    *plugin.state().mpp_pays.lock() = mpp_pays;
+    Ok(())
+}
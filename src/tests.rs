@@ -170,6 +170,441 @@ fn test_feeppm_effective_from_amts() {
     assert!(result1.is_err());
 }
 
+#[test]
+fn test_liquidity_uncertainty_penalty() {
+    use crate::model::Liquidity;
+    use crate::util::liquidity_uncertainty_penalty;
+
+    const HALF_LIFE: u64 = 60 * 60 * 12;
+    const PENALTY_MULTIPLIER: u64 = 200;
+
+    // no belief at all: flat mid-range penalty
+    let no_belief = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        None,
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert!(no_belief > 0);
+
+    // fresh belief with plenty of headroom above amount: cheaper than no belief
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let fresh_confident = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 1_000_000_000,
+            liquidity_age: now,
+            min_liquidity_msat: 0,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert!(fresh_confident < no_belief);
+
+    // fresh belief with the amount already below the learned min: no penalty at all
+    let fresh_safe = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 1_000_000_000,
+            liquidity_age: now,
+            min_liquidity_msat: 500_000_000,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert_eq!(fresh_safe, 0);
+
+    // fresh belief with only modest headroom above the amount: pricier than the confident
+    // case, but not so tight it saturates the penalty cap
+    let fresh_tight = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 555_555_556,
+            liquidity_age: now,
+            min_liquidity_msat: 0,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert!(fresh_tight > fresh_confident);
+
+    // fresh belief with the amount above the learned max: capped, not infinite, but still
+    // pricier than merely tight headroom
+    let fresh_unroutable = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 499_999_999,
+            liquidity_age: now,
+            min_liquidity_msat: 0,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert!(fresh_unroutable > fresh_tight);
+
+    // same tight belief, but aged a month: fully decayed back towards `[0, capacity]`, so
+    // it's now cheaper than both the fresh tight belief and the flat no-belief default
+    let stale_tight = liquidity_uncertainty_penalty(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 555_555_556,
+            liquidity_age: now - 60 * 60 * 24 * 30,
+            min_liquidity_msat: 0,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+        PENALTY_MULTIPLIER,
+    );
+    assert!(stale_tight < fresh_tight);
+    assert!(stale_tight < no_belief);
+}
+
+#[test]
+fn test_edge_success_probability() {
+    use crate::model::Liquidity;
+    use crate::util::edge_success_probability;
+
+    const HALF_LIFE: u64 = 60 * 60 * 12;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // amount at or below the learned min: certain
+    let safe = edge_success_probability(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 1_000_000_000,
+            liquidity_age: now,
+            min_liquidity_msat: 500_000_000,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+    );
+    assert_eq!(safe, 1.0);
+
+    // amount above the learned max: certain failure
+    let unroutable = edge_success_probability(
+        1_000_000_000,
+        500_000_000,
+        Some(&Liquidity {
+            liquidity_msat: 499_999_999,
+            liquidity_age: now,
+            min_liquidity_msat: 0,
+            capacity_msat: 0,
+            success_buckets: [0.0; 8],
+            fail_buckets: [0.0; 8],
+        }),
+        HALF_LIFE,
+    );
+    assert_eq!(unroutable, 0.0);
+
+    // no belief at all: amount at the midpoint of the assumed [0, capacity/2] range is a
+    // coin flip
+    let no_belief = edge_success_probability(1_000_000_000, 250_000_000, None, HALF_LIFE);
+    assert!((no_belief - 0.5).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_bucket_success_probability() {
+    use crate::model::Liquidity;
+    use crate::util::{bucket_success_probability, record_liquidity_bucket};
+
+    const HALF_LIFE: u64 = 60 * 60 * 12;
+    const CAPACITY_MSAT: u64 = 1_000_000_000;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // nothing recorded yet: no opinion
+    let empty = Liquidity {
+        liquidity_msat: CAPACITY_MSAT,
+        liquidity_age: now,
+        min_liquidity_msat: 0,
+        capacity_msat: 0,
+        success_buckets: [0.0; 8],
+        fail_buckets: [0.0; 8],
+    };
+    assert_eq!(
+        bucket_success_probability(Some(&empty), 500_000_000, CAPACITY_MSAT, HALF_LIFE),
+        None
+    );
+    assert_eq!(
+        bucket_success_probability(None, 500_000_000, CAPACITY_MSAT, HALF_LIFE),
+        None
+    );
+
+    // nine of ten recorded forwards near half capacity succeeded: high confidence around there
+    let mut mostly_successful = empty;
+    for _ in 0..9 {
+        record_liquidity_bucket(&mut mostly_successful, 500_000_000, CAPACITY_MSAT, true);
+    }
+    record_liquidity_bucket(&mut mostly_successful, 500_000_000, CAPACITY_MSAT, false);
+    let confident = bucket_success_probability(
+        Some(&mostly_successful),
+        500_000_000,
+        CAPACITY_MSAT,
+        HALF_LIFE,
+    )
+    .unwrap();
+    assert!(confident > 0.5);
+
+    // the same evidence, but a month stale: decayed back towards no opinion
+    let mut stale = mostly_successful;
+    stale.liquidity_age = now - 60 * 60 * 24 * 30;
+    let decayed = bucket_success_probability(Some(&stale), 500_000_000, CAPACITY_MSAT, HALF_LIFE);
+    assert!(decayed.is_none() || decayed.unwrap() < confident);
+
+    // nine of ten recorded forwards near half capacity failed: low confidence around there
+    let mut mostly_failed = empty;
+    for _ in 0..9 {
+        record_liquidity_bucket(&mut mostly_failed, 500_000_000, CAPACITY_MSAT, false);
+    }
+    record_liquidity_bucket(&mut mostly_failed, 500_000_000, CAPACITY_MSAT, true);
+    let unconfident =
+        bucket_success_probability(Some(&mostly_failed), 500_000_000, CAPACITY_MSAT, HALF_LIFE)
+            .unwrap();
+    assert!(unconfident < 0.5);
+}
+
+#[test]
+fn test_reset_liquidity_if_capacity_changed() {
+    use crate::model::Liquidity;
+    use crate::util::{record_liquidity_bucket, reset_liquidity_if_capacity_changed};
+
+    const OLD_CAPACITY_MSAT: u64 = 1_000_000_000;
+    const NEW_CAPACITY_MSAT: u64 = 2_000_000_000;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // a learned estimate against the old capacity, with both bounds set and history recorded
+    let mut learned = Liquidity {
+        liquidity_msat: 600_000_000,
+        liquidity_age: now,
+        min_liquidity_msat: 300_000_000,
+        capacity_msat: OLD_CAPACITY_MSAT,
+        success_buckets: [0.0; 8],
+        fail_buckets: [0.0; 8],
+    };
+    record_liquidity_bucket(&mut learned, 500_000_000, OLD_CAPACITY_MSAT, true);
+
+    // capacity changed: bounds and buckets are wiped, and the new capacity is stored
+    reset_liquidity_if_capacity_changed(&mut learned, NEW_CAPACITY_MSAT);
+    assert_eq!(learned.liquidity_msat, NEW_CAPACITY_MSAT);
+    assert_eq!(learned.min_liquidity_msat, 0);
+    assert_eq!(learned.success_buckets, [0.0; 8]);
+    assert_eq!(learned.fail_buckets, [0.0; 8]);
+    assert_eq!(learned.capacity_msat, NEW_CAPACITY_MSAT);
+
+    // calling it again with the same capacity is a no-op on the (now reset) bounds
+    learned.min_liquidity_msat = 100_000_000;
+    reset_liquidity_if_capacity_changed(&mut learned, NEW_CAPACITY_MSAT);
+    assert_eq!(learned.min_liquidity_msat, 100_000_000);
+
+    // a zero stored capacity_msat is a pre-upgrade sentinel, not a real change: left alone
+    let mut pre_upgrade = Liquidity {
+        liquidity_msat: 600_000_000,
+        liquidity_age: now,
+        min_liquidity_msat: 300_000_000,
+        capacity_msat: 0,
+        success_buckets: [0.0; 8],
+        fail_buckets: [0.0; 8],
+    };
+    record_liquidity_bucket(&mut pre_upgrade, 500_000_000, OLD_CAPACITY_MSAT, true);
+    let before = pre_upgrade;
+    reset_liquidity_if_capacity_changed(&mut pre_upgrade, OLD_CAPACITY_MSAT);
+    assert_eq!(pre_upgrade.liquidity_msat, before.liquidity_msat);
+    assert_eq!(pre_upgrade.min_liquidity_msat, before.min_liquidity_msat);
+    assert_eq!(pre_upgrade.success_buckets, before.success_buckets);
+    assert_eq!(pre_upgrade.fail_buckets, before.fail_buckets);
+    assert_eq!(pre_upgrade.capacity_msat, OLD_CAPACITY_MSAT);
+}
+
+#[test]
+fn test_parse_schedule_interval_secs() {
+    use crate::scheduler::parse_schedule_interval_secs;
+
+    assert_eq!(parse_schedule_interval_secs("every 3600s").unwrap(), 3600);
+    assert_eq!(parse_schedule_interval_secs("every 1s").unwrap(), 1);
+    assert_eq!(parse_schedule_interval_secs("  every 30s  ").unwrap(), 30);
+
+    assert!(parse_schedule_interval_secs("every 0s").is_err());
+    assert!(parse_schedule_interval_secs("0 3 * * *").is_err());
+    assert!(parse_schedule_interval_secs("every 3600").is_err());
+    assert!(parse_schedule_interval_secs("garbage").is_err());
+}
+
+#[test]
+fn test_parse_schedule() {
+    use crate::scheduler::{parse_schedule, ScheduleSpec};
+
+    assert_eq!(
+        parse_schedule("every 600s").unwrap(),
+        ScheduleSpec::Interval(600)
+    );
+    assert_eq!(
+        parse_schedule("22:00-06:00").unwrap(),
+        ScheduleSpec::Window {
+            start_min: 22 * 60,
+            end_min: 6 * 60,
+        }
+    );
+    assert_eq!(
+        parse_schedule("0 3 *").unwrap(),
+        ScheduleSpec::Cron {
+            minute: Some(0),
+            hour: Some(3),
+            dow: None,
+        }
+    );
+
+    assert!(parse_schedule("25:00-06:00").is_err());
+    assert!(parse_schedule("60 3 *").is_err());
+    assert!(parse_schedule("0 3 7").is_err());
+    assert!(parse_schedule("garbage").is_err());
+}
+
+#[test]
+fn test_parse_duration_secs() {
+    use crate::config::parse_duration_secs;
+
+    // suffixed durations convert to seconds regardless of the option's own base unit
+    assert_eq!(parse_duration_secs("30m", 1).unwrap(), 30 * 60);
+    assert_eq!(parse_duration_secs("12h", 60).unwrap(), 12 * 60 * 60);
+    assert_eq!(parse_duration_secs("7d", 60).unwrap(), 7 * 24 * 60 * 60);
+    assert_eq!(parse_duration_secs("2w", 60).unwrap(), 2 * 7 * 24 * 60 * 60);
+    assert_eq!(parse_duration_secs("45s", 60).unwrap(), 45);
+
+    // a bare number keeps meaning "the option's existing base unit" for backwards compatibility
+    assert_eq!(parse_duration_secs("360", 60).unwrap(), 360 * 60);
+    assert_eq!(parse_duration_secs("30", 24 * 60 * 60).unwrap(), 30 * 24 * 60 * 60);
+    assert_eq!(parse_duration_secs("  3600  ", 1).unwrap(), 3600);
+
+    assert!(parse_duration_secs("garbage", 1).is_err());
+    assert!(parse_duration_secs("", 1).is_err());
+    assert!(parse_duration_secs("99999999999999999999w", 1).is_err());
+}
+
+#[test]
+fn test_validate_range() {
+    use crate::config::{spec_for, validate_range};
+
+    // within bounds
+    assert_eq!(
+        validate_range(crate::OPT_MAXHOPS, 8, spec_for(crate::OPT_MAXHOPS)).unwrap(),
+        8
+    );
+    assert_eq!(
+        validate_range(crate::OPT_PARALLELJOBS, 1, spec_for(crate::OPT_PARALLELJOBS)).unwrap(),
+        1
+    );
+
+    // below the lower bound
+    assert!(validate_range(crate::OPT_MAXHOPS, 1, spec_for(crate::OPT_MAXHOPS)).is_err());
+
+    // above the upper bound
+    assert!(validate_range(crate::OPT_MAXHOPS, 21, spec_for(crate::OPT_MAXHOPS)).is_err());
+    assert!(validate_range(crate::OPT_PARALLELJOBS, 101, spec_for(crate::OPT_PARALLELJOBS)).is_err());
+    assert!(validate_range(
+        crate::OPT_MAX_HTLC_COUNT,
+        484,
+        spec_for(crate::OPT_MAX_HTLC_COUNT)
+    )
+    .is_err());
+
+    // negative values are rejected regardless of the configured lower bound
+    assert!(validate_range(crate::OPT_DEPLETEUPTOAMOUNT, -1, spec_for(crate::OPT_DEPLETEUPTOAMOUNT)).is_err());
+}
+
+#[test]
+fn test_apply_inform_layers() {
+    use crate::config::apply_inform_layers;
+
+    // replace mode overwrites the whole list
+    let mut layers = vec!["xpay".to_string()];
+    apply_inform_layers(&mut layers, vec!["askrene".to_string()], false);
+    assert_eq!(layers, vec!["askrene".to_string()]);
+
+    // append mode grows the list without disturbing existing entries
+    let mut layers = vec!["xpay".to_string()];
+    apply_inform_layers(
+        &mut layers,
+        vec!["askrene".to_string(), "xpay".to_string()],
+        true,
+    );
+    assert_eq!(layers, vec!["xpay".to_string(), "askrene".to_string()]);
+}
+
+#[test]
+fn test_checksummed_record_round_trip() {
+    use crate::util::{decode_checksummed_records, encode_checksummed_record};
+
+    let mut buf = Vec::new();
+    buf.extend(encode_checksummed_record(b"first"));
+    buf.extend(encode_checksummed_record(b"second"));
+    buf.extend(encode_checksummed_record(b""));
+
+    let records = decode_checksummed_records(&buf);
+    assert_eq!(records, vec![b"first".as_slice(), b"second", b""]);
+}
+
+#[test]
+fn test_checksummed_record_stops_at_torn_write() {
+    use crate::util::{decode_checksummed_records, encode_checksummed_record};
+
+    let mut buf = Vec::new();
+    buf.extend(encode_checksummed_record(b"good"));
+    // A crash mid-append truncates the record after the header: this must stop the replay
+    // instead of panicking or returning the truncated record.
+    buf.extend(encode_checksummed_record(b"truncated"));
+    buf.truncate(buf.len() - 3);
+
+    assert_eq!(decode_checksummed_records(&buf), vec![b"good".as_slice()]);
+}
+
+#[test]
+fn test_checksummed_record_stops_at_corrupt_checksum() {
+    use crate::util::{decode_checksummed_records, encode_checksummed_record};
+
+    let mut buf = encode_checksummed_record(b"good");
+    let mut corrupt = encode_checksummed_record(b"corrupt");
+    // Flip a payload byte without updating its checksum, simulating a bit-flip on disk.
+    let payload_start = corrupt.len() - "corrupt".len();
+    corrupt[payload_start] ^= 0xff;
+    buf.extend(corrupt);
+
+    assert_eq!(decode_checksummed_records(&buf), vec![b"good".as_slice()]);
+}
+
 // Read current RSS from /proc/self/statm (Linux-specific)
 fn get_current_rss() -> Option<u64> {
     let mut file = File::open("/proc/self/statm").ok()?;
@@ -216,6 +651,8 @@ fn test_gossip_file_reader() {
             &mut graph,
             &mut incomplete_channels,
             &mut offset,
+            60 * 60 * 24 * 14,
+            60 * 60 * 24,
         )
         .expect("read_gossip_file failed");
         let elapsed = now.elapsed().as_millis();
@@ -228,6 +665,8 @@ fn test_gossip_file_reader() {
             &mut graph,
             &mut incomplete_channels,
             &mut offset,
+            60 * 60 * 24 * 14,
+            60 * 60 * 24,
         )
         .expect("read_gossip_file failed");
         let elapsed_after = now.elapsed().as_millis();
@@ -304,6 +743,8 @@ fn test_dijkstra_speed() {
         &mut graph,
         &mut incomplete_channels,
         &mut offset,
+        60 * 60 * 24 * 14,
+        60 * 60 * 24,
     )
     .expect("read_gossip_file failed");
 
@@ -370,6 +811,7 @@ fn test_dijkstra_speed() {
             &job,
             &[],
             &HashMap::new(),
+            &HashMap::new(),
         )
         .iter()
         .map(|r| r.0.short_channel_id)
@@ -393,6 +835,7 @@ fn test_dijkstra_speed() {
             &candidatelist,
             &excepts,
             &HashMap::new(),
+            &HashMap::new(),
         )
         .unwrap();
         let elapsed = now.elapsed().as_millis();
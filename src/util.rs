@@ -1,9 +1,10 @@
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
+    hash::Hasher,
     io,
     path::{Path, PathBuf},
     str::FromStr,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Error};
@@ -13,26 +14,37 @@ use bitcoin::{
 };
 use cln_plugin::Plugin;
 use cln_rpc::{
-    model::responses::ListpeerchannelsChannels,
+    model::{requests::SendpayRoute, responses::ListpeerchannelsChannels},
     primitives::{Amount, ChannelState, PublicKey, Sha256, ShortChannelId, ShortChannelIdDir},
 };
 use rand::{rng, Rng};
-use sling::{Job, SatDirection};
+use sling::{ExceptChan, ExceptDirection, Job, PeerExcept, SatDirection};
 use tokio::{
-    fs::{self, File},
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
     time::{self, Instant},
 };
 
 use crate::{
     model::{
+        GraphSnapshot,
+        IncompleteChannels,
         JobMessage,
         Liquidity,
+        LiquidityJournalRecord,
+        LnGraph,
+        MppPayRecord,
         PluginState,
         TaskIdentifier,
         EXCEPTS_CHANS_FILE_NAME,
         EXCEPTS_PEERS_FILE_NAME,
+        GRAPH_SNAPSHOT_FILE_NAME,
+        GRAPH_SNAPSHOT_VERSION,
         JOB_FILE_NAME,
+        LIQUIDITY_BUCKETS,
         LIQUIDITY_FILE_NAME,
+        LIQUIDITY_JOURNAL_FILE_NAME,
+        MPP_PAYS_FILE_NAME,
         PLUGIN_NAME,
     },
     ShortChannelIdDirState,
@@ -43,12 +55,12 @@ pub async fn read_jobs(
     plugin: Plugin<PluginState>,
 ) -> Result<BTreeMap<ShortChannelId, Job>, Error> {
     let jobfile = sling_dir.join(JOB_FILE_NAME);
-    let jobfilecontent = fs::read_to_string(jobfile.clone()).await;
+    let jobfilecontent = fs::read(jobfile.clone()).await;
     let mut jobs: BTreeMap<ShortChannelId, Job>;
 
     create_sling_dir(sling_dir).await?;
     match jobfilecontent {
-        Ok(file) => jobs = serde_json::from_str(&file).unwrap_or(BTreeMap::new()),
+        Ok(bytes) => jobs = parse_checksummed_or_legacy(&jobfile, &bytes).await?,
         Err(e) => {
             log::warn!(
                 "Couldn't open {}: {}. First time using sling? Creating new file.",
@@ -109,9 +121,9 @@ pub async fn write_job(
         }
     }
     jobs.retain(|i, _j| !jobs_to_remove.contains(i));
-    fs::write(
-        sling_dir.join(JOB_FILE_NAME),
-        serde_json::to_string_pretty(&jobs)?,
+    write_file_atomic(
+        &sling_dir.join(JOB_FILE_NAME),
+        &encode_checksummed_record(&serde_json::to_vec(&jobs)?),
     )
     .await?;
 
@@ -126,6 +138,7 @@ async fn refresh_job_excepts(
     jobs: &BTreeMap<ShortChannelId, Job>,
 ) -> Result<(), Error> {
     let static_excepts = read_except_chans(sling_dir).await?;
+    let now = now_secs();
     let mut config = plugin.state().config.lock();
     config.exclude_chans_pull.clear();
     config.exclude_chans_push.clear();
@@ -135,50 +148,235 @@ async fn refresh_job_excepts(
             SatDirection::Push => config.exclude_chans_push.insert(*scid),
         };
     }
-    for except in static_excepts {
-        config.exclude_chans_pull.insert(except);
-        config.exclude_chans_push.insert(except);
+    for (except, entry) in static_excepts {
+        if entry.is_expired(now) {
+            continue;
+        }
+        match entry.direction {
+            ExceptDirection::Pull => {
+                config.exclude_chans_pull.insert(except);
+            }
+            ExceptDirection::Push => {
+                config.exclude_chans_push.insert(except);
+            }
+            ExceptDirection::Both => {
+                config.exclude_chans_pull.insert(except);
+                config.exclude_chans_push.insert(except);
+            }
+        }
     }
 
     Ok(())
 }
 
-pub async fn write_excepts<T: ToString>(
-    excepts: HashSet<T>,
-    file: &str,
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Reads [`MPP_PAYS_FILE_NAME`], the durable record of in-flight [`crate::model::MppPay`]s kept
+/// so a restart can resume waiting on a multi-part rebalance's remaining parts instead of
+/// leaving them unclaimed. Missing or unparsable files are treated as "nothing in flight".
+pub async fn read_mpp_pay_records(
+    sling_dir: &Path,
+) -> Result<HashMap<String, MppPayRecord>, Error> {
+    let path = sling_dir.join(MPP_PAYS_FILE_NAME);
+    match fs::read(&path).await {
+        Ok(bytes) => parse_checksummed_or_legacy(&path, &bytes).await,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Overwrites [`MPP_PAYS_FILE_NAME`] with the current set of in-flight [`crate::model::MppPay`]s.
+/// Volume here is inherently low (one entry per in-flight MPP rebalance), so a plain atomic
+/// full-file rewrite on every registration/resolution is simpler than the journal-plus-snapshot
+/// scheme [`write_liquidity`] needs for its much higher write rate.
+pub async fn write_mpp_pay_records(
+    records: &HashMap<String, MppPayRecord>,
     sling_dir: &Path,
 ) -> Result<(), Error> {
-    let excepts_tostring = excepts
-        .into_iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<_>>();
-
-    fs::write(
-        sling_dir.join(file),
-        serde_json::to_string(&excepts_tostring)?,
+    write_file_atomic(
+        &sling_dir.join(MPP_PAYS_FILE_NAME),
+        &encode_checksummed_record(&serde_json::to_vec(records)?),
     )
-    .await?;
+    .await
+}
+
+/// Snapshots [`PluginState::mpp_pays`] to [`MPP_PAYS_FILE_NAME`], called right after every
+/// insertion or removal so the on-disk record never lags the in-memory one by more than the
+/// single write it takes to catch up.
+pub async fn sync_mpp_pays_to_disk(
+    plugin: &Plugin<PluginState>,
+    sling_dir: &Path,
+) -> Result<(), Error> {
+    let records: HashMap<String, MppPayRecord> = plugin
+        .state()
+        .mpp_pays
+        .lock()
+        .iter()
+        .map(|(payment_hash, pay)| {
+            (
+                payment_hash.clone(),
+                MppPayRecord {
+                    payment_hash: payment_hash.clone(),
+                    resolve: pay.resolve.clone(),
+                    target_msat: pay.target_msat,
+                    parts_expected: pay.parts_expected,
+                    part_timeout_secs: pay.part_timeout_secs,
+                },
+            )
+        })
+        .collect();
+    write_mpp_pay_records(&records, sling_dir).await
+}
+
+pub async fn write_except_chans(
+    excepts: &BTreeMap<ShortChannelId, ExceptChan>,
+    sling_dir: &Path,
+) -> Result<(), Error> {
+    write_file_atomic(
+        &sling_dir.join(EXCEPTS_CHANS_FILE_NAME),
+        &encode_checksummed_record(&serde_json::to_vec(excepts)?),
+    )
+    .await
+}
+
+pub async fn write_except_peers(
+    excepts: &HashMap<PublicKey, Option<u64>>,
+    sling_dir: &Path,
+) -> Result<(), Error> {
+    let list: Vec<PeerExcept> = excepts
+        .iter()
+        .map(|(id, expires_at)| PeerExcept {
+            id: id.to_string(),
+            expires_at: *expires_at,
+        })
+        .collect();
 
+    write_file_atomic(
+        &sling_dir.join(EXCEPTS_PEERS_FILE_NAME),
+        &encode_checksummed_record(&serde_json::to_vec(&list)?),
+    )
+    .await
+}
+
+/// Hashes `payload` for the checksum stored alongside it in a liquidity record, so a torn
+/// write (truncated or bit-flipped mid-record) is detected instead of silently accepted.
+pub(crate) fn record_checksum(payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(payload);
+    hasher.finish()
+}
+
+/// Frames `payload` as `[len: u32 LE][checksum: u64 LE][payload]`. Used for every persisted
+/// file in this module ([`JOB_FILE_NAME`], [`LIQUIDITY_FILE_NAME`], [`EXCEPTS_CHANS_FILE_NAME`],
+/// [`EXCEPTS_PEERS_FILE_NAME`]) and each [`LIQUIDITY_JOURNAL_FILE_NAME`] record, so corruption
+/// or a torn write is detected on read instead of silently parsed as something else.
+pub(crate) fn encode_checksummed_record(payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + 8 + payload.len());
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&record_checksum(payload).to_le_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// Decodes consecutive length-prefixed, checksummed records from `buf`, stopping at the first
+/// one whose header or checksum doesn't check out (a partial or corrupt write) rather than
+/// discarding every record successfully decoded before it.
+pub(crate) fn decode_checksummed_records(buf: &[u8]) -> Vec<&[u8]> {
+    let mut records = Vec::new();
+    let mut pos = 0;
+    loop {
+        let Some(header) = buf.get(pos..pos + 12) else {
+            break;
+        };
+        let len = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let checksum = u64::from_le_bytes(header[4..12].try_into().unwrap());
+        let payload_start = pos + 12;
+        let Some(payload) = buf.get(payload_start..payload_start + len) else {
+            break;
+        };
+        if record_checksum(payload) != checksum {
+            break;
+        }
+        records.push(payload);
+        pos = payload_start + len;
+    }
+    records
+}
+
+/// Writes `bytes` to `path` via a sibling `.tmp` file, `fsync`, then rename over the real
+/// target, so a crash mid-write can never leave a truncated or partially written file in
+/// its place.
+async fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} has no file name", path.display()))?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = File::create(&tmp_path).await?;
+    file.write_all(bytes).await?;
+    file.sync_all().await?;
+    fs::rename(&tmp_path, path).await?;
     Ok(())
 }
 
+/// Renames a file that failed every known parse path aside to a timestamped `.corrupt`
+/// backup and logs an error, instead of silently discarding its contents by falling back to
+/// an empty collection, so the operator can recover the original bytes.
+async fn backup_corrupt_file(path: &Path) -> Result<(), Error> {
+    let backup_path = path.with_file_name(format!(
+        "{}.{}.corrupt",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        now_secs(),
+    ));
+    log::error!(
+        "{} is corrupt or unreadable; backed it up to {} and starting from an empty file",
+        path.display(),
+        backup_path.display()
+    );
+    fs::rename(path, &backup_path)
+        .await
+        .map_err(|e| anyhow!("could not back up corrupt file {}: {e}", path.display()))
+}
+
+/// Parses `bytes` (already read from `path`) as a checksummed record first, then falls back
+/// to treating them as a legacy pre-integrity plain-JSON file, so upgrading doesn't
+/// `.corrupt` a file an older sling wrote. Only [`backup_corrupt_file`]s and logs an error —
+/// instead of silently substituting an empty default — when neither interpretation parses.
+async fn parse_checksummed_or_legacy<T: serde::de::DeserializeOwned + Default>(
+    path: &Path,
+    bytes: &[u8],
+) -> Result<T, Error> {
+    if let Some(payload) = decode_checksummed_records(bytes).first() {
+        if let Ok(value) = serde_json::from_slice(payload) {
+            return Ok(value);
+        }
+    }
+    if let Ok(value) = serde_json::from_slice(bytes) {
+        return Ok(value);
+    }
+    backup_corrupt_file(path).await?;
+    Ok(T::default())
+}
+
 pub async fn read_liquidity(
     sling_dir: &PathBuf,
+    liquidity_max_age: u64,
 ) -> Result<HashMap<ShortChannelIdDir, Liquidity>, Error> {
     let liquidity_file = sling_dir.join(LIQUIDITY_FILE_NAME);
-    let liquidity_file_content = fs::read_to_string(liquidity_file.clone()).await;
-    let liquidity: HashMap<ShortChannelIdDir, Liquidity>;
+    let liquidity_file_content = fs::read(liquidity_file.clone()).await;
+    let mut liquidity: HashMap<ShortChannelIdDir, Liquidity>;
 
     create_sling_dir(sling_dir).await?;
     match liquidity_file_content {
-        Ok(file) => {
-            liquidity = match serde_json::from_str(&file) {
-                Ok(o) => o,
-                Err(e) => {
-                    log::warn!("could not read liquidity: {e}");
-                    HashMap::new()
-                }
-            }
+        Ok(bytes) => {
+            liquidity = parse_checksummed_or_legacy(&liquidity_file, &bytes).await?;
         }
         Err(e) => {
             log::warn!(
@@ -191,17 +389,230 @@ pub async fn read_liquidity(
         }
     }
 
+    // Replay whatever updates landed after the last compaction on top of the snapshot above.
+    match fs::read(sling_dir.join(LIQUIDITY_JOURNAL_FILE_NAME)).await {
+        Ok(bytes) => {
+            let records = decode_checksummed_records(&bytes);
+            for payload in &records {
+                match serde_json::from_slice::<LiquidityJournalRecord>(payload) {
+                    Ok(record) => {
+                        liquidity.insert(record.scid_dir, record.liquidity);
+                    }
+                    Err(e) => log::warn!("could not decode liquidity journal record: {e}"),
+                }
+            }
+            if !records.is_empty() {
+                log::debug!("Replayed {} liquidity journal record(s)", records.len());
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => log::warn!("could not open liquidity journal: {e}"),
+    }
+
+    if liquidity_max_age > 0 {
+        // An estimate this old carries so little remaining signal (see
+        // `edge_success_probability`'s decay) that it's not worth paying to decay and
+        // keep around across a restart; drop it entirely instead.
+        let now = now_secs();
+        let before = liquidity.len();
+        liquidity.retain(|_, liq| now.saturating_sub(liq.liquidity_age) <= liquidity_max_age);
+        let dropped = before - liquidity.len();
+        if dropped > 0 {
+            log::debug!(
+                "Discarded {dropped} liquidity estimate(s) older than {liquidity_max_age}s"
+            );
+        }
+    }
+
     Ok(liquidity)
 }
+
+/// Appends a single updated channel's liquidity to [`LIQUIDITY_JOURNAL_FILE_NAME`] so the
+/// update survives a crash before the next periodic [`write_liquidity`] compaction. Takes
+/// `liquidity_journal_lock` so the append can't interleave with a concurrent compaction
+/// truncating the same file out from under it.
+pub async fn append_liquidity_update(
+    plugin: &Plugin<PluginState>,
+    sling_dir: &Path,
+    scid_dir: ShortChannelIdDir,
+    liquidity: Liquidity,
+) -> Result<(), Error> {
+    let record = LiquidityJournalRecord {
+        scid_dir,
+        liquidity,
+    };
+    let payload = serde_json::to_vec(&record)?;
+    let _guard = plugin.state().liquidity_journal_lock.lock().await;
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(sling_dir.join(LIQUIDITY_JOURNAL_FILE_NAME))
+        .await?;
+    file.write_all(&encode_checksummed_record(&payload)).await?;
+    Ok(())
+}
+
+/// Writes a fresh full snapshot of the liquidity map and truncates the journal, folding
+/// everything replayed from it into the snapshot it was recorded against. Called both
+/// periodically in the background and at shutdown.
+///
+/// Holds `liquidity_journal_lock` across both the snapshot read and the truncate so a
+/// concurrent [`append_liquidity_update`] can't land in between and get silently discarded:
+/// either it runs entirely before this snapshot (so its update is already in it) or entirely
+/// after the truncate (so it starts the next journal).
 pub async fn write_liquidity(plugin: Plugin<PluginState>) -> Result<(), Error> {
-    let graph_string = serde_json::to_string(&*plugin.state().liquidity.lock())?;
     let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
     let now = Instant::now();
-    fs::write(sling_dir.join(LIQUIDITY_FILE_NAME), graph_string).await?;
+    let liquidity_halflife = plugin.state().config.lock().liquidity_halflife;
+    let _guard = plugin.state().liquidity_journal_lock.lock().await;
+    prune_decayed_liquidity(&plugin.state().liquidity, liquidity_halflife);
+    let payload = serde_json::to_vec(&*plugin.state().liquidity.lock())?;
+    write_file_atomic(
+        &sling_dir.join(LIQUIDITY_FILE_NAME),
+        &encode_checksummed_record(&payload),
+    )
+    .await?;
+    let journal_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(sling_dir.join(LIQUIDITY_JOURNAL_FILE_NAME))
+        .await?;
+    journal_file.sync_all().await?;
     log::debug!("Wrote liquidity to disk in {}ms", now.elapsed().as_millis());
     Ok(())
 }
 
+/// Drops learned liquidity bounds that have decayed back to (indistinguishable from) their
+/// default `[0, capacity]` state, so a channel we haven't seen succeed or fail in a very long
+/// time stops permanently bloating [`LIQUIDITY_FILE_NAME`] with a belief that carries no
+/// signal anymore. A belief counts as decayed once its `liquidity_age` is ten half-lives old.
+/// Runs at every [`write_liquidity`] compaction rather than continuously. `refresh_liquidity`
+/// resets `liquidity_age` whenever it folds decay into a stale belief, so a belief it has
+/// rewritten is measured from that rewrite and is not dropped here unless ten half-lives are
+/// shorter than `sling-reset-liquidity-interval`.
+fn prune_decayed_liquidity(
+    liquidity: &parking_lot::Mutex<HashMap<ShortChannelIdDir, Liquidity>>,
+    liquidity_halflife: u64,
+) {
+    if liquidity_halflife == 0 {
+        return;
+    }
+    // 0.5^10 =~ 0.001: close enough to fully decayed that keeping the entry around buys us
+    // nothing over treating the channel as never having had a recorded belief.
+    const STALE_HALFLIVES: f64 = 10.0;
+    let stale_after_secs = (liquidity_halflife as f64 * STALE_HALFLIVES) as u64;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    liquidity
+        .lock()
+        .retain(|_, liq| now.saturating_sub(liq.liquidity_age) < stale_after_secs);
+}
+
+/// How many bytes at the start of `gossip_store` [`GraphSnapshot`] hashes to detect the file
+/// having been rotated or compacted by `lightningd` since the snapshot was taken. CLN rewrites
+/// the whole file on compaction, so a change this early in it is a reliable rotation signal
+/// without the cost of reading the entire (often tens-of-MB) consumed prefix on every snapshot.
+const GOSSIP_STORE_PREFIX_HASH_BYTES: usize = 4096;
+
+async fn gossip_store_prefix_hash_and_len(path: &Path) -> Result<(u64, u64), Error> {
+    let mut file = File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let mut buf = vec![0u8; (len as usize).min(GOSSIP_STORE_PREFIX_HASH_BYTES)];
+    file.read_exact(&mut buf).await?;
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&buf);
+    Ok((hasher.finish(), len))
+}
+
+/// Loads a previously written [`GraphSnapshot`] and validates it against the current
+/// `gossip_store`, returning `None` (instead of an error) on anything that means the snapshot
+/// can't be trusted: no snapshot file yet, a corrupt/truncated record, or a `gossip_store` that
+/// has since been rotated, compacted, or shrunk out from under the recorded offset. The caller
+/// falls back to a full reparse from the start of the file in every `None` case.
+pub async fn load_graph_snapshot(
+    sling_dir: &Path,
+    gossip_store_path: &Path,
+) -> Option<GraphSnapshot> {
+    let bytes = match fs::read(sling_dir.join(GRAPH_SNAPSHOT_FILE_NAME)).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::debug!("no graph snapshot to load, doing a full gossip_store parse: {e}");
+            return None;
+        }
+    };
+    let payload = decode_checksummed_records(&bytes).into_iter().next()?;
+    let snapshot: GraphSnapshot = match serde_json::from_slice(payload) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            log::warn!("could not decode graph snapshot, doing a full reparse: {e}");
+            return None;
+        }
+    };
+
+    if snapshot.version != GRAPH_SNAPSHOT_VERSION {
+        log::info!(
+            "graph snapshot is version {}, sling expects version {}, doing a full reparse",
+            snapshot.version,
+            GRAPH_SNAPSHOT_VERSION
+        );
+        return None;
+    }
+
+    let (prefix_hash, len) = match gossip_store_prefix_hash_and_len(gossip_store_path).await {
+        Ok(hash_and_len) => hash_and_len,
+        Err(e) => {
+            log::warn!("could not open gossip_store to validate graph snapshot: {e}");
+            return None;
+        }
+    };
+    if len < snapshot.gossip_store_len || prefix_hash != snapshot.gossip_store_prefix_hash {
+        log::info!(
+            "gossip_store looks rotated or truncated since the last graph snapshot, \
+            doing a full reparse"
+        );
+        return None;
+    }
+
+    Some(snapshot)
+}
+
+/// Writes the current graph/incomplete-channels state plus how far into `gossip_store` it
+/// accounts for, so a restart can load it back via [`load_graph_snapshot`] instead of
+/// reparsing the whole file. Called periodically by [`crate::tasks::compact_graph_snapshot`].
+pub async fn save_graph_snapshot(plugin: Plugin<PluginState>) -> Result<(), Error> {
+    let sling_dir = Path::new(&plugin.configuration().lightning_dir).join(PLUGIN_NAME);
+    let gossip_store_path = Path::new(&plugin.configuration().lightning_dir).join("gossip_store");
+    let now = Instant::now();
+
+    let offset = *plugin.state().gossip_store_offset.lock();
+    let (prefix_hash, len) = gossip_store_prefix_hash_and_len(&gossip_store_path).await?;
+    let graph = plugin.state().graph.lock().clone();
+    let incomplete_channels = plugin.state().incomplete_channels.lock().clone();
+
+    let snapshot = GraphSnapshot {
+        version: GRAPH_SNAPSHOT_VERSION,
+        offset,
+        gossip_store_len: len,
+        gossip_store_prefix_hash: prefix_hash,
+        graph,
+        incomplete_channels,
+    };
+    let payload = serde_json::to_vec(&snapshot)?;
+    write_file_atomic(
+        &sling_dir.join(GRAPH_SNAPSHOT_FILE_NAME),
+        &encode_checksummed_record(&payload),
+    )
+    .await?;
+    log::debug!(
+        "Wrote graph snapshot to disk in {}ms",
+        now.elapsed().as_millis()
+    );
+    Ok(())
+}
+
 pub async fn create_sling_dir(sling_dir: &PathBuf) -> Result<(), Error> {
     match fs::create_dir(sling_dir).await {
         Ok(()) => Ok(()),
@@ -231,8 +642,305 @@ pub fn get_total_htlc_count(channel: &ListpeerchannelsChannels) -> u64 {
     }
 }
 
-pub fn edge_cost(edge: &ShortChannelIdDirState, amount: u64) -> u64 {
-    feeppm_effective(edge.fee_per_millionth, edge.base_fee_millisatoshi, amount) + 2
+/// The true usable liquidity a candidate channel can contribute to a rebalance going in
+/// `sat_direction`, using CLN's own `spendable_msat`/`receivable_msat` instead of a raw
+/// balance difference. Both already account for each side's `channel_reserve`,
+/// already-offered/received in-flight HTLCs, the commitment-transaction fee buffer when we're
+/// the channel opener, and clamp to the chain's max-payment ceiling, so a channel that merely
+/// looks full enough by balance but can't actually carry the HTLC is excluded correctly.
+/// `Pull` needs the candidate to be able to send onward (`spendable_msat`); `Push` needs it to
+/// be able to receive the incoming rebalance (`receivable_msat`).
+pub fn usable_liquidity_msat(channel: &ListpeerchannelsChannels, sat_direction: SatDirection) -> u64 {
+    match sat_direction {
+        SatDirection::Pull => channel.spendable_msat.map_or(0, |a| Amount::msat(&a)),
+        SatDirection::Push => channel.receivable_msat.map_or(0, |a| Amount::msat(&a)),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn edge_cost(
+    edge: &ShortChannelIdDirState,
+    amount: u64,
+    liquidity: Option<&Liquidity>,
+    liquidity_halflife: u64,
+    liquidity_penalty_multiplier: u64,
+    liquidity_probabilistic_scoring: bool,
+    reserved_msat: u64,
+) -> u64 {
+    let fee_cost = feeppm_effective(edge.fee_per_millionth, edge.base_fee_millisatoshi, amount) + 2;
+    if !liquidity_probabilistic_scoring {
+        return fee_cost;
+    }
+    fee_cost
+        + liquidity_uncertainty_penalty(
+            edge.htlc_maximum_msat.msat().saturating_sub(reserved_msat),
+            amount,
+            liquidity,
+            liquidity_halflife,
+            liquidity_penalty_multiplier,
+        )
+}
+
+/// Extra cost added for routing over a channel whose learned liquidity bounds give us
+/// little confidence it can actually forward `amount`, so dijkstra prefers channels we
+/// believe are more likely to succeed over merely cheaper ones.
+///
+/// `liquidity` holds our learned `[min_liquidity_msat, liquidity_msat]` bounds on the
+/// channel's forwardable balance. We model the chance it can carry `amount_msat` as
+/// `P = 1` below the min, `P = 0` above the max, and a linear ramp in between, then add
+/// `-ln(P)` (scaled to ppm) to the cost so routes through channels we're unsure about, but
+/// haven't hard-excluded, still fall behind more promising ones. Both bounds decay back
+/// toward `[0, capacity]` with age over `liquidity_halflife` seconds, so a belief we
+/// haven't refreshed in a while stops being trusted. Channels with no belief at all get
+/// the same flat mid-range treatment `edges()` assumes when filtering (htlc_maximum_msat /
+/// 2 as an assumed max). `liquidity_penalty_multiplier` (`sling-liquidity-penalty-multiplier`)
+/// scales how aggressively uncertainty is punished.
+pub fn liquidity_uncertainty_penalty(
+    htlc_maximum_msat: u64,
+    amount_msat: u64,
+    liquidity: Option<&Liquidity>,
+    liquidity_halflife: u64,
+    liquidity_penalty_multiplier: u64,
+) -> u64 {
+    const MAX_PENALTY_PPM: f64 = 2_000.0;
+    // Floor applied before taking ln() so a dead-certain-failure hop (probability 0) still
+    // gets a large finite penalty rather than +inf.
+    const MIN_PROBABILITY: f64 = 1e-9;
+
+    if htlc_maximum_msat == 0 {
+        return 0;
+    }
+    let probability =
+        edge_success_probability(htlc_maximum_msat, amount_msat, liquidity, liquidity_halflife);
+
+    let penalty_ppm = (-probability.max(MIN_PROBABILITY).ln() * liquidity_penalty_multiplier as f64)
+        .min(MAX_PENALTY_PPM);
+    fee_total_msat_precise(penalty_ppm as u32, 0, amount_msat).ceil() as u64
+}
+
+/// Our best estimate of the chance a single directed channel can forward `amount_msat`,
+/// derived from the same learned `[min_liquidity_msat, liquidity_msat]` bounds (decayed by
+/// `liquidity_halflife`) that [`liquidity_uncertainty_penalty`] turns into a routing-cost
+/// penalty: `1.0` below the effective min, `0.0` above the effective max, and a linear ramp
+/// in between. Blended with [`bucket_success_probability`]'s historical-bucket estimate when
+/// that channel has recorded outcomes, so recurring liquidity patterns the bounds alone would
+/// discard still shape the estimate. Shared so route-level probability pruning
+/// (`route_success_probability`) can multiply the same per-channel estimate along a full path
+/// instead of re-deriving it.
+pub fn edge_success_probability(
+    htlc_maximum_msat: u64,
+    amount_msat: u64,
+    liquidity: Option<&Liquidity>,
+    liquidity_halflife: u64,
+) -> f64 {
+    if htlc_maximum_msat == 0 {
+        return 1.0;
+    }
+    let capacity = htlc_maximum_msat as f64;
+
+    let (eff_min, eff_max) = match liquidity {
+        Some(liq) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let age = now.saturating_sub(liq.liquidity_age) as f64;
+            let decay = if liquidity_halflife == 0 {
+                0.0
+            } else {
+                0.5_f64.powf(age / liquidity_halflife as f64)
+            };
+            let max_msat = liq.liquidity_msat.min(htlc_maximum_msat) as f64;
+            let eff_min = liq.min_liquidity_msat as f64 * decay;
+            let eff_max = capacity - (capacity - max_msat) * decay;
+            (eff_min, eff_max)
+        }
+        None => (0.0, capacity / 2.0),
+    };
+
+    let amount = amount_msat as f64;
+    let bounds_probability = if amount <= eff_min {
+        1.0
+    } else if amount > eff_max || eff_max <= eff_min {
+        0.0
+    } else {
+        (eff_max - amount) / (eff_max - eff_min)
+    };
+
+    match bucket_success_probability(liquidity, amount_msat, htlc_maximum_msat, liquidity_halflife) {
+        Some(bucket_probability) => (bounds_probability + bucket_probability) / 2.0,
+        None => bounds_probability,
+    }
+}
+
+/// Sort key `build_candidatelist` ranks candidates by, ascending (lower is better): a penalty
+/// for how unlikely our learned liquidity bounds say `amount_msat` is to go through, plus the
+/// candidate's effective fee ppm scaled by `fee_weight` (`sling-candidate-fee-weight`). `0.0`
+/// for `fee_weight` ranks purely by success probability; the higher it goes, the more a cheap
+/// but uncertain candidate can outrank an expensive sure thing. Mirrors the
+/// `-k * ln(p)`-style penalty [`liquidity_uncertainty_penalty`] already adds to dijkstra's
+/// per-edge cost, just in `log10` terms so the penalty's magnitude lines up with ppm-sized
+/// fee terms instead of dijkstra's msat-sized ones.
+pub fn candidate_rank_score(probability: f64, effective_ppm: u64, fee_weight: f64) -> f64 {
+    const MIN_PROBABILITY: f64 = 1e-9;
+    const SCALE: f64 = 1_000.0;
+
+    let probability_penalty = -probability.max(MIN_PROBABILITY).log10() * SCALE;
+    probability_penalty + fee_weight * effective_ppm as f64
+}
+
+/// Wipes a learned estimate back to a uniform prior when the channel's own `capacity_msat`
+/// (its current `htlc_maximum_msat`) no longer matches the capacity it was learned against —
+/// a splice, fee-policy bump, or other gossip update that moves the ceiling invalidates the
+/// old bounds and buckets, which were learned as absolutes/fractions of the previous capacity.
+/// A zero `liq.capacity_msat` means this estimate predates the field (an older persisted
+/// record) and is left alone rather than reset on every first read after an upgrade.
+pub fn reset_liquidity_if_capacity_changed(liq: &mut Liquidity, capacity_msat: u64) {
+    if liq.capacity_msat != 0 && liq.capacity_msat != capacity_msat {
+        liq.liquidity_msat = capacity_msat;
+        liq.min_liquidity_msat = 0;
+        liq.success_buckets = [0.0; LIQUIDITY_BUCKETS];
+        liq.fail_buckets = [0.0; LIQUIDITY_BUCKETS];
+    }
+    liq.capacity_msat = capacity_msat;
+}
+
+/// Relaxes both learned bounds toward their uniform-prior defaults (`min_liquidity_msat`
+/// toward 0, `liquidity_msat` toward `capacity_msat`) by the same `0.5^(elapsed/half_life)`
+/// factor [`edge_success_probability`] applies ephemerally at read time, but writes the result
+/// back into `liq` itself. Called right before a new success/failure clamps a bound, so that
+/// evidence is combined with how much the *previous* belief has already faded rather than with
+/// a value that's gone stale sitting at its old extreme. Does not touch `liq.liquidity_age` —
+/// callers reset that themselves once they're done folding in the new evidence.
+pub fn decay_liquidity_bounds(liq: &mut Liquidity, capacity_msat: u64, now: u64, half_life: u64) {
+    let age = now.saturating_sub(liq.liquidity_age) as f64;
+    let decay = if half_life == 0 {
+        0.0
+    } else {
+        0.5_f64.powf(age / half_life as f64)
+    };
+    liq.min_liquidity_msat = (liq.min_liquidity_msat as f64 * decay) as u64;
+    let capacity = capacity_msat as f64;
+    let max_msat = liq.liquidity_msat.min(capacity_msat) as f64;
+    liq.liquidity_msat = (capacity - (capacity - max_msat) * decay) as u64;
+}
+
+/// Which of [`LIQUIDITY_BUCKETS`] equal slices of `0..capacity_msat` covers `amount_msat`.
+fn liquidity_bucket(amount_msat: u64, capacity_msat: u64) -> usize {
+    if capacity_msat == 0 {
+        return 0;
+    }
+    let fraction = amount_msat as f64 / capacity_msat as f64;
+    ((fraction * LIQUIDITY_BUCKETS as f64) as usize).min(LIQUIDITY_BUCKETS - 1)
+}
+
+/// Records that a forward of `amount_msat` over a channel with `capacity_msat` total
+/// succeeded or failed, adding weight to the bucket covering that amount's fraction of
+/// capacity. Called alongside the `[min_liquidity_msat, liquidity_msat]` bound update for
+/// the same event (`raise_min_liquidity`/`lower_max_liquidity` in `response.rs`), so both
+/// models are always updated together from the same evidence. Like the bounds, buckets are
+/// stored undecayed and decayed lazily by [`bucket_success_probability`] (and periodically
+/// rolled forward for stale entries in `refresh_liquidity`), so `liq.liquidity_age` doesn't
+/// need touching here.
+pub fn record_liquidity_bucket(liq: &mut Liquidity, amount_msat: u64, capacity_msat: u64, success: bool) {
+    let bucket = liquidity_bucket(amount_msat, capacity_msat);
+    if success {
+        liq.success_buckets[bucket] += 1.0;
+    } else {
+        liq.fail_buckets[bucket] += 1.0;
+    }
+}
+
+/// Our historical-bucket estimate of the chance a channel can forward `amount_msat`: the
+/// decayed success weight in every bucket covering "at least this much was available",
+/// divided by the total decayed weight across all buckets, or `None` if nothing has ever
+/// been recorded for this channel. Decayed by the same `0.5^(elapsed/liquidity_halflife)`
+/// factor as the `[min_liquidity_msat, liquidity_msat]` bounds, sharing `liquidity_age`, so a
+/// histogram we haven't updated in a while gradually stops dominating the blend in
+/// [`edge_success_probability`].
+pub fn bucket_success_probability(
+    liquidity: Option<&Liquidity>,
+    amount_msat: u64,
+    capacity_msat: u64,
+    liquidity_halflife: u64,
+) -> Option<f64> {
+    // Below this much decayed weight there isn't enough (or recent enough) evidence to
+    // trust the histogram over the bounds model, so it drops out of the blend entirely
+    // rather than keeping a ratio computed from all-but-vanished observations.
+    const MIN_BUCKET_WEIGHT: f64 = 1.0;
+
+    let liq = liquidity?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let age = now.saturating_sub(liq.liquidity_age) as f64;
+    let decay = if liquidity_halflife == 0 {
+        0.0
+    } else {
+        0.5_f64.powf(age / liquidity_halflife as f64)
+    };
+
+    let total_weight: f64 =
+        (liq.success_buckets.iter().sum::<f64>() + liq.fail_buckets.iter().sum::<f64>()) * decay;
+    if total_weight < MIN_BUCKET_WEIGHT {
+        return None;
+    }
+
+    let bucket = liquidity_bucket(amount_msat, capacity_msat);
+    let success_at_or_above: f64 = liq.success_buckets[bucket..].iter().sum::<f64>() * decay;
+    Some(success_at_or_above / total_weight)
+}
+
+/// Multiplies [`edge_success_probability`] along every hop of an already-constructed
+/// `route` to estimate the odds the whole path can carry `amount_msat` start to finish.
+/// Mirrors the per-hop direction derivation `response.rs`'s `hop_dir_chan` uses once a
+/// route has actually been routed over: `route[i]` is the hop landing on `route[i].id`, so
+/// the last entry has no successor to pair it with.
+pub fn route_success_probability(
+    route: &[SendpayRoute],
+    lngraph: &LnGraph,
+    amount_msat: u64,
+    liquidity: &HashMap<ShortChannelIdDir, Liquidity>,
+    liquidity_halflife: u64,
+) -> f64 {
+    let mut probability = 1.0;
+    for i in 0..route.len().saturating_sub(1) {
+        let direction = match get_direction_from_nodes(route[i].id, route[i + 1].id) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let dir_chan = ShortChannelIdDir {
+            short_channel_id: route[i].channel,
+            direction,
+        };
+        let htlc_maximum_msat = lngraph
+            .get_state(dir_chan)
+            .map_or(0, |s| Amount::msat(&s.htlc_maximum_msat));
+        probability *= edge_success_probability(
+            htlc_maximum_msat,
+            amount_msat,
+            liquidity.get(&dir_chan),
+            liquidity_halflife,
+        );
+    }
+    probability
+}
+
+/// Checks whether a node's raw `node_announcement` feature bytes (big-endian, as advertised)
+/// indicate support for BOLT 9 feature number `feature`. Per BOLT 9, features come in even/odd
+/// pairs (`2*feature` "compulsory", `2*feature+1` "optional"); either bit being set counts as
+/// support.
+pub fn node_supports_feature(features: &[u8], feature: u32) -> bool {
+    let bit_set = |bit: u32| -> bool {
+        let byte_from_end = (bit / 8) as usize;
+        if byte_from_end >= features.len() {
+            return false;
+        }
+        let byte = features[features.len() - 1 - byte_from_end];
+        byte & (1 << (bit % 8)) != 0
+    };
+    bit_set(2 * feature) || bit_set(2 * feature + 1)
 }
 
 pub fn feeppm_effective(feeppm: u32, basefee_msat: u32, amount_msat: u64) -> u64 {
@@ -307,7 +1015,13 @@ pub async fn my_sleep(plugin: Plugin<PluginState>, seconds: u64, task_ident: &Ta
     log::debug!("{task_ident}: Starting sleeper for {seconds}s");
     let timer = Instant::now();
     while timer.elapsed() < Duration::from_secs(seconds) {
-        time::sleep(Duration::from_secs(1)).await;
+        tokio::select! {
+            _ = time::sleep(Duration::from_secs(1)) => {}
+            _ = plugin.state().wake.notified() => {
+                log::debug!("{task_ident}: Sleeper woken early by a relevant state change");
+                break;
+            }
+        }
         {
             if let Some(o) = plugin.state().tasks.lock().get_task(task_ident) {
                 if o.should_stop() {
@@ -381,58 +1095,146 @@ pub fn get_direction_from_nodes(
     Err(anyhow!("Nodes are equal"))
 }
 
-pub async fn read_except_chans(sling_dir: &PathBuf) -> Result<HashSet<ShortChannelId>, Error> {
+pub async fn read_except_chans(
+    sling_dir: &PathBuf,
+) -> Result<BTreeMap<ShortChannelId, ExceptChan>, Error> {
     let excepts_chan_file = sling_dir.join(EXCEPTS_CHANS_FILE_NAME);
-    let excepts_chan_file_content = fs::read_to_string(excepts_chan_file.clone()).await;
+    let excepts_chan_file_content = fs::read(excepts_chan_file.clone()).await;
 
     create_sling_dir(sling_dir).await?;
 
-    parse_excepts(excepts_chan_file_content, excepts_chan_file).await
+    let bytes = match excepts_chan_file_content {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            if e.kind() == io::ErrorKind::NotFound {
+                log::info!("{} not found. Creating...", excepts_chan_file.display());
+                File::create(excepts_chan_file.clone()).await?;
+                return Ok(BTreeMap::new());
+            } else {
+                log::warn!(
+                    "Could not open {}: {}.",
+                    excepts_chan_file.to_str().unwrap(),
+                    e
+                );
+                return Err(anyhow!(
+                    "Could not open {}: {}.",
+                    excepts_chan_file.to_str().unwrap(),
+                    e
+                ));
+            }
+        }
+    };
+
+    // Current checksummed format.
+    if let Some(payload) = decode_checksummed_records(&bytes).first() {
+        if let Ok(excepts) = serde_json::from_slice::<BTreeMap<ShortChannelId, ExceptChan>>(payload)
+        {
+            return Ok(excepts);
+        }
+    }
+
+    // Backward compatible parsing of the pre-integrity, unwrapped current format.
+    if let Ok(excepts) = serde_json::from_slice::<BTreeMap<ShortChannelId, ExceptChan>>(&bytes) {
+        return Ok(excepts);
+    }
+
+    // Backward compatible parsing of the pre-TTL direction-only map format.
+    if let Ok(excepts) = serde_json::from_slice::<BTreeMap<ShortChannelId, ExceptDirection>>(&bytes)
+    {
+        return Ok(excepts
+            .into_iter()
+            .map(|(scid, direction)| (scid, ExceptChan::permanent(direction)))
+            .collect());
+    }
+
+    // Backward compatible parsing of the oldest flat scid-list format, where every
+    // entry excepted a channel in both directions permanently.
+    if let Ok(legacy) = serde_json::from_slice::<Vec<String>>(&bytes) {
+        let mut excepts = BTreeMap::new();
+        for scid in legacy {
+            match ShortChannelId::from_str(&scid) {
+                Ok(id) => {
+                    excepts.insert(id, ExceptChan::permanent(ExceptDirection::Both));
+                }
+                Err(_e) => {
+                    log::warn!("excepts file contains invalid short_channel_id: {scid}");
+                }
+            }
+        }
+        return Ok(excepts);
+    }
+
+    backup_corrupt_file(&excepts_chan_file).await?;
+    Ok(BTreeMap::new())
 }
-pub async fn read_except_peers(sling_dir: &PathBuf) -> Result<HashSet<PublicKey>, Error> {
+pub async fn read_except_peers(
+    sling_dir: &PathBuf,
+) -> Result<HashMap<PublicKey, Option<u64>>, Error> {
     let excepts_peers_file = sling_dir.join(EXCEPTS_PEERS_FILE_NAME);
-    let excepts_peers_file_content = fs::read_to_string(excepts_peers_file.clone()).await;
+    let excepts_peers_file_content = fs::read(excepts_peers_file.clone()).await;
 
     create_sling_dir(sling_dir).await?;
 
-    parse_excepts(excepts_peers_file_content, excepts_peers_file).await
-}
-async fn parse_excepts<T: FromStr + std::hash::Hash + Eq>(
-    content: Result<String, io::Error>,
-    excepts_file: PathBuf,
-) -> Result<HashSet<T>, Error> {
-    let excepts_tostring: Vec<String>;
-    let mut excepts: HashSet<T> = HashSet::new();
-
-    match content {
-        Ok(file) => excepts_tostring = serde_json::from_str(&file).unwrap_or(Vec::new()),
+    let bytes = match excepts_peers_file_content {
+        Ok(bytes) => bytes,
         Err(e) => {
             if e.kind() == io::ErrorKind::NotFound {
-                log::info!("{} not found. Creating...", excepts_file.display());
-                File::create(excepts_file.clone()).await?;
-                excepts_tostring = Vec::new();
+                log::info!("{} not found. Creating...", excepts_peers_file.display());
+                File::create(excepts_peers_file.clone()).await?;
+                return Ok(HashMap::new());
             } else {
-                log::warn!("Could not open {}: {}.", excepts_file.to_str().unwrap(), e);
+                log::warn!(
+                    "Could not open {}: {}.",
+                    excepts_peers_file.to_str().unwrap(),
+                    e
+                );
                 return Err(anyhow!(
                     "Could not open {}: {}.",
-                    excepts_file.to_str().unwrap(),
+                    excepts_peers_file.to_str().unwrap(),
                     e
                 ));
             }
         }
-    }
+    };
 
-    for except in excepts_tostring {
-        match T::from_str(&except) {
-            Ok(id) => {
-                excepts.insert(id);
+    // Current checksummed format, and its pre-integrity unwrapped equivalent.
+    let list = decode_checksummed_records(&bytes)
+        .first()
+        .and_then(|payload| serde_json::from_slice::<Vec<PeerExcept>>(payload).ok())
+        .or_else(|| serde_json::from_slice::<Vec<PeerExcept>>(&bytes).ok());
+    if let Some(list) = list {
+        let mut excepts = HashMap::new();
+        for entry in list {
+            match PublicKey::from_str(&entry.id) {
+                Ok(id) => {
+                    excepts.insert(id, entry.expires_at);
+                }
+                Err(_e) => {
+                    log::warn!("excepts file contains invalid node_id: {}", entry.id);
+                }
             }
-            Err(_e) => {
-                log::warn!("excepts file contains invalid short_channel_id/node_id: {except}");
+        }
+        return Ok(excepts);
+    }
+
+    // Backward compatible parsing of the pre-TTL flat node_id-list format.
+    if let Ok(legacy) = serde_json::from_slice::<Vec<String>>(&bytes) {
+        let mut excepts = HashMap::new();
+        for id in legacy {
+            match PublicKey::from_str(&id) {
+                Ok(id) => {
+                    excepts.insert(id, None);
+                }
+                Err(_e) => {
+                    log::warn!("excepts file contains invalid node_id: {id}");
+                }
             }
         }
+        return Ok(excepts);
     }
-    Ok(excepts)
+
+    backup_corrupt_file(&excepts_peers_file).await?;
+    Ok(HashMap::new())
 }
 
 pub fn at_or_above_version(my_version: &str, min_version: &str) -> Result<bool, Error> {